@@ -0,0 +1,63 @@
+//! Compares reading UCS-2 strings through `read_fixed_ucs2`
+//! against the naive approach of reading one character at a time.
+//!
+//! Run with `cargo bench --bench ucs2`.
+
+use bytemuncher::{End, Muncher};
+use std::hint::black_box;
+use std::io::Cursor;
+use std::time::{Duration, Instant};
+
+const ITERATIONS: u32 = 200;
+
+fn main() {
+    for char_count in [64, 4 * 1024, 256 * 1024] {
+        let text: String = "bytemuncher 世界 "
+            .chars()
+            .cycle()
+            .take(char_count)
+            .collect();
+        let mut data = Vec::new();
+        Muncher::new(&mut data)
+            .write_pref_ucs2::<u32>(End::Big, &text)
+            .unwrap();
+
+        let naive = bench(|| {
+            let mut muncher = Muncher::new(Cursor::new(&data));
+            let len = muncher.read_be::<u32>().unwrap() as usize;
+            let mut chars = Vec::new();
+            for _ in 0..len {
+                chars.push(muncher.read_be::<u16>().unwrap());
+            }
+            let mut out = Vec::new();
+            ucs2::decode_with(&chars, |bytes| {
+                out.extend_from_slice(bytes);
+                Ok(())
+            })
+            .unwrap();
+            String::from_utf8(out).unwrap()
+        });
+        let bulk = bench(|| {
+            let mut muncher = Muncher::new(Cursor::new(&data));
+            muncher.read_pref_ucs2::<u32>(End::Big).unwrap()
+        });
+
+        println!(
+            "{char_count:>7} chars: per-char {:>10.2?}  bulk {:>10.2?}  ({:.1}x faster)",
+            naive,
+            bulk,
+            naive.as_secs_f64() / bulk.as_secs_f64()
+        );
+    }
+}
+
+/// Average time of one call to `f`.
+fn bench(mut f: impl FnMut() -> String) -> Duration {
+    // Warm up
+    black_box(f());
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        black_box(f());
+    }
+    start.elapsed() / ITERATIONS
+}
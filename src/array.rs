@@ -0,0 +1,136 @@
+use crate::trace::{self, TraceKind};
+use crate::{ByteOrder, End, Muncher, Primitive};
+use std::io::{Error, Read, Write};
+
+/// **Bulk primitive array read methods** (blocking)
+impl<T: Read, O: ByteOrder> Muncher<T, O> {
+    /// Reads `count` values of any [`crate::Primitive`] type
+    /// (such as integers or floats) into a [`Vec`],
+    /// with the endianness specified in the `end` argument.
+    ///
+    /// For the built-in types, this reads the bytes in chunks
+    /// of up to 4 KiB and converts each chunk in one go,
+    /// which is much faster than calling [`Muncher::read_m`]
+    /// in a loop. The conversion is a plain copy when `end` is
+    /// the target's endianness, and only swaps the bytes otherwise.
+    ///
+    /// The total size (`count * size_of::<E>()`) is checked
+    /// against the allocation limit (see [`Muncher::set_max_alloc`]),
+    /// and the [`Vec`] grows in bounded chunks as the data arrives
+    /// (see [`Muncher::set_eager_alloc`]).
+    ///
+    /// For more info on endianness see [`crate::End`].
+    ///
+    /// ```
+    /// # use bytemuncher::{End, Muncher};
+    /// # use std::io::Cursor;
+    /// let mut muncher = Muncher::new(Cursor::new([0, 1, 0, 2, 0, 3]));
+    /// let values = muncher.read_array_m::<u16>(3, End::Big).unwrap();
+    /// assert_eq!(values, [1, 2, 3]);
+    /// ```
+    pub fn read_array_m<E: Primitive + Default + Clone>(
+        &mut self,
+        count: usize,
+        end: End,
+    ) -> Result<Vec<E>, Error> {
+        self.read_array_order(count, end)
+    }
+
+    /// Reads `count` values of any [`crate::Primitive`] type
+    /// (such as integers or floats) into a [`Vec`],
+    /// with the default endianness of the [`Muncher`] (see [`Muncher::set_end`]).
+    ///
+    /// See [`Muncher::read_array_m`] for more info.
+    pub fn read_array<E: Primitive + Default + Clone>(
+        &mut self,
+        count: usize,
+    ) -> Result<Vec<E>, Error> {
+        self.read_array_order(count, self.order)
+    }
+
+    /// Reads an array in any [`ByteOrder`] (traced as [`Muncher::read_array_m`]).
+    pub(crate) fn read_array_order<E: Primitive + Default + Clone>(
+        &mut self,
+        count: usize,
+        order: impl ByteOrder,
+    ) -> Result<Vec<E>, Error> {
+        self.traced(
+            TraceKind::Read,
+            "read_array_m",
+            |values: &Vec<E>| trace::describe_all(values),
+            |m| {
+                m.verify_len(array_size::<E>(count))?;
+                let chunk = m.alloc_chunk(std::mem::size_of::<E>());
+                let mut values = Vec::new();
+                while values.len() < count {
+                    let start = values.len();
+                    values.resize(start + chunk.min(count - start), E::default());
+                    E::read_slice_order(m, &mut values[start..], order)?;
+                }
+                Ok(values)
+            },
+        )
+    }
+
+    /// Reads values of any [`crate::Primitive`] type (such as integers or floats)
+    /// into `out`, filling it completely.
+    ///
+    /// See [`Muncher::read_array_m`] for more info.
+    pub fn read_into_m<E: Primitive>(&mut self, out: &mut [E], end: End) -> Result<(), Error> {
+        self.traced(
+            TraceKind::Read,
+            "read_into_m",
+            |()| None,
+            |m| E::read_slice_endian(m, out, end),
+        )
+    }
+}
+
+/// **Bulk primitive array write methods** (blocking)
+impl<T: Write, O: ByteOrder> Muncher<T, O> {
+    /// Writes all the `values` of any [`crate::Primitive`] type
+    /// (such as integers or floats), with the endianness specified
+    /// in the `end` argument.
+    ///
+    /// For the built-in types, this writes the values in large chunks
+    /// instead of one by one, which is much faster than
+    /// calling [`Muncher::write_m`] in a loop.
+    ///
+    /// For more info on endianness see [`crate::End`].
+    pub fn write_slice_m<E: Primitive + Clone>(
+        &mut self,
+        values: &[E],
+        end: End,
+    ) -> Result<(), Error> {
+        self.write_slice_order(values, end)
+    }
+
+    /// Writes all the `values` of any [`crate::Primitive`] type
+    /// (such as integers or floats), with the default endianness
+    /// of the [`Muncher`] (see [`Muncher::set_end`]).
+    ///
+    /// See [`Muncher::write_slice_m`] for more info.
+    pub fn write_slice<E: Primitive + Clone>(&mut self, values: &[E]) -> Result<(), Error> {
+        self.write_slice_order(values, self.order)
+    }
+
+    /// Writes values in any [`ByteOrder`] (traced as [`Muncher::write_slice_m`]).
+    pub(crate) fn write_slice_order<E: Primitive + Clone>(
+        &mut self,
+        values: &[E],
+        order: impl ByteOrder,
+    ) -> Result<(), Error> {
+        self.traced(
+            TraceKind::Write,
+            "write_slice_m",
+            |()| trace::describe_all(values),
+            |m| E::write_slice_order(values, m, order),
+        )
+    }
+}
+
+/// Size in bytes of `count` values of `E`,
+/// saturating so that overflows fail the allocation limit.
+fn array_size<E>(count: usize) -> usize {
+    count.saturating_mul(std::mem::size_of::<E>())
+}
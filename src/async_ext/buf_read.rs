@@ -0,0 +1,252 @@
+use std::io::{Error, ErrorKind};
+
+use super::AsyncMunchRead;
+use super::sealed::FillLookahead;
+use super::traced_a;
+use crate::backend::{AsyncBufSource, Backend};
+use crate::string::{bytes, string};
+use crate::trace::TraceKind;
+use crate::{AsyncPrimitive, ByteOrder, End, Muncher};
+
+/// **Async read methods that require a buffered reader**
+///
+/// Implemented for every [`Muncher`] whose inner reader is
+/// a buffered async reader of backend `B` (see [`crate::backend`]).
+/// These mirror the blocking methods that require `T: `[`std::io::BufRead`].
+// See `AsyncMunchRead` on `async_fn_in_trait`.
+#[allow(async_fn_in_trait)]
+pub trait AsyncMunchBufRead<B: Backend>: AsyncMunchRead<B> + AsyncBufSource<B> {
+    // ================================
+    // Strings
+    // ================================
+
+    /// Reads a C-style string (ending with `\0` null byte)
+    /// into a buffer of bytes (`Vec<u8>`).
+    ///
+    /// **The resulting buffer does not include a null byte!
+    /// Add one on your own if necessary!**
+    ///
+    /// If you want the UTF-8 format (unicode, extension of ASCII, widely used),
+    /// see [`Muncher::read_cstr_utf8`].
+    async fn read_cstr_bytes_a(&mut self) -> Result<Vec<u8>, Error> {
+        traced_a(
+            self,
+            TraceKind::Read,
+            "read_cstr_bytes_a",
+            bytes,
+            read_cstr_a,
+        )
+        .await
+    }
+
+    /// Reads a C-style string (ending with `\0` null byte)
+    /// in the UTF-8 format (tries to parse, fails if invalid).
+    ///
+    /// If you want bytes or some other format,
+    /// see [`Muncher::read_cstr_bytes`].
+    async fn read_cstr_utf8_a(&mut self) -> Result<String, Error> {
+        traced_a(
+            self,
+            TraceKind::Read,
+            "read_cstr_utf8_a",
+            string,
+            async |m| crate::string::bytes2utf8(read_cstr_a(m).await?),
+        )
+        .await
+    }
+
+    /// Reads bytes until the specified delimiter is encountered.
+    ///
+    /// Essentially it reads bytes again and again until it hits a byte that
+    /// is the `delim` argument. The resulting buffer will include the delimiter
+    /// byte if found.
+    async fn read_delim_bytes_a(&mut self, delim: u8) -> Result<Vec<u8>, Error> {
+        traced_a(
+            self,
+            TraceKind::Read,
+            "read_delim_bytes_a",
+            bytes,
+            async |m| read_until_a(m, delim).await,
+        )
+        .await
+    }
+
+    /// Reads a C-style string (ending with `\0` null byte)
+    /// in the MUTF-8 format (tries to parse, fails if invalid).
+    ///
+    /// See [`Muncher::read_cstr_mutf8`] for more info.
+    #[cfg(feature = "mutf8")]
+    async fn read_cstr_mutf8_a(&mut self) -> Result<String, crate::MutfError> {
+        traced_a(
+            self,
+            TraceKind::Read,
+            "read_cstr_mutf8_a",
+            string,
+            async |m| crate::mutf::mutf2utf(&read_cstr_a(m).await?),
+        )
+        .await
+    }
+
+    // ================================
+    // Clean end-of-input detection
+    // ================================
+
+    /// Returns `true` if there's nothing more to read,
+    /// without consuming anything.
+    ///
+    /// See [`Muncher::is_eof`] for more info.
+    async fn is_eof_a(&mut self) -> Result<bool, Error> {
+        Ok(self.fill_buf().await?.is_empty())
+    }
+
+    /// Runs `parse` unless the input has ended, in which case it returns `Ok(None)`.
+    ///
+    /// See [`Muncher::try_read_with`] for more info.
+    async fn try_read_with_a<R, Err: From<Error>>(
+        &mut self,
+        parse: impl AsyncFnOnce(&mut Self) -> Result<R, Err>,
+    ) -> Result<Option<R>, Err> {
+        if self.is_eof_a().await? {
+            return Ok(None);
+        }
+        parse(self).await.map(Some)
+    }
+
+    /// Like [`AsyncMunchRead::read_m_a`], but returns `Ok(None)` at the end of input.
+    async fn try_read_m_a<E: AsyncPrimitive>(&mut self, end: End) -> Result<Option<E>, Error> {
+        self.try_read_with_a(async |m| m.read_m_a(end).await).await
+    }
+
+    /// Like [`AsyncMunchRead::read_a`], but returns `Ok(None)` at the end of input.
+    async fn try_read_a<E: AsyncPrimitive>(&mut self) -> Result<Option<E>, Error> {
+        let end = self.muncher().end();
+        self.try_read_m_a(end).await
+    }
+
+    /// Like [`AsyncMunchRead::read_le_a`], but returns `Ok(None)` at the end of input.
+    async fn try_read_le_a<E: AsyncPrimitive>(&mut self) -> Result<Option<E>, Error> {
+        self.try_read_m_a(End::Little).await
+    }
+
+    /// Like [`AsyncMunchRead::read_be_a`], but returns `Ok(None)` at the end of input.
+    async fn try_read_be_a<E: AsyncPrimitive>(&mut self) -> Result<Option<E>, Error> {
+        self.try_read_m_a(End::Big).await
+    }
+
+    /// Like [`AsyncMunchRead::read_ne_a`], but returns `Ok(None)` at the end of input.
+    async fn try_read_ne_a<E: AsyncPrimitive>(&mut self) -> Result<Option<E>, Error> {
+        self.try_read_m_a(End::Native).await
+    }
+
+    /// Like [`AsyncMunchRead::read_pref_bytes_a`], but returns `Ok(None)` at the end of input.
+    async fn try_read_pref_bytes_a<E: AsyncPrimitive>(
+        &mut self,
+        end: impl Into<Option<End>>,
+    ) -> Result<Option<Vec<u8>>, Error> {
+        self.try_read_with_a(async |m| m.read_pref_bytes_a::<E>(end).await)
+            .await
+    }
+
+    /// Like [`AsyncMunchRead::read_pref_utf8_a`], but returns `Ok(None)` at the end of input.
+    async fn try_read_pref_utf8_a<E: AsyncPrimitive>(
+        &mut self,
+        end: impl Into<Option<End>>,
+    ) -> Result<Option<String>, Error> {
+        self.try_read_with_a(async |m| m.read_pref_utf8_a::<E>(end).await)
+            .await
+    }
+
+    /// Like [`AsyncMunchBufRead::read_cstr_bytes_a`], but returns `Ok(None)` at the end of input.
+    async fn try_read_cstr_bytes_a(&mut self) -> Result<Option<Vec<u8>>, Error> {
+        self.try_read_with_a(async |m| m.read_cstr_bytes_a().await)
+            .await
+    }
+
+    /// Like [`AsyncMunchBufRead::read_cstr_utf8_a`], but returns `Ok(None)` at the end of input.
+    async fn try_read_cstr_utf8_a(&mut self) -> Result<Option<String>, Error> {
+        self.try_read_with_a(async |m| m.read_cstr_utf8_a().await)
+            .await
+    }
+
+    // ================================
+    // Record iteration
+    // ================================
+
+    /// Returns a [`futures_util::Stream`] that calls `parse` for each record
+    /// until the input ends.
+    ///
+    /// See [`Muncher::records`] for more info.
+    fn records_a<'a, R, F: AsyncFnMut(&mut Self) -> Result<R, Error> + 'a>(
+        &'a mut self,
+        parse: F,
+    ) -> impl futures_util::Stream<Item = Result<R, Error>> + 'a {
+        futures_util::stream::unfold(Some((self, parse)), async |state| {
+            let (muncher, mut parse) = state?;
+            let start = muncher.muncher().position();
+            let result = match muncher.is_eof_a().await {
+                Ok(true) => return None,
+                Ok(false) => parse(muncher)
+                    .await
+                    .map_err(|e| crate::records::truncated(e, start)),
+                Err(e) => Err(e),
+            };
+            let next = result.is_ok().then_some((muncher, parse));
+            Some((result, next))
+        })
+    }
+}
+
+impl<B: Backend, T, O: ByteOrder> AsyncMunchBufRead<B> for Muncher<T, O> where
+    Self: AsyncBufSource<B> + FillLookahead<B>
+{
+}
+
+/// Reads a C-style string, without the null terminator.
+async fn read_cstr_a<B: Backend, M: AsyncMunchRead<B>>(muncher: &mut M) -> Result<Vec<u8>, Error> {
+    let mut buf = read_until_a(muncher, 0).await?;
+    if buf.is_empty() {
+        return Err(Error::new(
+            ErrorKind::UnexpectedEof,
+            "EOF reached before null terminator",
+        ));
+    }
+
+    // Remove the null terminator
+    if buf.last() == Some(&0) {
+        buf.pop();
+    }
+    Ok(buf)
+}
+
+/// Reads bytes until the `delim` byte (included) or the end of input,
+/// through the look-ahead buffer of a [`Muncher`].
+///
+/// The bytes are only consumed once `delim` (or the end) is found,
+/// so this is cancellation safe.
+async fn read_until_a<B: Backend, M: AsyncMunchRead<B>>(
+    muncher: &mut M,
+    delim: u8,
+) -> Result<Vec<u8>, Error> {
+    muncher.begin_read();
+    // How much of the look-ahead buffer is known to not have `delim`.
+    let mut searched = 0;
+    let len = loop {
+        let m = muncher.muncher();
+        if let Some(i) = m.lookahead[searched..].iter().position(|&b| b == delim) {
+            break searched + i + 1;
+        }
+        searched = m.lookahead.len();
+        // Checked as it grows, so input without `delim` can't
+        // make the look-ahead buffer grow past the limits.
+        m.check_len(searched)?;
+        let chunk = m.alloc_chunk(1);
+        if muncher.fill_lookahead(chunk).await? == 0 {
+            break searched;
+        }
+    };
+    let m = muncher.muncher();
+    m.verify_len(len)?;
+    let bytes = m.lookahead[..len].to_vec();
+    m.consume_lookahead(len);
+    Ok(bytes)
+}
@@ -0,0 +1,148 @@
+//! The async methods of [`Muncher`], as extension traits
+//! that are generic over the backend (see [`crate::backend`]).
+//!
+//! This is what lets the `tokio` and `futures` features be enabled together:
+//! a [`Muncher`] around a `tokio` reader gets `AsyncMunchRead<Tokio>`,
+//! and one around a `futures` reader gets `AsyncMunchRead<Futures>`,
+//! with the same method names.
+//!
+//! # Cancellation safety
+//!
+//! The reads keep the bytes of a value in the [`Muncher`]'s look-ahead buffer
+//! (the same one used by [`Muncher::peek_bytes`]) until the whole value
+//! has arrived, and only then consume them. So if a read future is dropped
+//! (like a losing branch of `tokio::select!`), nothing is lost: the next read
+//! starts from the same place, with the bytes that already arrived.
+
+use std::fmt::Display;
+use std::io::{Error, ErrorKind};
+
+#[cfg(feature = "futures")]
+use crate::backend::Futures;
+#[cfg(feature = "tokio")]
+use crate::backend::Tokio;
+use crate::backend::{AsyncSource, Backend};
+use crate::trace::TraceKind;
+use crate::{ByteOrder, Muncher};
+
+mod buf_read;
+mod read;
+mod write;
+
+pub use buf_read::AsyncMunchBufRead;
+pub use read::AsyncMunchRead;
+pub use write::AsyncMunchWrite;
+
+mod sealed {
+    use crate::{ByteOrder, Muncher};
+
+    /// Gives the default methods of the extension traits
+    /// access to the [`Muncher`] they're implemented for.
+    pub trait Sealed: Sized {
+        type Inner;
+        type Order: ByteOrder;
+
+        fn muncher(&mut self) -> &mut Muncher<Self::Inner, Self::Order>;
+    }
+
+    /// Reads from the inner reader of a [`Muncher`], into its look-ahead buffer.
+    pub trait FillLookahead<B: crate::backend::Backend>: Sealed {
+        /// Starts the clock of the read timeout (see `Muncher::set_read_timeout`),
+        /// called at the start of every read.
+        fn begin_read(&mut self);
+
+        /// Appends up to `max` bytes, returning how many (`0` at the end of input).
+        /// Cancellation safe.
+        fn fill_lookahead(&mut self, max: usize) -> impl Future<Output = std::io::Result<usize>>;
+    }
+}
+
+impl<T, O: ByteOrder> sealed::Sealed for Muncher<T, O> {
+    type Inner = T;
+    type Order = O;
+
+    fn muncher(&mut self) -> &mut Muncher<T, O> {
+        self
+    }
+}
+
+impl<T, O: ByteOrder> Muncher<T, O> {
+    /// Consumes the first `len` bytes of the look-ahead buffer.
+    fn consume_lookahead(&mut self, len: usize) {
+        self.observers.read(&self.lookahead[..len]);
+        self.record_consumed(len);
+        self.lookahead.consume(len);
+    }
+}
+
+/// Runs `run` as one traced method (the async version of `Muncher::traced`).
+///
+/// Unlike the blocking version, this doesn't hide the traced methods that `run`
+/// calls (a flag for that would get stuck if the future was dropped),
+/// so `run` must not call any.
+async fn traced_a<M: sealed::Sealed, R, Err: Display>(
+    muncher: &mut M,
+    kind: TraceKind,
+    op: &'static str,
+    describe: impl FnOnce(&R) -> Option<String>,
+    run: impl AsyncFnOnce(&mut M) -> Result<R, Err>,
+) -> Result<R, Err> {
+    let Some(start) = muncher.muncher().trace_start(kind) else {
+        return run(muncher).await;
+    };
+    let result = run(muncher).await;
+    let (value, error) = match &result {
+        Ok(value) => (describe(value), None),
+        Err(error) => (None, Some(error.to_string())),
+    };
+    muncher.muncher().trace_end(start, op, value, error);
+    result
+}
+
+#[cfg(feature = "tokio")]
+impl<T: AsyncSource<Tokio>, O: ByteOrder> sealed::FillLookahead<Tokio> for Muncher<T, O> {
+    fn begin_read(&mut self) {
+        Muncher::begin_read(self);
+    }
+
+    async fn fill_lookahead(&mut self, max: usize) -> Result<usize, Error> {
+        let Some(deadline) = self.timeouts.current else {
+            return fill_lookahead(self, max).await;
+        };
+        match tokio::time::timeout_at(deadline, fill_lookahead(self, max)).await {
+            Ok(result) => result,
+            Err(_) => Err(self.timed_out()),
+        }
+    }
+}
+
+#[cfg(feature = "futures")]
+impl<T: AsyncSource<Futures>, O: ByteOrder> sealed::FillLookahead<Futures> for Muncher<T, O> {
+    fn begin_read(&mut self) {}
+
+    async fn fill_lookahead(&mut self, max: usize) -> Result<usize, Error> {
+        fill_lookahead(self, max).await
+    }
+}
+
+async fn fill_lookahead<B: Backend, T: AsyncSource<B>, O: ByteOrder>(
+    muncher: &mut Muncher<T, O>,
+    max: usize,
+) -> Result<usize, Error> {
+    let buffered = muncher.lookahead.len();
+    let allowed = muncher
+        .read_allowance(buffered + max)?
+        .saturating_sub(buffered);
+    if allowed == 0 {
+        return Err(Error::new(
+            ErrorKind::QuotaExceeded,
+            "read budget exceeded: the rest of the budget is already buffered",
+        ));
+    }
+    // Read into a separate buffer, so that nothing changes
+    // if this is dropped halfway.
+    let mut buf = vec![0; allowed];
+    let n = muncher.inner.read_some(&mut buf).await?;
+    muncher.lookahead.extend_from_slice(&buf[..n]);
+    Ok(n)
+}
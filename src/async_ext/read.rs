@@ -0,0 +1,501 @@
+use std::io::Error;
+
+use std::io::ErrorKind;
+
+use super::sealed::{FillLookahead, Sealed};
+use super::traced_a;
+use crate::backend::{AsyncSource, Backend};
+#[cfg(feature = "ucs2")]
+use crate::sans_io::Ucs2;
+use crate::sans_io::{Bytes, Decode, Parsed, Prefixed, Value, decode_slice};
+use crate::string::{bytes, string};
+use crate::trace::{self, TraceKind};
+use crate::{AsyncPrimitive, ByteOrder, End, Muncher};
+
+/// **Async read methods**
+///
+/// Implemented for every [`Muncher`] whose inner reader is
+/// an async reader of backend `B` (see [`crate::backend`]).
+/// These mirror the blocking methods, with `_a` added to the end of their names.
+///
+/// # Cancellation safety
+///
+/// Reads of a single value (primitives, strings, arrays of built-in types)
+/// are cancellation safe: the bytes are kept in the [`Muncher`] until the
+/// whole value has arrived. So if a read is dropped (like a losing branch
+/// of `tokio::select!`), the next read starts from the same place.
+/// Reads made of several values (like [`AsyncMunchRead::read_pref_vec_a`])
+/// are only safe between values.
+///
+/// ```
+/// # #[cfg(feature = "futures")]
+/// # futures::executor::block_on(async {
+/// use bytemuncher::{AsyncMunchRead, End, Muncher};
+/// use futures::io::Cursor;
+///
+/// let mut muncher = Muncher::new(Cursor::new(b"\x00\x02hi"));
+/// assert_eq!(muncher.read_pref_utf8_a::<u16>(End::Big).await.unwrap(), "hi");
+/// # });
+/// ```
+// The futures are `Send` whenever the inner reader is,
+// callers just can't require that in generic code.
+#[allow(async_fn_in_trait)]
+pub trait AsyncMunchRead<B: Backend>: AsyncSource<B> + Sealed + FillLookahead<B> {
+    // ================================
+    // Primitives
+    // ================================
+
+    /// Reads any [`crate::AsyncPrimitive`] type (such as integers or floats),
+    /// with the endianness specified in the `end` argument.
+    ///
+    /// For more info on endianness see [`crate::End`].
+    async fn read_m_a<E: AsyncPrimitive>(&mut self, end: End) -> Result<E, Error> {
+        traced_a(self, TraceKind::Read, "read_m_a", E::describe, async |m| {
+            decode_a(m, || Value::new(end)).await
+        })
+        .await
+    }
+
+    /// Reads any [`crate::AsyncPrimitive`] type (such as integers or floats),
+    /// with the default endianness of the [`Muncher`] (see [`Muncher::set_end`]).
+    ///
+    /// For more info on endianness see [`crate::End`].
+    async fn read_a<E: AsyncPrimitive>(&mut self) -> Result<E, Error> {
+        let end = self.muncher().end();
+        self.read_m_a(end).await
+    }
+
+    /// Reads any [`crate::AsyncPrimitive`] type (such as integers or floats),
+    /// as little endian.
+    ///
+    /// For more info on endianness see [`crate::End`].
+    async fn read_le_a<E: AsyncPrimitive>(&mut self) -> Result<E, Error> {
+        self.read_m_a(End::Little).await
+    }
+
+    /// Reads any [`crate::AsyncPrimitive`] type (such as integers or floats),
+    /// as big endian.
+    ///
+    /// For more info on endianness see [`crate::End`].
+    async fn read_be_a<E: AsyncPrimitive>(&mut self) -> Result<E, Error> {
+        self.read_m_a(End::Big).await
+    }
+
+    /// Reads any [`crate::AsyncPrimitive`] type (such as integers or floats),
+    /// as native endian (as per the target platform).
+    ///
+    /// For more info on endianness see [`crate::End`].
+    async fn read_ne_a<E: AsyncPrimitive>(&mut self) -> Result<E, Error> {
+        self.read_m_a(End::Native).await
+    }
+
+    // ================================
+    // Arrays and collections
+    // ================================
+
+    /// Reads `count` values of any [`crate::AsyncPrimitive`] type
+    /// (such as integers or floats) into a [`Vec`].
+    ///
+    /// See [`Muncher::read_array_m`] for more info.
+    async fn read_array_m_a<E: AsyncPrimitive + Default + Clone>(
+        &mut self,
+        count: usize,
+        end: End,
+    ) -> Result<Vec<E>, Error> {
+        traced_a(
+            self,
+            TraceKind::Read,
+            "read_array_m_a",
+            |values: &Vec<E>| trace::describe_all(values),
+            async |m| match E::SIZE {
+                Some(size) => decode_a(m, || array(count, size, end)).await,
+                None => {
+                    let muncher = m.muncher();
+                    muncher.verify_len(count.saturating_mul(std::mem::size_of::<E>()))?;
+                    let mut values = Vec::with_capacity(count.min(muncher.alloc_chunk(1)));
+                    for _ in 0..count {
+                        values.push(decode_a(m, || Value::new(end)).await?);
+                    }
+                    Ok(values)
+                }
+            },
+        )
+        .await
+    }
+
+    /// Reads values of any [`crate::AsyncPrimitive`] type
+    /// (such as integers or floats) into `out`, filling it completely.
+    ///
+    /// See [`Muncher::read_array_m`] for more info.
+    async fn read_into_m_a<E: AsyncPrimitive>(
+        &mut self,
+        out: &mut [E],
+        end: End,
+    ) -> Result<(), Error> {
+        traced_a(
+            self,
+            TraceKind::Read,
+            "read_into_m_a",
+            |()| None,
+            async |m| {
+                let Some(size) = E::SIZE else {
+                    for value in out {
+                        *value = decode_a(m, || Value::new(end)).await?;
+                    }
+                    return Ok(());
+                };
+                let len = out.len().saturating_mul(size);
+                // `out` is already allocated, so this isn't checked against the limits.
+                m.begin_read();
+                while m.muncher().lookahead.len() < len {
+                    let missing = len - m.muncher().lookahead.len();
+                    let chunk = m.muncher().alloc_chunk(1);
+                    fill_a(m, missing.min(chunk)).await?;
+                }
+                let muncher = m.muncher();
+                E::read_slice_endian(&mut &muncher.lookahead[..len], out, end)?;
+                muncher.consume_lookahead(len);
+                Ok(())
+            },
+        )
+        .await
+    }
+
+    /// Reads a collection prefixed by a count (number of elements) of type `<Len>`,
+    /// calling `parse` to read each element.
+    ///
+    /// See [`Muncher::read_pref_vec`] for more info.
+    async fn read_pref_vec_a<Len: AsyncPrimitive, Elem>(
+        &mut self,
+        end: impl Into<Option<End>>,
+        mut parse: impl AsyncFnMut(&mut Self) -> Result<Elem, Error>,
+    ) -> Result<Vec<Elem>, Error> {
+        let end = self.muncher().resolve_end(end);
+        let count = self.read_m_a::<Len>(end).await?.into_usize();
+        let muncher = self.muncher();
+        muncher.check_len(count.saturating_mul(std::mem::size_of::<Elem>()))?;
+        let mut values =
+            Vec::with_capacity(count.min(muncher.alloc_chunk(std::mem::size_of::<Elem>())));
+        for _ in 0..count {
+            values.push(parse(self).await?);
+        }
+        Ok(values)
+    }
+
+    /// Reads an array of any [`crate::AsyncPrimitive`] type
+    /// prefixed by a count (number of elements) of type `<Len>`.
+    ///
+    /// See [`Muncher::read_pref_array_m`] for more info.
+    async fn read_pref_array_m_a<Len: AsyncPrimitive, E: AsyncPrimitive + Default + Clone>(
+        &mut self,
+        end: impl Into<Option<End>>,
+    ) -> Result<Vec<E>, Error> {
+        let end = self.muncher().resolve_end(end);
+        match E::SIZE {
+            Some(size) => {
+                traced_a(
+                    self,
+                    TraceKind::Read,
+                    "read_pref_array_m_a",
+                    |values: &Vec<E>| trace::describe_all(values),
+                    async |m| {
+                        decode_a(m, || {
+                            Prefixed::<Len, _, _, _>::new(end, move |count| array(count, size, end))
+                        })
+                        .await
+                    },
+                )
+                .await
+            }
+            None => {
+                let count = self.read_m_a::<Len>(end).await?.into_usize();
+                self.read_array_m_a(count, end).await
+            }
+        }
+    }
+
+    // ================================
+    // Strings
+    // ================================
+
+    /// Reads some bytes prefixed by a length (number of bytes) of type `<E>`.
+    ///
+    /// Through the `end` argument you can choose the endianness of the length field,
+    /// or pass `None` to use the default endianness (see [`Muncher::set_end`]).
+    ///
+    /// For more info on endianness see [`crate::End`].
+    async fn read_pref_bytes_a<E: AsyncPrimitive>(
+        &mut self,
+        end: impl Into<Option<End>>,
+    ) -> Result<Vec<u8>, Error> {
+        let end = self.muncher().resolve_end(end);
+        traced_a(
+            self,
+            TraceKind::Read,
+            "read_pref_bytes_a",
+            bytes,
+            async |m| decode_a(m, || Prefixed::<E, _, _>::new(end, Bytes::new)).await,
+        )
+        .await
+    }
+
+    /// Reads `len` number of bytes into a `Vec<u8>`
+    ///
+    /// See [`Muncher::read_fixed_bytes`] for more info.
+    async fn read_fixed_bytes_a(&mut self, len: usize) -> Result<Vec<u8>, Error> {
+        traced_a(
+            self,
+            TraceKind::Read,
+            "read_fixed_bytes_a",
+            bytes,
+            async |m| decode_a(m, || Bytes::new(len)).await,
+        )
+        .await
+    }
+
+    /// Reads a UTF-8 string prefixed by a length (number of bytes) of type `<E>`.
+    ///
+    /// Through the `end` argument you can choose the endianness of the length field,
+    /// or pass `None` to use the default endianness (see [`Muncher::set_end`]).
+    ///
+    /// For more info on endianness see [`crate::End`].
+    async fn read_pref_utf8_a<E: AsyncPrimitive>(
+        &mut self,
+        end: impl Into<Option<End>>,
+    ) -> Result<String, Error> {
+        let end = self.muncher().resolve_end(end);
+        traced_a(
+            self,
+            TraceKind::Read,
+            "read_pref_utf8_a",
+            string,
+            async |m| {
+                decode_a(m, || {
+                    Prefixed::<E, _, _>::new(end, Bytes::new).map(crate::string::bytes2utf8)
+                })
+                .await
+            },
+        )
+        .await
+    }
+
+    /// Reads `len` number of bytes into a UTF-8 [`String`].
+    async fn read_fixed_utf8_a(&mut self, len: usize) -> Result<String, Error> {
+        traced_a(
+            self,
+            TraceKind::Read,
+            "read_fixed_utf8_a",
+            string,
+            async |m| decode_a(m, || Bytes::new(len).map(crate::string::bytes2utf8)).await,
+        )
+        .await
+    }
+
+    /// Reads a UCS-2 string prefixed by a length (number of characters) in the type `<E>`.
+    ///
+    /// UCS-2 consists of big endian 16-bit words, each of which represent a Unicode
+    /// code point between U+0000 and U+FFFF inclusive.
+    ///
+    /// Through the `end` argument you can choose the endianness of the length field,
+    /// or pass `None` to use the default endianness (see [`Muncher::set_end`]).
+    ///
+    /// For more info on endianness see [`crate::End`].
+    #[cfg(feature = "ucs2")]
+    async fn read_pref_ucs2_a<E: AsyncPrimitive>(
+        &mut self,
+        end: impl Into<Option<End>>,
+    ) -> Result<String, Error> {
+        let end = self.muncher().resolve_end(end);
+        traced_a(
+            self,
+            TraceKind::Read,
+            "read_pref_ucs2_a",
+            string,
+            async |m| decode_a(m, || Prefixed::<E, _, _>::new(end, Ucs2::new)).await,
+        )
+        .await
+    }
+
+    /// Reads `char_count` number of 16-bit characters as a UCS-2 string,
+    /// and converts it to UTF-8 [`String`].
+    #[cfg(feature = "ucs2")]
+    async fn read_fixed_ucs2_a(&mut self, char_count: usize) -> Result<String, Error> {
+        traced_a(
+            self,
+            TraceKind::Read,
+            "read_fixed_ucs2_a",
+            string,
+            async |m| decode_a(m, || Ucs2::new(char_count)).await,
+        )
+        .await
+    }
+
+    /// Reads a MUTF-8 string prefixed by a length (number of bytes) of type `<E>`,
+    /// then converts it to a UTF-8 [`String`].
+    ///
+    /// See [`Muncher::read_pref_mutf8`] for more info.
+    #[cfg(feature = "mutf8")]
+    async fn read_pref_mutf8_a<E: AsyncPrimitive>(
+        &mut self,
+        end: impl Into<Option<End>>,
+    ) -> Result<String, crate::MutfError> {
+        let end = self.muncher().resolve_end(end);
+        traced_a(
+            self,
+            TraceKind::Read,
+            "read_pref_mutf8_a",
+            string,
+            async |m| {
+                let buf = decode_a(m, || Prefixed::<E, _, _>::new(end, Bytes::new)).await?;
+                crate::mutf::mutf2utf(&buf)
+            },
+        )
+        .await
+    }
+
+    /// Reads `len` number of bytes into a MUTF-8 string,
+    /// then converts it to a UTF-8 [`String`].
+    ///
+    /// See [`Muncher::read_fixed_mutf8`] for more info.
+    #[cfg(feature = "mutf8")]
+    async fn read_fixed_mutf8_a(&mut self, len: usize) -> Result<String, crate::MutfError> {
+        traced_a(
+            self,
+            TraceKind::Read,
+            "read_fixed_mutf8_a",
+            string,
+            async |m| crate::mutf::mutf2utf(&decode_a(m, || Bytes::new(len)).await?),
+        )
+        .await
+    }
+
+    // ================================
+    // Validation and detection
+    // ================================
+
+    /// Reads `expected.len()` bytes and checks that they
+    /// are exactly equal to `expected`.
+    ///
+    /// See [`Muncher::expect_bytes`] for more info.
+    async fn expect_bytes_a(&mut self, expected: &[u8]) -> Result<(), Error> {
+        let actual = self.read_fixed_bytes_a(expected.len()).await?;
+        crate::expect::check_bytes(expected, &actual)
+    }
+
+    /// Reads any [`crate::AsyncPrimitive`] type and checks
+    /// that it's equal to `expected`.
+    ///
+    /// See [`Muncher::expect`] for more info.
+    async fn expect_a<E: AsyncPrimitive + PartialEq>(
+        &mut self,
+        expected: E,
+        end: End,
+    ) -> Result<(), Error> {
+        let actual = self.read_m_a::<E>(end).await?;
+        crate::expect::check_value(expected, actual, end)
+    }
+
+    /// Checks the upcoming bytes against multiple possible magic numbers,
+    /// returning the index (in `candidates`) of the one that matched.
+    ///
+    /// See [`Muncher::expect_one_of`] for more info.
+    async fn expect_one_of_a(&mut self, candidates: &[&[u8]]) -> Result<usize, Error> {
+        use crate::expect::{OneOf, check_one_of, no_match};
+
+        self.begin_read();
+        let mut at_end = false;
+        loop {
+            let m = self.muncher();
+            match check_one_of(candidates, &m.lookahead, at_end) {
+                OneOf::Matched(i) => {
+                    self.read_fixed_bytes_a(candidates[i].len()).await?;
+                    return Ok(i);
+                }
+                OneOf::NoMatch => return Err(no_match(candidates, &m.lookahead)),
+                OneOf::NeedMore(len) => at_end = self.fill_lookahead(len).await? == 0,
+            }
+        }
+    }
+
+    /// Reads a marker that is `le` in little endian files
+    /// and `be` in big endian files, returning the matching endianness.
+    ///
+    /// Only available with the runtime [`End`] byte order (the default).
+    ///
+    /// See [`Muncher::detect_end_from_magic`] for more info.
+    async fn detect_end_from_magic_a(&mut self, le: &[u8], be: &[u8]) -> Result<End, Error>
+    where
+        Self: Sealed<Order = End>,
+    {
+        let end = crate::detect::magic_end(self.expect_one_of_a(&[le, be]).await?);
+        self.muncher().order = end;
+        Ok(end)
+    }
+}
+
+impl<B: Backend, T, O: ByteOrder> AsyncMunchRead<B> for Muncher<T, O> where
+    Self: AsyncSource<B> + FillLookahead<B>
+{
+}
+
+/// Runs the decoder made by `decoder` on the look-ahead buffer of a [`Muncher`],
+/// filling it from the inner reader until the value is complete,
+/// within the allocation limits (the async version of `Muncher::decode`).
+///
+/// The bytes are only consumed once the value is complete,
+/// so this is cancellation safe (the decoder starts over on the next call).
+pub(super) async fn decode_a<B: Backend, M: AsyncMunchRead<B>, D: Decode>(
+    muncher: &mut M,
+    decoder: impl Fn() -> D,
+) -> Result<D::Output, Error> {
+    muncher.begin_read();
+    loop {
+        let m = muncher.muncher();
+        let mut checked = 0;
+        let parsed = decode_slice(&m.lookahead, decoder(), |len| {
+            m.verify_max_alloc(len)?;
+            checked += len;
+            m.check_alloc(checked)
+        })?;
+        match parsed {
+            Parsed::Done((value, used)) => {
+                m.charge_alloc(checked)?;
+                m.consume_lookahead(used);
+                return Ok(value);
+            }
+            Parsed::Incomplete { needed } => {
+                // Grow in bounded chunks (see `Muncher::set_eager_alloc`).
+                let chunk = m.alloc_chunk(1);
+                fill_a(muncher, needed.min(chunk)).await?;
+            }
+        }
+    }
+}
+
+/// Reads up to `max` more bytes into the look-ahead buffer,
+/// failing at the end of input.
+async fn fill_a<B: Backend, M: AsyncMunchRead<B>>(
+    muncher: &mut M,
+    max: usize,
+) -> Result<(), Error> {
+    if muncher.fill_lookahead(max).await? == 0 {
+        return Err(Error::new(
+            ErrorKind::UnexpectedEof,
+            "failed to fill whole buffer",
+        ));
+    }
+    Ok(())
+}
+
+/// Decodes `count` values of `size` bytes each, all at once.
+fn array<E: crate::Primitive + Default + Clone>(
+    count: usize,
+    size: usize,
+    end: End,
+) -> impl Decode<Output = Vec<E>> {
+    Bytes::new(count.saturating_mul(size)).map(move |bytes| {
+        let mut values = vec![E::default(); count];
+        E::read_slice_endian(&mut &bytes[..], &mut values, end)?;
+        Ok(values)
+    })
+}
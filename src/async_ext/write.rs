@@ -0,0 +1,264 @@
+use std::io::Error;
+
+use super::sealed::Sealed;
+use super::traced_a;
+use crate::backend::{AsyncSink, Backend};
+use crate::sans_io::Encoded;
+use crate::trace::{self, TraceKind};
+use crate::{AsyncPrimitive, ByteOrder, End, Muncher};
+
+/// **Async write methods**
+///
+/// Implemented for every [`Muncher`] whose inner writer is
+/// an async writer of backend `B` (see [`crate::backend`]).
+/// These mirror the blocking methods, with `_a` added to the end of their names.
+// See `AsyncMunchRead` on `async_fn_in_trait`.
+#[allow(async_fn_in_trait)]
+pub trait AsyncMunchWrite<B: Backend>: AsyncSink<B> + Sealed {
+    // ================================
+    // Primitives
+    // ================================
+
+    /// Writes any [`crate::AsyncPrimitive`] type (such as integers or floats),
+    /// with the endianness specified in the `end` argument.
+    ///
+    /// For more info on endianness see [`crate::End`].
+    async fn write_m_a<E: AsyncPrimitive>(&mut self, value: E, end: End) -> Result<(), Error> {
+        let described = self
+            .muncher()
+            .is_tracing()
+            .then(|| value.describe())
+            .flatten();
+        traced_a(
+            self,
+            TraceKind::Write,
+            "write_m_a",
+            |()| described,
+            async |m| value.write_endian_a::<B>(m, end).await,
+        )
+        .await
+    }
+
+    /// Writes any [`crate::AsyncPrimitive`] type (such as integers or floats),
+    /// with the default endianness of the [`Muncher`] (see [`Muncher::set_end`]).
+    ///
+    /// For more info on endianness see [`crate::End`].
+    async fn write_a<E: AsyncPrimitive>(&mut self, value: E) -> Result<(), Error> {
+        let end = self.muncher().end();
+        self.write_m_a(value, end).await
+    }
+
+    /// Writes any [`crate::AsyncPrimitive`] type (such as integers or floats),
+    /// as native endian (as per the target platform).
+    ///
+    /// For more info on endianness see [`crate::End`].
+    async fn write_ne_a<E: AsyncPrimitive>(&mut self, value: E) -> Result<(), Error> {
+        self.write_m_a(value, End::Native).await
+    }
+
+    /// Writes any [`crate::AsyncPrimitive`] type (such as integers or floats),
+    /// as big endian.
+    ///
+    /// For more info on endianness see [`crate::End`].
+    async fn write_be_a<E: AsyncPrimitive>(&mut self, value: E) -> Result<(), Error> {
+        self.write_m_a(value, End::Big).await
+    }
+
+    /// Writes any [`crate::AsyncPrimitive`] type (such as integers or floats),
+    /// as little endian.
+    ///
+    /// For more info on endianness see [`crate::End`].
+    async fn write_le_a<E: AsyncPrimitive>(&mut self, value: E) -> Result<(), Error> {
+        self.write_m_a(value, End::Little).await
+    }
+
+    // ================================
+    // Arrays and collections
+    // ================================
+
+    /// Writes all the `values` of any [`crate::AsyncPrimitive`] type
+    /// (such as integers or floats).
+    ///
+    /// See [`Muncher::write_slice_m`] for more info.
+    async fn write_slice_m_a<E: AsyncPrimitive + Clone>(
+        &mut self,
+        values: &[E],
+        end: End,
+    ) -> Result<(), Error> {
+        traced_a(
+            self,
+            TraceKind::Write,
+            "write_slice_m_a",
+            |()| trace::describe_all(values),
+            async |m| E::write_slice_endian_a::<B>(values, m, end).await,
+        )
+        .await
+    }
+
+    /// Writes a collection prefixed by a count (number of elements) of type `<Len>`,
+    /// calling `write` to write each element.
+    ///
+    /// See [`Muncher::write_pref_iter`] for more info.
+    async fn write_pref_iter_a<Len: AsyncPrimitive, I: IntoIterator>(
+        &mut self,
+        end: impl Into<Option<End>>,
+        items: I,
+        mut write: impl AsyncFnMut(&mut Self, I::Item) -> Result<(), Error>,
+    ) -> Result<(), Error>
+    where
+        I::IntoIter: ExactSizeIterator,
+    {
+        let end = self.muncher().resolve_end(end);
+        let items = items.into_iter();
+        self.write_m_a::<Len>(crate::prefix(items.len())?, end)
+            .await?;
+        for item in items {
+            write(self, item).await?;
+        }
+        Ok(())
+    }
+
+    /// Writes an array of any [`crate::AsyncPrimitive`] type
+    /// prefixed by a count (number of elements) of type `<Len>`.
+    ///
+    /// See [`Muncher::write_pref_slice_m`] for more info.
+    async fn write_pref_slice_m_a<Len: AsyncPrimitive, E: AsyncPrimitive + Clone>(
+        &mut self,
+        end: impl Into<Option<End>>,
+        values: &[E],
+    ) -> Result<(), Error> {
+        let end = self.muncher().resolve_end(end);
+        self.write_m_a::<Len>(crate::prefix(values.len())?, end)
+            .await?;
+        self.write_slice_m_a(values, end).await
+    }
+
+    // ================================
+    // Strings
+    // ================================
+
+    /// Writes a fixed array of `u16`'s in the specified endianness
+    /// (see [`crate::End`]).
+    async fn write_fixed_u16_a(&mut self, b: &[u16], end: End) -> Result<(), Error> {
+        self.write_slice_m_a(b, end).await
+    }
+
+    /// Writes some bytes prefixed by a length (number of bytes) of type `<E>`.
+    ///
+    /// Through the `end` argument you can choose the endianness of the length field,
+    /// or pass `None` to use the default endianness (see [`Muncher::set_end`]).
+    ///
+    /// For more info on endianness see [`crate::End`].
+    async fn write_pref_bytes_a<E: AsyncPrimitive>(
+        &mut self,
+        end: impl Into<Option<End>>,
+        buf: &[u8],
+    ) -> Result<(), Error> {
+        let end = self.muncher().resolve_end(end);
+        traced_a(
+            self,
+            TraceKind::Write,
+            "write_pref_bytes_a",
+            |()| trace::describe_bytes(buf),
+            async |m| write_encoded_a(m, &Encoded::prefixed::<E>(buf.len(), buf, end)?).await,
+        )
+        .await
+    }
+
+    /// Writes some bytes, with a null terminator.
+    async fn write_cstr_bytes_a(&mut self, buf: &[u8]) -> Result<(), Error> {
+        traced_a(
+            self,
+            TraceKind::Write,
+            "write_cstr_bytes_a",
+            |()| trace::describe_bytes(buf),
+            async |m| write_encoded_a(m, &Encoded::cstr(buf)).await,
+        )
+        .await
+    }
+
+    /// Writes a UCS-2 string prefixed by a length (number of characters) in the type `<E>`.
+    ///
+    /// UCS-2 consists of big endian 16-bit words, each of which represent a Unicode
+    /// code point between U+0000 and U+FFFF inclusive.
+    ///
+    /// Through the `end` argument you can choose the endianness of the length field,
+    /// or pass `None` to use the default endianness (see [`Muncher::set_end`]).
+    ///
+    /// For more info on endianness see [`crate::End`].
+    #[cfg(feature = "ucs2")]
+    async fn write_pref_ucs2_a<E: AsyncPrimitive>(
+        &mut self,
+        end: impl Into<Option<End>>,
+        msg: &str,
+    ) -> Result<(), Error> {
+        let end = self.muncher().resolve_end(end);
+        traced_a(
+            self,
+            TraceKind::Write,
+            "write_pref_ucs2_a",
+            |()| trace::describe_str(msg),
+            async |m| {
+                let (char_count, bytes) = crate::sans_io::encode_ucs2(msg)?;
+                write_encoded_a(m, &Encoded::prefixed::<E>(char_count, bytes, end)?).await
+            },
+        )
+        .await
+    }
+
+    /// Writes a MUTF-8 string (converted from UTF-8)
+    /// prefixed by a length (number of bytes) of type `<P>`.
+    ///
+    /// See [`Muncher::write_pref_mutf8`] for more info.
+    #[cfg(feature = "mutf8")]
+    async fn write_pref_mutf8_a<P: AsyncPrimitive>(
+        &mut self,
+        mutf: &str,
+        end: impl Into<Option<End>>,
+    ) -> Result<(), crate::MutfError> {
+        let end = self.muncher().resolve_end(end);
+        traced_a(
+            self,
+            TraceKind::Write,
+            "write_pref_mutf8_a",
+            |()| trace::describe_str(mutf),
+            async |m| {
+                let buf = crate::mutf::utf2mutf(mutf)?;
+                write_encoded_a(m, &Encoded::prefixed::<P>(buf.len(), buf, end)?).await?;
+                Ok(())
+            },
+        )
+        .await
+    }
+
+    /// Writes a MUTF-8 string (converted from UTF-8),
+    /// with a null terminator.
+    #[cfg(feature = "mutf8")]
+    async fn write_cstr_mutf8_a(&mut self, mutf: &str) -> Result<(), crate::MutfError> {
+        traced_a(
+            self,
+            TraceKind::Write,
+            "write_cstr_mutf8_a",
+            |()| trace::describe_str(mutf),
+            async |m| {
+                write_encoded_a(m, &Encoded::cstr(crate::mutf::utf2mutf(mutf)?)).await?;
+                Ok(())
+            },
+        )
+        .await
+    }
+}
+
+impl<B: Backend, T, O: ByteOrder> AsyncMunchWrite<B> for Muncher<T, O> where Self: AsyncSink<B> {}
+
+/// Writes all the bytes of `encoded`
+/// (the async version of `Muncher::write_encoded`).
+async fn write_encoded_a<B: Backend>(
+    writer: &mut impl AsyncSink<B>,
+    encoded: &Encoded<'_>,
+) -> Result<(), Error> {
+    for part in encoded.parts() {
+        writer.write_all(part).await?;
+    }
+    Ok(())
+}
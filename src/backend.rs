@@ -0,0 +1,158 @@
+//! Async backends.
+//!
+//! The async methods (see [`crate::AsyncMunchRead`]) work with both
+//! `tokio` and `futures` I/O types, and both crate features can be
+//! enabled at the same time. Which backend is used is picked from
+//! the inner reader/writer type, through the marker types
+//! [`Tokio`] and [`Futures`].
+//!
+//! You only need this module when implementing [`crate::AsyncPrimitive`]
+//! for your own types, which read and write through [`AsyncSource`]
+//! and [`AsyncSink`].
+
+use std::io::Result;
+
+/// An async I/O backend, either [`Tokio`] or [`Futures`].
+pub trait Backend: sealed::Sealed + 'static {}
+
+/// The `tokio` backend ([`tokio::io::AsyncRead`], [`tokio::io::AsyncWrite`]).
+#[cfg(feature = "tokio")]
+#[derive(Debug, Clone, Copy)]
+pub struct Tokio;
+
+/// The `futures` backend ([`futures::io::AsyncRead`], [`futures::io::AsyncWrite`]).
+#[cfg(feature = "futures")]
+#[derive(Debug, Clone, Copy)]
+pub struct Futures;
+
+#[cfg(feature = "tokio")]
+impl Backend for Tokio {}
+#[cfg(feature = "futures")]
+impl Backend for Futures {}
+
+mod sealed {
+    pub trait Sealed {}
+
+    #[cfg(feature = "tokio")]
+    impl Sealed for super::Tokio {}
+    #[cfg(feature = "futures")]
+    impl Sealed for super::Futures {}
+}
+
+/// An async reader of backend `B`.
+///
+/// Implemented for all `tokio` and `futures` readers.
+pub trait AsyncSource<B: Backend>: Unpin {
+    /// Reads exactly enough bytes to fill `buf`.
+    fn read_exact<'a>(&'a mut self, buf: &'a mut [u8]) -> impl Future<Output = Result<()>> + 'a;
+
+    /// Reads some bytes into `buf`, returning how many (`0` at the end of input).
+    ///
+    /// Unlike [`AsyncSource::read_exact`], this is cancellation safe:
+    /// if the future is dropped before completing, nothing was read.
+    fn read_some<'a>(&'a mut self, buf: &'a mut [u8]) -> impl Future<Output = Result<usize>> + 'a;
+}
+
+/// A buffered async reader of backend `B`.
+///
+/// Implemented for all `tokio` and `futures` buffered readers.
+pub trait AsyncBufSource<B: Backend>: AsyncSource<B> {
+    /// Returns the buffered bytes, filling the buffer if it's empty.
+    fn fill_buf(&mut self) -> impl Future<Output = Result<&[u8]>> + '_;
+
+    /// Marks `amount` buffered bytes as read.
+    fn consume(&mut self, amount: usize);
+
+    /// Reads into `buf` until the `delim` byte (included) or the end of input.
+    fn read_until<'a>(
+        &'a mut self,
+        delim: u8,
+        buf: &'a mut Vec<u8>,
+    ) -> impl Future<Output = Result<usize>> + 'a;
+}
+
+/// An async writer of backend `B`.
+///
+/// Implemented for all `tokio` and `futures` writers.
+pub trait AsyncSink<B: Backend>: Unpin {
+    /// Writes all of `buf`.
+    fn write_all<'a>(&'a mut self, buf: &'a [u8]) -> impl Future<Output = Result<()>> + 'a;
+
+    /// Flushes any buffered data.
+    fn flush(&mut self) -> impl Future<Output = Result<()>> + '_;
+}
+
+#[cfg(feature = "tokio")]
+impl<T: tokio::io::AsyncRead + Unpin + ?Sized> AsyncSource<Tokio> for T {
+    async fn read_exact<'a>(&'a mut self, buf: &'a mut [u8]) -> Result<()> {
+        tokio::io::AsyncReadExt::read_exact(self, buf).await?;
+        Ok(())
+    }
+
+    async fn read_some<'a>(&'a mut self, buf: &'a mut [u8]) -> Result<usize> {
+        tokio::io::AsyncReadExt::read(self, buf).await
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl<T: tokio::io::AsyncBufRead + Unpin + ?Sized> AsyncBufSource<Tokio> for T {
+    async fn fill_buf(&mut self) -> Result<&[u8]> {
+        tokio::io::AsyncBufReadExt::fill_buf(self).await
+    }
+
+    fn consume(&mut self, amount: usize) {
+        tokio::io::AsyncBufReadExt::consume(self, amount);
+    }
+
+    async fn read_until<'a>(&'a mut self, delim: u8, buf: &'a mut Vec<u8>) -> Result<usize> {
+        tokio::io::AsyncBufReadExt::read_until(self, delim, buf).await
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl<T: tokio::io::AsyncWrite + Unpin + ?Sized> AsyncSink<Tokio> for T {
+    async fn write_all<'a>(&'a mut self, buf: &'a [u8]) -> Result<()> {
+        tokio::io::AsyncWriteExt::write_all(self, buf).await
+    }
+
+    async fn flush(&mut self) -> Result<()> {
+        tokio::io::AsyncWriteExt::flush(self).await
+    }
+}
+
+#[cfg(feature = "futures")]
+impl<T: futures::io::AsyncRead + Unpin + ?Sized> AsyncSource<Futures> for T {
+    async fn read_exact<'a>(&'a mut self, buf: &'a mut [u8]) -> Result<()> {
+        futures::io::AsyncReadExt::read_exact(self, buf).await
+    }
+
+    async fn read_some<'a>(&'a mut self, buf: &'a mut [u8]) -> Result<usize> {
+        futures::io::AsyncReadExt::read(self, buf).await
+    }
+}
+
+#[cfg(feature = "futures")]
+impl<T: futures::io::AsyncBufRead + Unpin + ?Sized> AsyncBufSource<Futures> for T {
+    async fn fill_buf(&mut self) -> Result<&[u8]> {
+        futures::io::AsyncBufReadExt::fill_buf(self).await
+    }
+
+    fn consume(&mut self, amount: usize) {
+        futures::io::AsyncBufReadExt::consume_unpin(self, amount);
+    }
+
+    async fn read_until<'a>(&'a mut self, delim: u8, buf: &'a mut Vec<u8>) -> Result<usize> {
+        futures::io::AsyncBufReadExt::read_until(self, delim, buf).await
+    }
+}
+
+#[cfg(feature = "futures")]
+impl<T: futures::io::AsyncWrite + Unpin + ?Sized> AsyncSink<Futures> for T {
+    async fn write_all<'a>(&'a mut self, buf: &'a [u8]) -> Result<()> {
+        futures::io::AsyncWriteExt::write_all(self, buf).await
+    }
+
+    async fn flush(&mut self) -> Result<()> {
+        futures::io::AsyncWriteExt::flush(self).await
+    }
+}
@@ -0,0 +1,108 @@
+//! JSON output of the decoded values (for `--json`).
+
+use std::fmt::Write as _;
+
+use bytemuncher::pack::Value;
+
+/// Appends `value` to `out` as JSON.
+///
+/// Bytes are written as lists of numbers,
+/// and floats that JSON can't represent (NaN, infinities) as `null`.
+pub(crate) fn json(out: &mut String, value: &Value) {
+    match value {
+        Value::Bool(b) => out.push_str(if *b { "true" } else { "false" }),
+        Value::Int(n) => out.push_str(&n.to_string()),
+        Value::UInt(n) => out.push_str(&n.to_string()),
+        Value::Float(f) if f.is_finite() => out.push_str(&f.to_string()),
+        Value::Float(_) => out.push_str("null"),
+        Value::Bytes(bytes) => json_list(out, bytes, |out, b| out.push_str(&b.to_string())),
+        Value::Str(s) => json_str(out, s),
+        Value::List(values) => json_list(out, values, json),
+        Value::Record(fields) => {
+            out.push('{');
+            for (i, (name, value)) in fields.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                json_str(out, name);
+                out.push(':');
+                json(out, value);
+            }
+            out.push('}');
+        }
+    }
+}
+
+fn json_list<V>(out: &mut String, values: &[V], mut item: impl FnMut(&mut String, &V)) {
+    out.push('[');
+    for (i, value) in values.iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        item(out, value);
+    }
+    out.push(']');
+}
+
+/// Appends `s` to `out` as a quoted JSON string.
+pub(crate) fn json_str(out: &mut String, s: &str) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if u32::from(c) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", u32::from(c));
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn to_json(value: &Value) -> String {
+        let mut out = String::new();
+        json(&mut out, value);
+        out
+    }
+
+    #[test]
+    fn test_json_scalars() {
+        assert_eq!(to_json(&Value::Bool(true)), "true");
+        assert_eq!(to_json(&Value::Int(-5)), "-5");
+        assert_eq!(to_json(&Value::UInt(u64::MAX)), "18446744073709551615");
+        assert_eq!(to_json(&Value::Float(1.5)), "1.5");
+        assert_eq!(to_json(&Value::Float(f64::NAN)), "null");
+        assert_eq!(to_json(&Value::Float(f64::INFINITY)), "null");
+        assert_eq!(to_json(&Value::Bytes(vec![0, 255])), "[0,255]");
+    }
+
+    #[test]
+    fn test_json_nested() {
+        let value = Value::Record(vec![
+            ("empty".to_owned(), Value::List(Vec::new())),
+            (
+                "points".to_owned(),
+                Value::List(vec![Value::Record(vec![
+                    ("x".to_owned(), Value::Int(1)),
+                    ("y".to_owned(), Value::Int(2)),
+                ])]),
+            ),
+        ]);
+        assert_eq!(to_json(&value), r#"{"empty":[],"points":[{"x":1,"y":2}]}"#);
+    }
+
+    #[test]
+    fn test_json_str_escapes() {
+        let mut out = String::new();
+        json_str(&mut out, "a\"b\\c\nd\re\tf\u{1}g\u{7f}é");
+        assert_eq!(out, "\"a\\\"b\\\\c\\nd\\re\\tf\\u0001g\u{7f}é\"");
+    }
+}
@@ -0,0 +1,173 @@
+//! Decodes a file with a schema (or field specs) and prints its values
+//! with their offsets, or as JSON. Run with `--help` for the usage.
+
+use std::fmt::Write as _;
+use std::io::{Cursor, Read};
+use std::process::ExitCode;
+
+use bytemuncher::schema::Schema;
+use bytemuncher::trace::TraceKind;
+use bytemuncher::{End, Muncher};
+
+mod json;
+
+const USAGE: &str = "\
+Usage: bytemuncher [OPTIONS] <FILE> [FIELD SPECS...]
+
+Decodes FILE (or stdin, for `-`) with a schema file,
+or with field specs like `u32le magic, pref_u16be_utf8 name`,
+and prints each value with its offset.
+
+Options:
+  -s, --schema <PATH>   Read the layout from a schema file
+  -e, --end <ORDER>     Default byte order: le, be or ne (default: le)
+  -o, --offset <N>      Start decoding at byte N (decimal or 0x hex)
+      --json            Print the decoded record as JSON
+  -h, --help            Print this help
+";
+
+struct Args {
+    file: String,
+    schema: Option<String>,
+    specs: Vec<String>,
+    end: End,
+    offset: usize,
+    json: bool,
+}
+
+fn parse_args() -> Result<Option<Args>, String> {
+    let mut file = None;
+    let mut schema = None;
+    let mut specs = Vec::new();
+    let mut end = End::Little;
+    let mut offset = 0;
+    let mut json = false;
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| args.next().ok_or(format!("{name} needs a value"));
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(None),
+            "-s" | "--schema" => schema = Some(value(&arg)?),
+            "-e" | "--end" => {
+                end = match value(&arg)?.as_str() {
+                    "le" | "little" => End::Little,
+                    "be" | "big" => End::Big,
+                    "ne" | "native" => End::Native,
+                    other => return Err(format!("unknown byte order `{other}`")),
+                }
+            }
+            "-o" | "--offset" => {
+                let n = value(&arg)?;
+                offset = match n.strip_prefix("0x") {
+                    Some(hex) => usize::from_str_radix(hex, 16),
+                    None => n.parse(),
+                }
+                .map_err(|_| format!("invalid offset `{n}`"))?;
+            }
+            "--json" => json = true,
+            _ if arg.starts_with('-') && arg != "-" => {
+                return Err(format!("unknown option `{arg}`"));
+            }
+            _ if file.is_none() => file = Some(arg),
+            _ => specs.push(arg),
+        }
+    }
+
+    let file = file.ok_or("missing the file to decode")?;
+    if schema.is_some() != specs.is_empty() {
+        return Err("expected either a schema file or field specs".to_owned());
+    }
+    Ok(Some(Args {
+        file,
+        schema,
+        specs,
+        end,
+        offset,
+        json,
+    }))
+}
+
+fn run(args: &Args) -> Result<(), String> {
+    let schema = match &args.schema {
+        Some(path) => std::fs::read_to_string(path)
+            .map_err(|e| format!("{path}: {e}"))?
+            .parse::<Schema>(),
+        None => Schema::from_specs(&args.specs.join(" ")),
+    }
+    .map_err(|e| e.to_string())?;
+
+    let mut data = Vec::new();
+    if args.file == "-" {
+        std::io::stdin().read_to_end(&mut data)
+    } else {
+        std::fs::File::open(&args.file).and_then(|mut f| f.read_to_end(&mut data))
+    }
+    .map_err(|e| format!("{}: {e}", args.file))?;
+    let data = data
+        .get(args.offset..)
+        .ok_or(format!("the offset is past the end ({} bytes)", data.len()))?;
+
+    let mut muncher = Muncher::new(Cursor::new(data));
+    muncher.set_end(args.end);
+    if !args.json {
+        muncher.start_trace();
+    }
+    let result = muncher.read_schema(&schema);
+
+    if let Some(trace) = muncher.trace() {
+        for event in trace.events().iter().filter(|e| e.kind == TraceKind::Read) {
+            let value = event.value.clone().unwrap_or_else(|| hex(&event.bytes));
+            println!(
+                "{:08x}  {:>5}  {}: {value}",
+                args.offset as u64 + event.offset,
+                event.bytes.len(),
+                event.label,
+            );
+        }
+    }
+    let value = result.map_err(|e| e.to_string())?;
+    if args.json {
+        let mut out = String::new();
+        json::json(&mut out, &value);
+        println!("{out}");
+    }
+
+    let left = data.len() as u64 - muncher.position();
+    if left > 0 {
+        eprintln!("{left} bytes left");
+    }
+    Ok(())
+}
+
+fn hex(bytes: &[u8]) -> String {
+    let mut out = String::new();
+    for (i, b) in bytes.iter().enumerate() {
+        if i > 0 {
+            out.push(' ');
+        }
+        let _ = write!(out, "{b:02x}");
+    }
+    out
+}
+
+fn main() -> ExitCode {
+    let args = match parse_args() {
+        Ok(Some(args)) => args,
+        Ok(None) => {
+            print!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        Err(e) => {
+            eprintln!("error: {e}\n\n{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+    match run(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
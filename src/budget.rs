@@ -0,0 +1,181 @@
+use crate::{ByteOrder, Muncher};
+use std::io::{Error, ErrorKind};
+
+/// Cumulative limits on how much a [`Muncher`] may allocate and read,
+/// (see [`Muncher::set_alloc_budget`] and [`Muncher::set_read_budget`]).
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct Budget {
+    /// Max total bytes allocated by string/bytes reads.
+    pub alloc_limit: Option<u64>,
+    pub allocated: u64,
+    /// Max total bytes consumed from the input.
+    pub read_limit: Option<u64>,
+    pub consumed: u64,
+}
+
+/// **Cumulative allocation and read budgets**
+impl<T, O: ByteOrder> Muncher<T, O> {
+    /// Sets a cumulative limit on the total number of bytes
+    /// allocated by string/bytes reads (`pref`, `fixed`, `cstr`, ...)
+    /// and array reads, over the lifetime of the [`Muncher`].
+    ///
+    /// Default: `None` (unlimited)
+    ///
+    /// Unlike [`Muncher::set_max_alloc`] which limits a single allocation,
+    /// this stops malicious input from making you allocate
+    /// lots of medium sized strings (say, a million 1 MB strings).
+    ///
+    /// Once exceeded, reads fail with [`ErrorKind::QuotaExceeded`].
+    ///
+    /// See [`Muncher::allocated_bytes`] for the current total,
+    /// and [`Muncher::with_budget`] to limit a sub-parse.
+    pub fn set_alloc_budget(&mut self, bytes: Option<u64>) -> &mut Self {
+        self.budget.alloc_limit = bytes;
+        self
+    }
+
+    /// Sets a cumulative limit on the total number of bytes
+    /// consumed from the input by the [`Muncher`].
+    ///
+    /// Default: `None` (unlimited)
+    ///
+    /// Once the limit is reached, any further read fails with
+    /// [`ErrorKind::QuotaExceeded`] (even at end-of-input).
+    ///
+    /// See [`Muncher::consumed_bytes`] for the current total,
+    /// and [`Muncher::with_budget`] to limit a sub-parse.
+    pub fn set_read_budget(&mut self, bytes: Option<u64>) -> &mut Self {
+        self.budget.read_limit = bytes;
+        self
+    }
+
+    /// Total bytes allocated by string/bytes reads so far
+    /// (see [`Muncher::set_alloc_budget`]).
+    #[must_use]
+    pub fn allocated_bytes(&self) -> u64 {
+        self.budget.allocated
+    }
+
+    /// Total bytes consumed from the input so far
+    /// (see [`Muncher::set_read_budget`]).
+    #[must_use]
+    pub fn consumed_bytes(&self) -> u64 {
+        self.budget.consumed
+    }
+
+    /// Resets [`Muncher::allocated_bytes`] and [`Muncher::consumed_bytes`]
+    /// to zero, for example between independent messages of a stream.
+    /// The limits themselves are kept.
+    pub fn reset_budget_usage(&mut self) {
+        self.budget.allocated = 0;
+        self.budget.consumed = 0;
+    }
+
+    /// Runs `parse` with separate allocation (`alloc`) and read (`read`)
+    /// budgets, for limiting a sub-parse (such as a single record).
+    ///
+    /// The usage inside still counts towards the outer budgets,
+    /// and the inner budgets can't go beyond what's left of the outer ones.
+    /// Pass `None` to only be limited by the outer budget.
+    ///
+    /// ```
+    /// # use bytemuncher::{End, Muncher};
+    /// # use std::io::{Cursor, ErrorKind};
+    /// let mut muncher = Muncher::new(Cursor::new(b"\x03abc\x05hello"));
+    ///
+    /// let first = muncher.with_budget(None, Some(4), |m| m.read_pref_utf8::<u8>(End::Big));
+    /// assert_eq!(first.unwrap(), "abc");
+    ///
+    /// let second = muncher.with_budget(None, Some(4), |m| m.read_pref_utf8::<u8>(End::Big));
+    /// assert_eq!(second.unwrap_err().kind(), ErrorKind::QuotaExceeded);
+    ///
+    /// assert_eq!(muncher.consumed_bytes(), 8);
+    /// ```
+    pub fn with_budget<R>(
+        &mut self,
+        alloc: Option<u64>,
+        read: Option<u64>,
+        parse: impl FnOnce(&mut Self) -> R,
+    ) -> R {
+        let outer = self.budget;
+        self.budget = Budget {
+            alloc_limit: min_limit(alloc, remaining(outer.alloc_limit, outer.allocated)),
+            allocated: 0,
+            read_limit: min_limit(read, remaining(outer.read_limit, outer.consumed)),
+            consumed: 0,
+        };
+        let result = parse(self);
+        self.budget = Budget {
+            allocated: outer.allocated + self.budget.allocated,
+            consumed: outer.consumed + self.budget.consumed,
+            ..outer
+        };
+        result
+    }
+
+    /// Counts `len` bytes towards the allocation budget,
+    /// failing if it would be exceeded.
+    pub(crate) fn charge_alloc(&mut self, len: usize) -> Result<(), Error> {
+        self.check_alloc(len)?;
+        self.budget.allocated += len as u64;
+        Ok(())
+    }
+
+    /// Like [`Muncher::charge_alloc`], but without counting it.
+    pub(crate) fn check_alloc(&self, len: usize) -> Result<(), Error> {
+        let len = len as u64;
+        if let Some(limit) = self.budget.alloc_limit
+            && self.budget.allocated.saturating_add(len) > limit
+        {
+            return Err(Error::new(
+                ErrorKind::QuotaExceeded,
+                format!(
+                    "allocation budget exceeded: reading {len} more bytes would surpass the limit of {limit} bytes ({} already allocated)",
+                    self.budget.allocated
+                ),
+            ));
+        }
+        Ok(())
+    }
+
+    /// How many of the `wanted` bytes may be consumed
+    /// without exceeding the read budget.
+    ///
+    /// Fails if nothing can be consumed at all.
+    pub(crate) fn read_allowance(&self, wanted: usize) -> Result<usize, Error> {
+        let Some(left) = remaining(self.budget.read_limit, self.budget.consumed) else {
+            return Ok(wanted);
+        };
+        if left == 0 && wanted > 0 {
+            return Err(Error::new(
+                ErrorKind::QuotaExceeded,
+                format!(
+                    "read budget exceeded: already consumed the limit of {} bytes",
+                    self.budget.consumed
+                ),
+            ));
+        }
+        Ok(wanted.min(usize::try_from(left).unwrap_or(usize::MAX)))
+    }
+
+    pub(crate) fn record_consumed(&mut self, len: usize) {
+        self.budget.consumed += len as u64;
+        self.position += len as u64;
+    }
+
+    pub(crate) fn record_unconsumed(&mut self, len: usize) {
+        self.budget.consumed = self.budget.consumed.saturating_sub(len as u64);
+        self.position = self.position.saturating_sub(len as u64);
+    }
+}
+
+fn remaining(limit: Option<u64>, used: u64) -> Option<u64> {
+    limit.map(|limit| limit.saturating_sub(used))
+}
+
+fn min_limit(a: Option<u64>, b: Option<u64>) -> Option<u64> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
+    }
+}
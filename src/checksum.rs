@@ -0,0 +1,366 @@
+//! Running checksums over the bytes read or written by a [`Muncher`]
+//! (see [`Muncher::begin_checksum`]).
+//!
+//! Built in are [`Crc32`] (IEEE and Castagnoli), [`Crc16`] (several variants)
+//! and [`Adler32`]. Other algorithms can be plugged in by implementing [`Checksum`].
+//!
+//! ```
+//! # use bytemuncher::Muncher;
+//! # use bytemuncher::checksum::Crc32;
+//! # use std::io::Cursor;
+//! // A PNG chunk: length, then a CRC-32 over the type and data
+//! let mut muncher = Muncher::new(Cursor::new(b"\0\0\0\0IEND\xae\x42\x60\x82"));
+//!
+//! let len = muncher.read_be::<u32>().unwrap();
+//! muncher.begin_checksum(Crc32::new());
+//! let kind = muncher.read_fixed_bytes(4).unwrap();
+//! let data = muncher.read_fixed_bytes(len as usize).unwrap();
+//! let crc = muncher.finish_checksum::<Crc32>().unwrap();
+//!
+//! assert_eq!(kind, b"IEND");
+//! assert!(data.is_empty());
+//! assert_eq!(muncher.read_be::<u32>().unwrap(), crc);
+//! ```
+
+use std::any::Any;
+
+use crate::{ByteOrder, Muncher};
+
+/// A checksum (or hash) algorithm, updated with bytes as they go by.
+///
+/// Implement this to use your own algorithm with [`Muncher::begin_checksum`].
+pub trait Checksum: Clone + Send + Sync + 'static {
+    /// The type of the checksum value (like `u32`).
+    type Output;
+
+    /// Adds `bytes` to the checksum.
+    fn update(&mut self, bytes: &[u8]);
+
+    /// Returns the checksum of all the bytes so far.
+    fn finish(&self) -> Self::Output;
+}
+
+/// A [`Checksum`] of any type, so that a [`Muncher`] can hold one.
+pub(crate) trait AnyChecksum: Send + Sync {
+    fn update(&mut self, bytes: &[u8]);
+    fn clone_box(&self) -> Box<dyn AnyChecksum>;
+    fn as_any(&self) -> &dyn Any;
+    fn into_any(self: Box<Self>) -> Box<dyn Any>;
+}
+
+impl<C: Checksum> AnyChecksum for C {
+    fn update(&mut self, bytes: &[u8]) {
+        Checksum::update(self, bytes);
+    }
+
+    fn clone_box(&self) -> Box<dyn AnyChecksum> {
+        Box::new(self.clone())
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn into_any(self: Box<Self>) -> Box<dyn Any> {
+        self
+    }
+}
+
+/// **Checksums**
+impl<T, O: ByteOrder> Muncher<T, O> {
+    /// Starts computing `checksum` over every byte read or written from now on,
+    /// until [`Muncher::finish_checksum`].
+    ///
+    /// This covers all the reads and writes (of any type, blocking or async),
+    /// and also the [`std::io::Read`]/[`std::io::Write`] methods of the [`Muncher`].
+    /// Peeked bytes are only counted once they're actually read,
+    /// and bytes rolled back by [`Muncher::try_parse`] aren't counted.
+    ///
+    /// Only one checksum is computed at a time,
+    /// so this replaces any checksum already in progress.
+    ///
+    /// See the [`crate::checksum`] module for the built-in algorithms.
+    pub fn begin_checksum(&mut self, checksum: impl Checksum) {
+        self.observers.checksum = Some(Box::new(checksum));
+    }
+
+    /// Stops the checksum started by [`Muncher::begin_checksum`],
+    /// and returns its value.
+    ///
+    /// Returns `None` (and keeps the checksum going)
+    /// if there's no checksum of type `C` in progress.
+    pub fn finish_checksum<C: Checksum>(&mut self) -> Option<C::Output> {
+        let checksum = self.observers.checksum.take()?;
+        if !checksum.as_any().is::<C>() {
+            self.observers.checksum = Some(checksum);
+            return None;
+        }
+        checksum.into_any().downcast::<C>().ok().map(|c| c.finish())
+    }
+}
+
+// ================================
+// CRC-32
+// ================================
+
+/// CRC-32, as used by zlib, PNG, ZIP, Ethernet, ... (by [`Crc32::new`]),
+/// or CRC-32C, as used by iSCSI, ext4, ... (by [`Crc32::castagnoli`]).
+#[derive(Debug, Clone, Copy)]
+pub struct Crc32 {
+    table: &'static [u32; 256],
+    crc: u32,
+}
+
+static CRC32_IEEE: [u32; 256] = reflected_table_32(0xEDB8_8320);
+static CRC32_CASTAGNOLI: [u32; 256] = reflected_table_32(0x82F6_3B78);
+
+impl Crc32 {
+    /// The standard CRC-32 (IEEE 802.3, polynomial `0x04C11DB7`).
+    #[must_use]
+    pub fn new() -> Self {
+        Self::with_table(&CRC32_IEEE)
+    }
+
+    /// CRC-32C (Castagnoli, polynomial `0x1EDC6F41`).
+    #[must_use]
+    pub fn castagnoli() -> Self {
+        Self::with_table(&CRC32_CASTAGNOLI)
+    }
+
+    fn with_table(table: &'static [u32; 256]) -> Self {
+        Self { table, crc: !0 }
+    }
+}
+
+impl Default for Crc32 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Checksum for Crc32 {
+    type Output = u32;
+
+    fn update(&mut self, bytes: &[u8]) {
+        for &b in bytes {
+            self.crc = self.table[((self.crc ^ u32::from(b)) & 0xFF) as usize] ^ (self.crc >> 8);
+        }
+    }
+
+    fn finish(&self) -> u32 {
+        !self.crc
+    }
+}
+
+const fn reflected_table_32(poly: u32) -> [u32; 256] {
+    let mut table = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 == 0 {
+                crc >> 1
+            } else {
+                (crc >> 1) ^ poly
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+}
+
+// ================================
+// CRC-16
+// ================================
+
+/// CRC-16, in the variant picked by the constructor
+/// (there's no single standard CRC-16).
+///
+/// | Constructor | Also known as | Used by |
+/// |---|---|---|
+/// | [`Crc16::arc`] | CRC-16, CRC-IBM | LHA, ARC |
+/// | [`Crc16::modbus`] | | Modbus RTU |
+/// | [`Crc16::ccitt_false`] | CRC-16/IBM-3740 | many serial protocols |
+/// | [`Crc16::xmodem`] | CRC-16/ACORN | XMODEM, Bluetooth |
+/// | [`Crc16::kermit`] | CRC-16/CCITT | Kermit |
+/// | [`Crc16::x25`] | CRC-16/IBM-SDLC | HDLC, X.25 |
+#[derive(Debug, Clone, Copy)]
+pub struct Crc16 {
+    table: &'static [u16; 256],
+    reflected: bool,
+    xor_out: u16,
+    crc: u16,
+}
+
+static CRC16_ARC: [u16; 256] = reflected_table_16(0xA001);
+static CRC16_KERMIT: [u16; 256] = reflected_table_16(0x8408);
+static CRC16_XMODEM: [u16; 256] = table_16(0x1021);
+
+impl Crc16 {
+    /// CRC-16/ARC (polynomial `0x8005`, reflected, initial value `0`).
+    #[must_use]
+    pub fn arc() -> Self {
+        Self::reflected(&CRC16_ARC, 0, 0)
+    }
+
+    /// CRC-16/MODBUS (polynomial `0x8005`, reflected, initial value `0xFFFF`).
+    #[must_use]
+    pub fn modbus() -> Self {
+        Self::reflected(&CRC16_ARC, 0xFFFF, 0)
+    }
+
+    /// CRC-16/CCITT-FALSE (polynomial `0x1021`, initial value `0xFFFF`).
+    #[must_use]
+    pub fn ccitt_false() -> Self {
+        Self::normal(&CRC16_XMODEM, 0xFFFF)
+    }
+
+    /// CRC-16/XMODEM (polynomial `0x1021`, initial value `0`).
+    #[must_use]
+    pub fn xmodem() -> Self {
+        Self::normal(&CRC16_XMODEM, 0)
+    }
+
+    /// CRC-16/KERMIT (polynomial `0x1021`, reflected, initial value `0`).
+    #[must_use]
+    pub fn kermit() -> Self {
+        Self::reflected(&CRC16_KERMIT, 0, 0)
+    }
+
+    /// CRC-16/X-25 (polynomial `0x1021`, reflected,
+    /// initial value `0xFFFF`, inverted result).
+    #[must_use]
+    pub fn x25() -> Self {
+        Self::reflected(&CRC16_KERMIT, 0xFFFF, 0xFFFF)
+    }
+
+    fn reflected(table: &'static [u16; 256], init: u16, xor_out: u16) -> Self {
+        Self {
+            table,
+            reflected: true,
+            xor_out,
+            crc: init,
+        }
+    }
+
+    fn normal(table: &'static [u16; 256], init: u16) -> Self {
+        Self {
+            table,
+            reflected: false,
+            xor_out: 0,
+            crc: init,
+        }
+    }
+}
+
+impl Checksum for Crc16 {
+    type Output = u16;
+
+    fn update(&mut self, bytes: &[u8]) {
+        if self.reflected {
+            for &b in bytes {
+                self.crc =
+                    self.table[((self.crc ^ u16::from(b)) & 0xFF) as usize] ^ (self.crc >> 8);
+            }
+        } else {
+            for &b in bytes {
+                self.crc = self.table[((self.crc >> 8) ^ u16::from(b)) as usize] ^ (self.crc << 8);
+            }
+        }
+    }
+
+    fn finish(&self) -> u16 {
+        self.crc ^ self.xor_out
+    }
+}
+
+const fn reflected_table_16(poly: u16) -> [u16; 256] {
+    let mut table = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u16;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 == 0 {
+                crc >> 1
+            } else {
+                (crc >> 1) ^ poly
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+}
+
+const fn table_16(poly: u16) -> [u16; 256] {
+    let mut table = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = (i as u16) << 8;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 0x8000 == 0 {
+                crc << 1
+            } else {
+                (crc << 1) ^ poly
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+}
+
+// ================================
+// Adler-32
+// ================================
+
+/// Adler-32, as used by zlib streams.
+#[derive(Debug, Clone, Copy)]
+pub struct Adler32 {
+    a: u32,
+    b: u32,
+}
+
+impl Adler32 {
+    const MOD: u32 = 65521;
+    /// The most bytes that can be summed before `b` could overflow.
+    const CHUNK: usize = 5552;
+
+    /// Creates a new Adler-32 checksum.
+    #[must_use]
+    pub fn new() -> Self {
+        Self { a: 1, b: 0 }
+    }
+}
+
+impl Default for Adler32 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Checksum for Adler32 {
+    type Output = u32;
+
+    fn update(&mut self, bytes: &[u8]) {
+        for chunk in bytes.chunks(Self::CHUNK) {
+            for &b in chunk {
+                self.a += u32::from(b);
+                self.b += self.a;
+            }
+            self.a %= Self::MOD;
+            self.b %= Self::MOD;
+        }
+    }
+
+    fn finish(&self) -> u32 {
+        (self.b << 16) | self.a
+    }
+}
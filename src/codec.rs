@@ -0,0 +1,255 @@
+//! [`tokio_util::codec`] integration (crate feature: `tokio-util`),
+//! for length-prefixed framing over streams like TCP.
+//!
+//! [`PrefixCodec`] splits a stream into frames prefixed by their length
+//! (of any [`Primitive`] type, including your own varints), and
+//! [`BodyCodec`] also decodes each frame's body with a [`SliceMuncher`].
+//!
+//! ```
+//! # use bytemuncher::codec::PrefixCodec;
+//! # use bytemuncher::End;
+//! # use bytes::BytesMut;
+//! # use tokio_util::codec::{Decoder, Encoder};
+//! let mut codec = PrefixCodec::<u16>::new();
+//! codec.set_end(End::Big).set_max_frame(1024);
+//!
+//! let mut buf = BytesMut::new();
+//! codec.encode(&b"hello"[..], &mut buf).unwrap();
+//! assert_eq!(&buf[..], b"\x00\x05hello");
+//!
+//! assert_eq!(&codec.decode(&mut buf).unwrap().unwrap()[..], b"hello");
+//! ```
+
+use std::io::{Error, ErrorKind};
+use std::marker::PhantomData;
+
+use bytes::{Buf, BytesMut};
+use tokio_util::codec::{Decoder, Encoder};
+
+use crate::sans_io::{Encoded, Parsed, Value, decode_slice};
+use crate::{ByteOrder, End, Primitive, SliceMuncher};
+
+/// A [`Decoder`]/[`Encoder`] for frames prefixed by
+/// their length (number of bytes) of type `<Len>`.
+///
+/// Decoded frames are the frame body (without the prefix), as a [`BytesMut`].
+/// Anything that is `AsRef<[u8]>` can be encoded as a frame.
+///
+/// To decode the body into something else, see [`PrefixCodec::decode_body`].
+#[derive(Debug)]
+pub struct PrefixCodec<Len, O = End> {
+    max_frame_bytes: usize,
+    order: O,
+    len: PhantomData<fn() -> Len>,
+}
+
+impl<Len: Primitive> PrefixCodec<Len> {
+    /// Creates a new [`PrefixCodec`] with the default configuration:
+    /// - Max frame size of 8 MB: [`PrefixCodec::set_max_frame`]
+    /// - Endianness of little endian: [`PrefixCodec::set_end`]
+    #[must_use]
+    pub fn new() -> Self {
+        Self::with_order(End::Little)
+    }
+
+    /// Sets the endianness of the length prefix (and of the
+    /// [`SliceMuncher`] given to [`PrefixCodec::decode_body`]).
+    ///
+    /// Default: [`End::Little`]
+    pub fn set_end(&mut self, end: End) -> &mut Self {
+        self.order = end;
+        self
+    }
+}
+
+impl<Len: Primitive> Default for PrefixCodec<Len> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Not derived, to not require `Len: Clone`.
+impl<Len, O: ByteOrder> Clone for PrefixCodec<Len, O> {
+    fn clone(&self) -> Self {
+        Self {
+            max_frame_bytes: self.max_frame_bytes,
+            order: self.order,
+            len: PhantomData,
+        }
+    }
+}
+
+impl<Len: Primitive, O: ByteOrder> PrefixCodec<Len, O> {
+    /// Creates a new [`PrefixCodec`] with the endianness
+    /// decided by the `order` type (see [`ByteOrder`]).
+    ///
+    /// Other than that, it's the same as [`PrefixCodec::new`].
+    pub fn with_order(order: O) -> Self {
+        Self {
+            max_frame_bytes: 8 * 1024 * 1024,
+            order,
+            len: PhantomData,
+        }
+    }
+
+    /// Sets the max size of a frame body (in bytes).
+    ///
+    /// Default: 8 MB
+    ///
+    /// Like [`crate::Muncher::set_max_alloc`], this stops a forged length from
+    /// making the codec buffer (and allocate) huge amounts of memory.
+    /// Larger frames fail with [`ErrorKind::InvalidData`] when decoding,
+    /// and [`ErrorKind::InvalidInput`] when encoding.
+    pub fn set_max_frame(&mut self, max_frame_bytes: usize) -> &mut Self {
+        self.max_frame_bytes = max_frame_bytes;
+        self
+    }
+
+    /// Returns the endianness of the length prefix
+    /// (see [`PrefixCodec::set_end`] and [`PrefixCodec::with_order`]).
+    #[must_use]
+    pub fn end(&self) -> End {
+        self.order.end()
+    }
+
+    /// Turns this into a [`BodyCodec`], which decodes each frame body
+    /// by calling `decode` with a [`SliceMuncher`] over it.
+    pub fn decode_body<F, R>(self, decode: F) -> BodyCodec<Len, O, F>
+    where
+        F: FnMut(&mut SliceMuncher<'_, O>) -> Result<R, Error>,
+    {
+        BodyCodec {
+            codec: self,
+            decode,
+        }
+    }
+}
+
+impl<Len: Primitive, O: ByteOrder> Decoder for PrefixCodec<Len, O> {
+    type Item = BytesMut;
+    type Error = Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<BytesMut>, Error> {
+        let (len, head) = match decode_slice(src, Value::<Len, _>::new(self.order), |_| Ok(()))? {
+            Parsed::Done((len, head)) => (len.into_usize(), head),
+            Parsed::Incomplete { needed } => {
+                src.reserve(needed);
+                return Ok(None);
+            }
+        };
+        if len > self.max_frame_bytes {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!(
+                    "frame is too large ({len} bytes): surpassed the (customizable) limit of {} bytes",
+                    self.max_frame_bytes
+                ),
+            ));
+        }
+        if src.len() < head + len {
+            src.reserve(head + len - src.len());
+            return Ok(None);
+        }
+        src.advance(head);
+        Ok(Some(src.split_to(len)))
+    }
+}
+
+impl<Len: Primitive, O: ByteOrder, B: AsRef<[u8]>> Encoder<B> for PrefixCodec<Len, O> {
+    type Error = Error;
+
+    fn encode(&mut self, frame: B, dst: &mut BytesMut) -> Result<(), Error> {
+        let body = frame.as_ref();
+        if body.len() > self.max_frame_bytes
+            || Len::from_usize(body.len()).into_usize() != body.len()
+        {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "frame is too large ({} bytes) for the limit of {} bytes or the length prefix type",
+                    body.len(),
+                    self.max_frame_bytes
+                ),
+            ));
+        }
+        let encoded = Encoded::prefixed::<Len>(body.len(), body, self.order)?;
+        for part in encoded.parts() {
+            dst.extend_from_slice(part);
+        }
+        Ok(())
+    }
+}
+
+/// A [`PrefixCodec`] that also decodes each frame body,
+/// returned by [`PrefixCodec::decode_body`].
+///
+/// The `decode` closure is given a [`SliceMuncher`] over the frame body
+/// (with the endianness of the codec). Frames are encoded the same way
+/// as with [`PrefixCodec`].
+///
+/// ```
+/// # use bytemuncher::codec::PrefixCodec;
+/// # use bytes::BytesMut;
+/// # use tokio_util::codec::Decoder;
+/// let mut codec = PrefixCodec::<u8>::new().decode_body(|body| {
+///     let id = body.read::<u16>()?;
+///     let name = body.read_pref_utf8::<u8>(None)?;
+///     Ok((id, name.to_owned()))
+/// });
+///
+/// let mut buf = BytesMut::from(&b"\x06\x01\x00\x03abc"[..]);
+/// assert_eq!(codec.decode(&mut buf).unwrap(), Some((1, "abc".to_owned())));
+/// ```
+///
+/// Bytes left in the body after `decode` returns are ignored.
+#[derive(Debug)]
+pub struct BodyCodec<Len, O, F> {
+    codec: PrefixCodec<Len, O>,
+    decode: F,
+}
+
+impl<Len, O: ByteOrder, F: Clone> Clone for BodyCodec<Len, O, F> {
+    fn clone(&self) -> Self {
+        Self {
+            codec: self.codec.clone(),
+            decode: self.decode.clone(),
+        }
+    }
+}
+
+impl<Len, O, F> BodyCodec<Len, O, F> {
+    /// Returns the inner [`PrefixCodec`].
+    pub fn codec(&self) -> &PrefixCodec<Len, O> {
+        &self.codec
+    }
+
+    /// Returns the inner [`PrefixCodec`], to change its settings.
+    pub fn codec_mut(&mut self) -> &mut PrefixCodec<Len, O> {
+        &mut self.codec
+    }
+}
+
+impl<Len, O, F, R> Decoder for BodyCodec<Len, O, F>
+where
+    Len: Primitive,
+    O: ByteOrder,
+    F: FnMut(&mut SliceMuncher<'_, O>) -> Result<R, Error>,
+{
+    type Item = R;
+    type Error = Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<R>, Error> {
+        let Some(frame) = self.codec.decode(src)? else {
+            return Ok(None);
+        };
+        (self.decode)(&mut SliceMuncher::with_order(&frame, self.codec.order)).map(Some)
+    }
+}
+
+impl<Len: Primitive, O: ByteOrder, F, B: AsRef<[u8]>> Encoder<B> for BodyCodec<Len, O, F> {
+    type Error = Error;
+
+    fn encode(&mut self, frame: B, dst: &mut BytesMut) -> Result<(), Error> {
+        self.codec.encode(frame, dst)
+    }
+}
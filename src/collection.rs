@@ -0,0 +1,123 @@
+use crate::{ByteOrder, End, Muncher, Primitive};
+use std::io::{Error, Read, Write};
+
+/// **Length-prefixed collection read methods** (blocking)
+impl<T: Read, O: ByteOrder> Muncher<T, O> {
+    /// Reads a collection prefixed by a count (number of elements) of type `<Len>`,
+    /// calling `parse` to read each element.
+    ///
+    /// Through the `end` argument you can choose the endianness of the count field,
+    /// or pass `None` to use the default endianness (see [`Muncher::set_end`]).
+    ///
+    /// The total size (`count * size_of::<Elem>()`) is checked
+    /// against the allocation limit (see [`Muncher::set_max_alloc`])
+    /// and budget (see [`Muncher::set_alloc_budget`]) up front, but only what
+    /// the elements allocate as they're parsed is counted towards the budget.
+    ///
+    /// If the elements are just [`crate::Primitive`] values,
+    /// [`Muncher::read_pref_array_m`] is much faster.
+    ///
+    /// ```
+    /// # use bytemuncher::{End, Muncher};
+    /// # use std::io::Cursor;
+    /// let data = b"\x00\x02\x03abc\x02hi";
+    /// let mut muncher = Muncher::new(Cursor::new(data));
+    ///
+    /// let names = muncher
+    ///     .read_pref_vec::<u16, _>(End::Big, |m| m.read_pref_utf8::<u8>(End::Big))
+    ///     .unwrap();
+    /// assert_eq!(names, ["abc", "hi"]);
+    /// ```
+    pub fn read_pref_vec<Len: Primitive, Elem>(
+        &mut self,
+        end: impl Into<Option<End>>,
+        mut parse: impl FnMut(&mut Self) -> Result<Elem, Error>,
+    ) -> Result<Vec<Elem>, Error> {
+        let count = self
+            .read_order::<Len>(self.resolve_order(end))?
+            .into_usize();
+        self.check_len(count.saturating_mul(std::mem::size_of::<Elem>()))?;
+        let mut values =
+            Vec::with_capacity(count.min(self.alloc_chunk(std::mem::size_of::<Elem>())));
+        for _ in 0..count {
+            values.push(parse(self)?);
+        }
+        Ok(values)
+    }
+
+    /// Reads an array of any [`crate::Primitive`] type (such as integers or floats)
+    /// prefixed by a count (number of elements) of type `<Len>`.
+    ///
+    /// The `end` argument is the endianness of both the count and the elements
+    /// (pass `None` to use the default endianness, see [`Muncher::set_end`]).
+    ///
+    /// This is the fast path of [`Muncher::read_pref_vec`],
+    /// see [`Muncher::read_array_m`] for more info.
+    pub fn read_pref_array_m<Len: Primitive, E: Primitive + Default + Clone>(
+        &mut self,
+        end: impl Into<Option<End>>,
+    ) -> Result<Vec<E>, Error> {
+        let order = self.resolve_order(end);
+        let count = self.read_order::<Len>(order)?.into_usize();
+        self.read_array_order(count, order)
+    }
+}
+
+/// **Length-prefixed collection write methods** (blocking)
+impl<T: Write, O: ByteOrder> Muncher<T, O> {
+    /// Writes a collection prefixed by a count (number of elements) of type `<Len>`,
+    /// calling `write` to write each element.
+    ///
+    /// Through the `end` argument you can choose the endianness of the count field,
+    /// or pass `None` to use the default endianness (see [`Muncher::set_end`]).
+    ///
+    /// If the elements are just [`crate::Primitive`] values,
+    /// [`Muncher::write_pref_slice_m`] is much faster.
+    ///
+    /// ```
+    /// # use bytemuncher::{End, Muncher};
+    /// let mut data = Vec::new();
+    /// let mut muncher = Muncher::new(&mut data);
+    ///
+    /// muncher
+    ///     .write_pref_iter::<u16, _>(End::Big, ["abc", "hi"], |m, name| {
+    ///         m.write_pref_bytes::<u8>(End::Big, name.as_bytes())
+    ///     })
+    ///     .unwrap();
+    /// assert_eq!(data, b"\x00\x02\x03abc\x02hi");
+    /// ```
+    pub fn write_pref_iter<Len: Primitive, I: IntoIterator>(
+        &mut self,
+        end: impl Into<Option<End>>,
+        items: I,
+        mut write: impl FnMut(&mut Self, I::Item) -> Result<(), Error>,
+    ) -> Result<(), Error>
+    where
+        I::IntoIter: ExactSizeIterator,
+    {
+        let items = items.into_iter();
+        self.write_order::<Len>(crate::prefix(items.len())?, self.resolve_order(end))?;
+        for item in items {
+            write(self, item)?;
+        }
+        Ok(())
+    }
+
+    /// Writes an array of any [`crate::Primitive`] type (such as integers or floats)
+    /// prefixed by a count (number of elements) of type `<Len>`.
+    ///
+    /// The `end` argument is the endianness of both the count and the elements
+    /// (pass `None` to use the default endianness, see [`Muncher::set_end`]).
+    ///
+    /// This is the fast path of [`Muncher::write_pref_iter`],
+    /// see [`Muncher::write_slice_m`] for more info.
+    pub fn write_pref_slice_m<Len: Primitive, E: Primitive + Clone>(
+        &mut self,
+        end: impl Into<Option<End>>,
+        values: &[E],
+    ) -> Result<(), Error> {
+        let order = self.resolve_order(end);
+        self.write_order::<Len>(crate::prefix(values.len())?, order)?;
+        self.write_slice_order(values, order)
+    }
+}
@@ -0,0 +1,109 @@
+use crate::{ByteOrder, End, Muncher};
+use std::ops::{Deref, DerefMut};
+
+/// Tracks one level of nesting in a [`Muncher`],
+/// returned by [`Muncher::enter`].
+///
+/// It can be used just like the `Muncher` itself, and
+/// leaves the nesting level when dropped.
+pub struct DepthGuard<'a, T, O: ByteOrder = End> {
+    muncher: &'a mut Muncher<T, O>,
+}
+
+impl<T, O: ByteOrder> Deref for DepthGuard<'_, T, O> {
+    type Target = Muncher<T, O>;
+
+    fn deref(&self) -> &Self::Target {
+        self.muncher
+    }
+}
+
+impl<T, O: ByteOrder> DerefMut for DepthGuard<'_, T, O> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.muncher
+    }
+}
+
+impl<T, O: ByteOrder> Drop for DepthGuard<'_, T, O> {
+    fn drop(&mut self) {
+        self.muncher.depth -= 1;
+    }
+}
+
+/// **Nesting depth tracking for recursive formats**
+impl<T, O: ByteOrder> Muncher<T, O> {
+    /// Sets the maximum nesting depth (see [`Muncher::enter`]).
+    ///
+    /// Default: 128
+    ///
+    /// This prevents hostile input with deeply nested structures
+    /// from overflowing the stack of recursive parsers.
+    pub fn set_max_depth(&mut self, max_depth: usize) -> &mut Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Returns the current nesting depth (see [`Muncher::enter`]).
+    #[must_use]
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Enters one level of nesting, returning a guard that leaves it
+    /// when dropped. Call this when parsing a nested structure
+    /// (NBT compounds, nested TLVs, ...) in a recursive parser.
+    ///
+    /// Fails with [`std::io::ErrorKind::InvalidData`] if the
+    /// maximum depth (see [`Muncher::set_max_depth`]) is surpassed.
+    ///
+    /// ```
+    /// # use bytemuncher::{End, Muncher};
+    /// # use std::io::{Cursor, Read};
+    /// /// A list is a count followed by that many lists.
+    /// fn parse_list<T: Read>(muncher: &mut Muncher<T>) -> std::io::Result<usize> {
+    ///     let mut muncher = muncher.enter()?;
+    ///     let count = muncher.read_le::<u8>()?;
+    ///     let mut total = 1;
+    ///     for _ in 0..count {
+    ///         total += parse_list(&mut muncher)?;
+    ///     }
+    ///     Ok(total)
+    /// }
+    ///
+    /// let mut muncher = Muncher::new(Cursor::new([2, 0, 1, 0]));
+    /// assert_eq!(parse_list(&mut muncher).unwrap(), 4);
+    ///
+    /// // Hostile input: infinitely nested
+    /// let mut muncher = Muncher::new(Cursor::new([1; 1000]));
+    /// assert!(parse_list(&mut muncher).is_err());
+    /// ```
+    pub fn enter(&mut self) -> Result<DepthGuard<'_, T, O>, std::io::Error> {
+        self.verify_depth(self.depth + 1)?;
+        self.depth += 1;
+        Ok(DepthGuard { muncher: self })
+    }
+
+    /// Runs `parse` one level of nesting deeper,
+    /// see [`Muncher::enter`] for more info.
+    pub fn nested<R>(
+        &mut self,
+        parse: impl FnOnce(&mut Self) -> Result<R, std::io::Error>,
+    ) -> Result<R, std::io::Error> {
+        let mut guard = self.enter()?;
+        parse(&mut guard)
+    }
+
+    pub(crate) fn verify_depth(&self, depth: usize) -> Result<(), std::io::Error> {
+        if depth > self.max_depth {
+            Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!(
+                    "nesting is too deep ({depth} levels): surpassed the default (customizable) limit of {} levels",
+                    self.max_depth
+                ),
+            ))
+        } else {
+            Ok(())
+        }
+    }
+}
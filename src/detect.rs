@@ -0,0 +1,121 @@
+use crate::{End, Muncher};
+use std::io::{BufRead, Error, Read};
+
+/// A byte order mark (BOM) found at the start of some text,
+/// indicating its encoding and endianness.
+///
+/// Returned by [`Muncher::read_bom`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Bom {
+    /// `EF BB BF`
+    Utf8,
+    /// `FE FF` (big endian) or `FF FE` (little endian)
+    Utf16(End),
+    /// `00 00 FE FF` (big endian) or `FF FE 00 00` (little endian)
+    Utf32(End),
+}
+
+impl Bom {
+    /// Returns the endianness indicated by the BOM,
+    /// or `None` for UTF-8 (which doesn't have one).
+    #[must_use]
+    pub fn end(self) -> Option<End> {
+        match self {
+            Bom::Utf8 => None,
+            Bom::Utf16(end) | Bom::Utf32(end) => Some(end),
+        }
+    }
+
+    /// The raw bytes of the BOM.
+    #[must_use]
+    pub fn as_bytes(self) -> &'static [u8] {
+        match self {
+            Bom::Utf8 => &[0xEF, 0xBB, 0xBF],
+            Bom::Utf16(end) if end.is_le() => &[0xFF, 0xFE],
+            Bom::Utf16(_) => &[0xFE, 0xFF],
+            Bom::Utf32(end) if end.is_le() => &[0xFF, 0xFE, 0x00, 0x00],
+            Bom::Utf32(_) => &[0x00, 0x00, 0xFE, 0xFF],
+        }
+    }
+}
+
+/// Longest first, so UTF-32 LE isn't mistaken for UTF-16 LE.
+const BOMS: [Bom; 5] = [
+    Bom::Utf32(End::Little),
+    Bom::Utf32(End::Big),
+    Bom::Utf8,
+    Bom::Utf16(End::Little),
+    Bom::Utf16(End::Big),
+];
+
+/// **Endianness detection methods** (blocking)
+///
+/// Only available with the runtime [`End`] byte order (the default),
+/// since they change the default endianness of the [`Muncher`].
+impl<T: Read> Muncher<T> {
+    /// Reads a marker that is `le` in little endian files
+    /// and `be` in big endian files, returning the matching endianness.
+    ///
+    /// This also sets the default endianness of the [`Muncher`]
+    /// (see [`Muncher::set_end`]) to the detected value.
+    ///
+    /// If neither matches, an [`std::io::ErrorKind::InvalidData`]
+    /// error is returned.
+    ///
+    /// Some examples:
+    /// - TIFF: `detect_end_from_magic(b"II", b"MM")`
+    /// - pcap: `detect_end_from_magic(&[0xd4, 0xc3, 0xb2, 0xa1], &[0xa1, 0xb2, 0xc3, 0xd4])`
+    /// - ELF (`EI_DATA` byte): `detect_end_from_magic(&[1], &[2])`
+    ///
+    /// ```
+    /// # use bytemuncher::{End, Muncher};
+    /// # use std::io::Cursor;
+    /// let mut muncher = Muncher::new(Cursor::new(b"MM\x00\x2a"));
+    ///
+    /// assert_eq!(muncher.detect_end_from_magic(b"II", b"MM").unwrap(), End::Big);
+    /// assert_eq!(muncher.end(), End::Big);
+    /// ```
+    pub fn detect_end_from_magic(&mut self, le: &[u8], be: &[u8]) -> Result<End, Error> {
+        let end = magic_end(self.expect_one_of(&[le, be])?);
+        self.order = end;
+        Ok(end)
+    }
+}
+
+/// **Endianness detection methods that require `T: `[`std::io::BufRead`]**
+impl<T: BufRead> Muncher<T> {
+    /// Reads a Unicode byte order mark (BOM) if there is one,
+    /// returning its encoding and endianness.
+    ///
+    /// If there's no BOM, nothing is consumed and `None` is returned.
+    ///
+    /// For UTF-16 and UTF-32 BOMs this also sets the default
+    /// endianness of the [`Muncher`] (see [`Muncher::set_end`]).
+    ///
+    /// ```
+    /// # use bytemuncher::{Bom, End, Muncher};
+    /// # use std::io::{BufReader, Cursor};
+    /// let mut muncher = Muncher::new(BufReader::new(Cursor::new(b"\xff\xfeh\x00i\x00")));
+    ///
+    /// assert_eq!(muncher.read_bom().unwrap(), Some(Bom::Utf16(End::Little)));
+    /// assert_eq!(muncher.end(), End::Little);
+    /// ```
+    pub fn read_bom(&mut self) -> Result<Option<Bom>, Error> {
+        let upcoming = self.peek_bytes(4)?;
+        let Some(bom) = BOMS
+            .into_iter()
+            .find(|bom| upcoming.starts_with(bom.as_bytes()))
+        else {
+            return Ok(None);
+        };
+        self.consume(bom.as_bytes().len());
+        if let Some(end) = bom.end() {
+            self.order = end;
+        }
+        Ok(Some(bom))
+    }
+}
+
+pub(crate) fn magic_end(index: usize) -> End {
+    if index == 0 { End::Little } else { End::Big }
+}
@@ -0,0 +1,172 @@
+#[cfg(feature = "mutf8")]
+use crate::MutfError;
+use crate::{ByteOrder, End, Muncher, Primitive};
+use std::io::{BufRead, Error};
+
+/// **Clean end-of-input detection** (blocking)
+///
+/// The regular read methods fail with [`std::io::ErrorKind::UnexpectedEof`]
+/// whether the input ended before or in the middle of a value.
+/// The `try_read_*` methods return `Ok(None)` if the input ended
+/// right before the value, so stream parsers can stop cleanly,
+/// while a value that's cut off is still an error.
+///
+/// The `try_read_*` methods cover the common reads.
+/// [`Muncher::try_read_with`] is the general form, for any other read
+/// (or a whole record made of several reads).
+impl<T: BufRead, O: ByteOrder> Muncher<T, O> {
+    /// Returns `true` if there's nothing more to read,
+    /// without consuming anything.
+    ///
+    /// This may block until more data is available.
+    ///
+    /// ```
+    /// # use bytemuncher::Muncher;
+    /// # use std::io::Cursor;
+    /// let mut muncher = Muncher::new(Cursor::new([1, 2]));
+    /// assert!(!muncher.is_eof().unwrap());
+    /// muncher.read_le::<u16>().unwrap();
+    /// assert!(muncher.is_eof().unwrap());
+    /// ```
+    pub fn is_eof(&mut self) -> Result<bool, Error> {
+        Ok(self.fill_buf()?.is_empty())
+    }
+
+    /// Runs `parse` unless the input has ended (see [`Muncher::is_eof`]),
+    /// in which case it returns `Ok(None)`.
+    ///
+    /// `parse` can return any error that an [`Error`] converts into
+    /// (like [`crate::MutfError`]).
+    ///
+    /// ```
+    /// # use bytemuncher::{End, Muncher};
+    /// # use std::io::Cursor;
+    /// let mut muncher = Muncher::new(Cursor::new(b"\x02hi"));
+    /// let parse = |m: &mut Muncher<_>| m.read_pref_utf8::<u8>(End::Big);
+    /// assert_eq!(muncher.try_read_with(parse).unwrap().unwrap(), "hi");
+    /// assert!(muncher.try_read_with(parse).unwrap().is_none());
+    /// ```
+    pub fn try_read_with<R, Err: From<Error>>(
+        &mut self,
+        parse: impl FnOnce(&mut Self) -> Result<R, Err>,
+    ) -> Result<Option<R>, Err> {
+        if self.is_eof()? {
+            return Ok(None);
+        }
+        parse(self).map(Some)
+    }
+
+    /// Like [`Muncher::read_m`], but returns `Ok(None)` at the end of input.
+    pub fn try_read_m<E: Primitive>(&mut self, end: End) -> Result<Option<E>, Error> {
+        self.try_read_with(|m| m.read_m(end))
+    }
+
+    /// Like [`Muncher::read`], but returns `Ok(None)` at the end of input.
+    pub fn try_read<E: Primitive>(&mut self) -> Result<Option<E>, Error> {
+        self.try_read_with(|m| m.read())
+    }
+
+    /// Like [`Muncher::read_le`], but returns `Ok(None)` at the end of input.
+    pub fn try_read_le<E: Primitive>(&mut self) -> Result<Option<E>, Error> {
+        self.try_read_m(End::Little)
+    }
+
+    /// Like [`Muncher::read_be`], but returns `Ok(None)` at the end of input.
+    pub fn try_read_be<E: Primitive>(&mut self) -> Result<Option<E>, Error> {
+        self.try_read_m(End::Big)
+    }
+
+    /// Like [`Muncher::read_ne`], but returns `Ok(None)` at the end of input.
+    pub fn try_read_ne<E: Primitive>(&mut self) -> Result<Option<E>, Error> {
+        self.try_read_m(End::Native)
+    }
+
+    /// Like [`Muncher::read_array_m`], but returns `Ok(None)` at the end of input.
+    pub fn try_read_array_m<E: Primitive + Default + Clone>(
+        &mut self,
+        count: usize,
+        end: End,
+    ) -> Result<Option<Vec<E>>, Error> {
+        self.try_read_with(|m| m.read_array_m(count, end))
+    }
+
+    /// Like [`Muncher::read_pref_array_m`], but returns `Ok(None)` at the end of input.
+    pub fn try_read_pref_array_m<Len: Primitive, E: Primitive + Default + Clone>(
+        &mut self,
+        end: impl Into<Option<End>>,
+    ) -> Result<Option<Vec<E>>, Error> {
+        self.try_read_with(|m| m.read_pref_array_m::<Len, E>(end))
+    }
+
+    /// Like [`Muncher::read_fixed_bytes`], but returns `Ok(None)` at the end of input.
+    pub fn try_read_fixed_bytes(&mut self, len: usize) -> Result<Option<Vec<u8>>, Error> {
+        self.try_read_with(|m| m.read_fixed_bytes(len))
+    }
+
+    /// Like [`Muncher::read_fixed_utf8`], but returns `Ok(None)` at the end of input.
+    pub fn try_read_fixed_utf8(&mut self, len: usize) -> Result<Option<String>, Error> {
+        self.try_read_with(|m| m.read_fixed_utf8(len))
+    }
+
+    /// Like [`Muncher::read_pref_bytes`], but returns `Ok(None)` at the end of input.
+    pub fn try_read_pref_bytes<E: Primitive>(
+        &mut self,
+        end: impl Into<Option<End>>,
+    ) -> Result<Option<Vec<u8>>, Error> {
+        self.try_read_with(|m| m.read_pref_bytes::<E>(end))
+    }
+
+    /// Like [`Muncher::read_pref_utf8`], but returns `Ok(None)` at the end of input.
+    pub fn try_read_pref_utf8<E: Primitive>(
+        &mut self,
+        end: impl Into<Option<End>>,
+    ) -> Result<Option<String>, Error> {
+        self.try_read_with(|m| m.read_pref_utf8::<E>(end))
+    }
+
+    /// Like [`Muncher::read_cstr_bytes`], but returns `Ok(None)` at the end of input.
+    pub fn try_read_cstr_bytes(&mut self) -> Result<Option<Vec<u8>>, Error> {
+        self.try_read_with(Self::read_cstr_bytes)
+    }
+
+    /// Like [`Muncher::read_cstr_utf8`], but returns `Ok(None)` at the end of input.
+    pub fn try_read_cstr_utf8(&mut self) -> Result<Option<String>, Error> {
+        self.try_read_with(Self::read_cstr_utf8)
+    }
+
+    /// Like [`Muncher::read_pref_ucs2`], but returns `Ok(None)` at the end of input.
+    #[cfg(feature = "ucs2")]
+    pub fn try_read_pref_ucs2<E: Primitive>(
+        &mut self,
+        end: impl Into<Option<End>>,
+    ) -> Result<Option<String>, Error> {
+        self.try_read_with(|m| m.read_pref_ucs2::<E>(end))
+    }
+
+    /// Like [`Muncher::read_fixed_ucs2`], but returns `Ok(None)` at the end of input.
+    #[cfg(feature = "ucs2")]
+    pub fn try_read_fixed_ucs2(&mut self, char_count: usize) -> Result<Option<String>, Error> {
+        self.try_read_with(|m| m.read_fixed_ucs2(char_count))
+    }
+
+    /// Like [`Muncher::read_pref_mutf8`], but returns `Ok(None)` at the end of input.
+    #[cfg(feature = "mutf8")]
+    pub fn try_read_pref_mutf8<E: Primitive>(
+        &mut self,
+        end: impl Into<Option<End>>,
+    ) -> Result<Option<String>, MutfError> {
+        self.try_read_with(|m| m.read_pref_mutf8::<E>(end))
+    }
+
+    /// Like [`Muncher::read_fixed_mutf8`], but returns `Ok(None)` at the end of input.
+    #[cfg(feature = "mutf8")]
+    pub fn try_read_fixed_mutf8(&mut self, len: usize) -> Result<Option<String>, MutfError> {
+        self.try_read_with(|m| m.read_fixed_mutf8(len))
+    }
+
+    /// Like [`Muncher::read_cstr_mutf8`], but returns `Ok(None)` at the end of input.
+    #[cfg(feature = "mutf8")]
+    pub fn try_read_cstr_mutf8(&mut self) -> Result<Option<String>, MutfError> {
+        self.try_read_with(Self::read_cstr_mutf8)
+    }
+}
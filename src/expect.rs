@@ -0,0 +1,175 @@
+use crate::{ByteOrder, End, Muncher, Primitive};
+use std::io::{Error, ErrorKind, Read};
+
+/// **Magic number and constant validation methods** (blocking)
+impl<T: Read, O: ByteOrder> Muncher<T, O> {
+    /// Reads `expected.len()` bytes and checks that they
+    /// are exactly equal to `expected`.
+    ///
+    /// Useful for checking magic numbers at the start of files,
+    /// such as `b"RIFF"` or `b"\x89PNG"`.
+    ///
+    /// Returns an [`ErrorKind::InvalidData`] error showing the
+    /// expected and actual bytes (in hex) if they don't match.
+    pub fn expect_bytes(&mut self, expected: &[u8]) -> Result<(), Error> {
+        let actual = self.read_fixed_bytes(expected.len())?;
+        check_bytes(expected, &actual)
+    }
+
+    /// Reads any [`crate::Primitive`] type with the endianness
+    /// specified in the `end` argument, and checks that it's
+    /// equal to `expected`.
+    ///
+    /// Returns an [`ErrorKind::InvalidData`] error showing the
+    /// expected and actual bytes (in hex) if they don't match.
+    ///
+    /// For more info on endianness see [`crate::End`].
+    pub fn expect<E: Primitive + PartialEq>(&mut self, expected: E, end: End) -> Result<(), Error> {
+        let actual = self.read_m::<E>(end)?;
+        check_value(expected, actual, end)
+    }
+
+    /// Checks the upcoming bytes against multiple possible magic numbers,
+    /// returning the index (in `candidates`) of the one that matched.
+    ///
+    /// Candidates are checked from shortest to longest, and only
+    /// as many bytes as needed are read: reading stops as soon as
+    /// no longer candidate can match anymore.
+    ///
+    /// Only the matched candidate is consumed. If no candidate matches
+    /// (also when the input ends first), nothing is consumed and an
+    /// [`ErrorKind::InvalidData`] error is returned, listing all
+    /// the candidates and the bytes that were actually found.
+    ///
+    /// ```
+    /// # use bytemuncher::Muncher;
+    /// # use std::io::Cursor;
+    /// let mut muncher = Muncher::new(Cursor::new(b"RIFX...."));
+    /// let which = muncher.expect_one_of(&[b"RIFF", b"RIFX"]).unwrap();
+    /// assert_eq!(which, 1);
+    /// ```
+    pub fn expect_one_of(&mut self, candidates: &[&[u8]]) -> Result<usize, Error> {
+        let mut at_end = false;
+        loop {
+            match check_one_of(candidates, &self.lookahead, at_end) {
+                OneOf::Matched(i) => {
+                    self.read_fixed_bytes(candidates[i].len())?;
+                    return Ok(i);
+                }
+                OneOf::NoMatch => return Err(no_match(candidates, &self.lookahead)),
+                OneOf::NeedMore(len) => at_end = self.fill_lookahead(len)? == 0,
+            }
+        }
+    }
+
+    /// Appends up to `max` bytes from the inner reader to the look-ahead
+    /// buffer (without consuming them), returning how many
+    /// (`0` at the end of input).
+    fn fill_lookahead(&mut self, max: usize) -> Result<usize, Error> {
+        let buffered = self.lookahead.len();
+        let allowed = self
+            .read_allowance(buffered + max)?
+            .saturating_sub(buffered);
+        if allowed == 0 {
+            return Ok(0);
+        }
+        let mut buf = vec![0; allowed];
+        let n = loop {
+            match self.inner.read(&mut buf) {
+                Err(e) if e.kind() == ErrorKind::Interrupted => {}
+                result => break result?,
+            }
+        };
+        self.lookahead.extend_from_slice(&buf[..n]);
+        Ok(n)
+    }
+}
+
+/// The next step of [`Muncher::expect_one_of`].
+pub(crate) enum OneOf {
+    /// The index of the (shortest) candidate that the upcoming bytes start with.
+    Matched(usize),
+    /// No candidate can match anymore.
+    NoMatch,
+    /// Up to this many more bytes are needed to decide.
+    NeedMore(usize),
+}
+
+/// Decides the next step of [`Muncher::expect_one_of`], given
+/// the `upcoming` bytes so far and whether the input ended after them.
+pub(crate) fn check_one_of(candidates: &[&[u8]], upcoming: &[u8], at_end: bool) -> OneOf {
+    let matched = candidates
+        .iter()
+        .enumerate()
+        .filter(|(_, c)| upcoming.starts_with(c))
+        .min_by_key(|(_, c)| c.len());
+    if let Some((i, _)) = matched {
+        return OneOf::Matched(i);
+    }
+    // The longer candidates that the upcoming bytes could still become
+    let needed = candidates
+        .iter()
+        .filter(|c| c.starts_with(upcoming))
+        .map(|c| c.len() - upcoming.len())
+        .min();
+    match needed {
+        Some(needed) if !at_end => OneOf::NeedMore(needed),
+        _ => OneOf::NoMatch,
+    }
+}
+
+pub(crate) fn check_bytes(expected: &[u8], actual: &[u8]) -> Result<(), Error> {
+    if expected == actual {
+        Ok(())
+    } else {
+        Err(Error::new(
+            ErrorKind::InvalidData,
+            format!(
+                "unexpected bytes: expected [{}], found [{}]",
+                hex(expected),
+                hex(actual)
+            ),
+        ))
+    }
+}
+
+pub(crate) fn check_value<E: Primitive + PartialEq>(
+    expected: E,
+    actual: E,
+    end: End,
+) -> Result<(), Error> {
+    if expected == actual {
+        return Ok(());
+    }
+    // Both are re-encoded so the error shows the raw bytes as they
+    // appear in the input, regardless of what type `E` is.
+    let mut expected_bytes = Vec::new();
+    expected.write_endian(&mut expected_bytes, end)?;
+    let mut actual_bytes = Vec::new();
+    actual.write_endian(&mut actual_bytes, end)?;
+    check_bytes(&expected_bytes, &actual_bytes)
+}
+
+/// The error for when no candidate matches `actual`
+/// (cut to the longest candidate).
+pub(crate) fn no_match(candidates: &[&[u8]], actual: &[u8]) -> Error {
+    let longest = candidates.iter().map(|c| c.len()).max().unwrap_or(0);
+    let actual = &actual[..actual.len().min(longest)];
+    let expected: Vec<String> = candidates.iter().map(|c| format!("[{}]", hex(c))).collect();
+    Error::new(
+        ErrorKind::InvalidData,
+        format!(
+            "unexpected bytes: expected one of {}, found [{}]",
+            expected.join(", "),
+            hex(actual)
+        ),
+    )
+}
+
+pub(crate) fn hex(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
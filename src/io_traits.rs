@@ -1,26 +1,104 @@
 use std::io::{BufRead, Read, Write};
 
-use crate::Muncher;
+use crate::checksum::AnyChecksum;
+use crate::trace::Tracer;
+use crate::{ByteOrder, Muncher};
 
-impl<T: Read> Read for Muncher<T> {
+/// Everything that watches the bytes going through a [`Muncher`].
+#[derive(Default)]
+pub(crate) struct Observers {
+    /// See [`Muncher::begin_checksum`].
+    pub checksum: Option<Box<dyn AnyChecksum>>,
+    /// See [`Muncher::start_trace`].
+    pub trace: Option<Box<Tracer>>,
+}
+
+impl Observers {
+    pub fn is_active(&self) -> bool {
+        self.checksum.is_some() || self.trace.is_some()
+    }
+
+    /// Called with the bytes consumed from the input.
+    pub fn read(&mut self, bytes: &[u8]) {
+        if let Some(checksum) = &mut self.checksum {
+            checksum.update(bytes);
+        }
+        if let Some(trace) = &mut self.trace {
+            trace.read(bytes);
+        }
+    }
+
+    /// Called with the bytes written to the output.
+    pub fn written(&mut self, bytes: &[u8]) {
+        if let Some(checksum) = &mut self.checksum {
+            checksum.update(bytes);
+        }
+        if let Some(trace) = &mut self.trace {
+            trace.written(bytes);
+        }
+    }
+}
+
+impl<T: Read, O: ByteOrder> Read for Muncher<T, O> {
     fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
-        self.inner.read(buf)
+        let allowed = self.read_allowance(buf.len())?;
+        let buf = &mut buf[..allowed];
+        let n = if self.lookahead.is_empty() {
+            self.inner.read(buf)?
+        } else {
+            let n = buf.len().min(self.lookahead.len());
+            buf[..n].copy_from_slice(&self.lookahead[..n]);
+            self.lookahead.consume(n);
+            n
+        };
+        if let Some(journal) = &mut self.journal {
+            journal.extend_from_slice(&buf[..n]);
+        }
+        self.observers.read(&buf[..n]);
+        self.record_consumed(n);
+        Ok(n)
     }
 }
 
-impl<T: BufRead> BufRead for Muncher<T> {
+impl<T: BufRead, O: ByteOrder> BufRead for Muncher<T, O> {
     fn fill_buf(&mut self) -> std::io::Result<&[u8]> {
-        self.inner.fill_buf()
+        let allowed = self.read_allowance(usize::MAX)?;
+        if self.lookahead.is_empty() && (self.journal.is_some() || self.observers.is_active()) {
+            // `consume` has to see the bytes it consumes,
+            // so keep them in the look-ahead buffer.
+            let buf = self.inner.fill_buf()?;
+            let n = buf.len().min(allowed);
+            self.lookahead.extend_from_slice(&buf[..n]);
+            self.inner.consume(n);
+        }
+        let buf = if self.lookahead.is_empty() {
+            self.inner.fill_buf()?
+        } else {
+            &self.lookahead
+        };
+        Ok(&buf[..buf.len().min(allowed)])
     }
 
     fn consume(&mut self, amount: usize) {
-        self.inner.consume(amount);
+        self.record_consumed(amount);
+        if self.lookahead.is_empty() {
+            // Nothing is watching (see `fill_buf`)
+            self.inner.consume(amount);
+            return;
+        }
+        if let Some(journal) = &mut self.journal {
+            journal.extend_from_slice(&self.lookahead[..amount]);
+        }
+        self.observers.read(&self.lookahead[..amount]);
+        self.lookahead.consume(amount);
     }
 }
 
-impl<T: Write> Write for Muncher<T> {
+impl<T: Write, O: ByteOrder> Write for Muncher<T, O> {
     fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
-        self.inner.write(buf)
+        let n = self.inner.write(buf)?;
+        self.observers.written(&buf[..n]);
+        Ok(n)
     }
 
     fn flush(&mut self) -> std::io::Result<()> {
@@ -28,63 +106,192 @@ impl<T: Write> Write for Muncher<T> {
     }
 }
 
-#[cfg(feature = "futures")]
-use futures::io::{AsyncRead, AsyncWrite};
 #[cfg(feature = "tokio")]
-use tokio::io::{AsyncRead, AsyncWrite};
+impl<T: tokio::io::AsyncRead + Unpin, O: ByteOrder> tokio::io::AsyncRead for Muncher<T, O> {
+    fn poll_read(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+        buf: &mut tokio::io::ReadBuf<'_>,
+    ) -> std::task::Poll<std::io::Result<()>> {
+        let this = self.get_mut();
+        let allowed = this.read_allowance(buf.remaining())?;
+        let before = buf.filled().len();
+        let n = if !this.lookahead.is_empty() {
+            let n = allowed.min(this.lookahead.len());
+            buf.put_slice(&this.lookahead[..n]);
+            this.lookahead.consume(n);
+            n
+        } else if allowed < buf.remaining() {
+            // Rare case (the read budget is nearly used up),
+            // so just read into a smaller temporary buffer.
+            let mut temp = vec![0; allowed];
+            let mut limited = tokio::io::ReadBuf::new(&mut temp);
+            std::task::ready!(std::pin::Pin::new(&mut this.inner).poll_read(cx, &mut limited))?;
+            buf.put_slice(limited.filled());
+            limited.filled().len()
+        } else {
+            std::task::ready!(std::pin::Pin::new(&mut this.inner).poll_read(cx, buf))?;
+            buf.filled().len() - before
+        };
+        this.observers.read(&buf.filled()[before..]);
+        this.record_consumed(n);
+        std::task::Poll::Ready(Ok(()))
+    }
+}
 
-#[cfg(feature = "tokio")]
-type PollReadOut = std::task::Poll<std::io::Result<()>>;
 #[cfg(feature = "futures")]
-type PollReadOut = std::task::Poll<std::io::Result<usize>>;
-
-#[cfg(any(feature = "tokio", feature = "futures"))]
-impl<T: AsyncRead + Unpin> AsyncRead for Muncher<T> {
+impl<T: futures::io::AsyncRead + Unpin, O: ByteOrder> futures::io::AsyncRead for Muncher<T, O> {
     fn poll_read(
-        mut self: std::pin::Pin<&mut Self>,
+        self: std::pin::Pin<&mut Self>,
         cx: &mut std::task::Context<'_>,
-        #[cfg(feature = "tokio")] buf: &mut tokio::io::ReadBuf<'_>,
-        #[cfg(feature = "futures")] buf: &mut [u8],
-    ) -> PollReadOut {
-        let pinned = std::pin::pin!(&mut self.inner);
-        pinned.poll_read(cx, buf)
+        buf: &mut [u8],
+    ) -> std::task::Poll<std::io::Result<usize>> {
+        let this = self.get_mut();
+        let allowed = this.read_allowance(buf.len())?;
+        let n = if this.lookahead.is_empty() {
+            std::task::ready!(
+                std::pin::Pin::new(&mut this.inner).poll_read(cx, &mut buf[..allowed])
+            )?
+        } else {
+            let n = allowed.min(this.lookahead.len());
+            buf[..n].copy_from_slice(&this.lookahead[..n]);
+            this.lookahead.consume(n);
+            n
+        };
+        this.observers.read(&buf[..n]);
+        this.record_consumed(n);
+        std::task::Poll::Ready(Ok(n))
     }
 }
 
-#[cfg(any(feature = "tokio", feature = "futures"))]
-impl<T: AsyncWrite + Unpin> AsyncWrite for Muncher<T> {
+#[cfg(feature = "tokio")]
+impl<T: tokio::io::AsyncBufRead + Unpin, O: ByteOrder> tokio::io::AsyncBufRead for Muncher<T, O> {
+    fn poll_fill_buf(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<std::io::Result<&[u8]>> {
+        let this = self.get_mut();
+        let allowed = this.read_allowance(usize::MAX)?;
+        if this.lookahead.is_empty() && this.observers.is_active() {
+            // `consume` has to see the bytes it consumes,
+            // so keep them in the look-ahead buffer.
+            let mut inner = std::pin::Pin::new(&mut this.inner);
+            let buf = std::task::ready!(inner.as_mut().poll_fill_buf(cx))?;
+            let n = buf.len().min(allowed);
+            this.lookahead.extend_from_slice(&buf[..n]);
+            inner.consume(n);
+        }
+        let buf = if this.lookahead.is_empty() {
+            std::task::ready!(std::pin::Pin::new(&mut this.inner).poll_fill_buf(cx))?
+        } else {
+            &this.lookahead
+        };
+        std::task::Poll::Ready(Ok(&buf[..buf.len().min(allowed)]))
+    }
+
+    fn consume(self: std::pin::Pin<&mut Self>, amount: usize) {
+        let this = self.get_mut();
+        this.record_consumed(amount);
+        if this.lookahead.is_empty() {
+            // Nothing is watching (see `poll_fill_buf`)
+            std::pin::Pin::new(&mut this.inner).consume(amount);
+            return;
+        }
+        this.observers.read(&this.lookahead[..amount]);
+        this.lookahead.consume(amount);
+    }
+}
+
+#[cfg(feature = "futures")]
+impl<T: futures::io::AsyncBufRead + Unpin, O: ByteOrder> futures::io::AsyncBufRead
+    for Muncher<T, O>
+{
+    fn poll_fill_buf(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<std::io::Result<&[u8]>> {
+        let this = self.get_mut();
+        let allowed = this.read_allowance(usize::MAX)?;
+        if this.lookahead.is_empty() && this.observers.is_active() {
+            // `consume` has to see the bytes it consumes,
+            // so keep them in the look-ahead buffer.
+            let mut inner = std::pin::Pin::new(&mut this.inner);
+            let buf = std::task::ready!(inner.as_mut().poll_fill_buf(cx))?;
+            let n = buf.len().min(allowed);
+            this.lookahead.extend_from_slice(&buf[..n]);
+            inner.consume(n);
+        }
+        let buf = if this.lookahead.is_empty() {
+            std::task::ready!(std::pin::Pin::new(&mut this.inner).poll_fill_buf(cx))?
+        } else {
+            &this.lookahead
+        };
+        std::task::Poll::Ready(Ok(&buf[..buf.len().min(allowed)]))
+    }
+
+    fn consume(self: std::pin::Pin<&mut Self>, amount: usize) {
+        let this = self.get_mut();
+        this.record_consumed(amount);
+        if this.lookahead.is_empty() {
+            // Nothing is watching (see `poll_fill_buf`)
+            std::pin::Pin::new(&mut this.inner).consume(amount);
+            return;
+        }
+        this.observers.read(&this.lookahead[..amount]);
+        this.lookahead.consume(amount);
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl<T: tokio::io::AsyncWrite + Unpin, O: ByteOrder> tokio::io::AsyncWrite for Muncher<T, O> {
     fn poll_write(
         mut self: std::pin::Pin<&mut Self>,
         cx: &mut std::task::Context<'_>,
         buf: &[u8],
     ) -> std::task::Poll<std::io::Result<usize>> {
-        let pinned = std::pin::pin!(&mut self.inner);
-        pinned.poll_write(cx, buf)
+        let n = std::task::ready!(std::pin::Pin::new(&mut self.inner).poll_write(cx, buf))?;
+        self.observers.written(&buf[..n]);
+        std::task::Poll::Ready(Ok(n))
     }
 
     fn poll_flush(
         mut self: std::pin::Pin<&mut Self>,
         cx: &mut std::task::Context<'_>,
     ) -> std::task::Poll<std::io::Result<()>> {
-        let pinned = std::pin::pin!(&mut self.inner);
-        pinned.poll_flush(cx)
+        std::pin::Pin::new(&mut self.inner).poll_flush(cx)
     }
 
-    #[cfg(feature = "tokio")]
     fn poll_shutdown(
         mut self: std::pin::Pin<&mut Self>,
         cx: &mut std::task::Context<'_>,
     ) -> std::task::Poll<std::io::Result<()>> {
-        let pinned = std::pin::pin!(&mut self.inner);
-        pinned.poll_shutdown(cx)
+        std::pin::Pin::new(&mut self.inner).poll_shutdown(cx)
+    }
+}
+
+#[cfg(feature = "futures")]
+impl<T: futures::io::AsyncWrite + Unpin, O: ByteOrder> futures::io::AsyncWrite for Muncher<T, O> {
+    fn poll_write(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+        buf: &[u8],
+    ) -> std::task::Poll<std::io::Result<usize>> {
+        let n = std::task::ready!(std::pin::Pin::new(&mut self.inner).poll_write(cx, buf))?;
+        self.observers.written(&buf[..n]);
+        std::task::Poll::Ready(Ok(n))
+    }
+
+    fn poll_flush(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<std::io::Result<()>> {
+        std::pin::Pin::new(&mut self.inner).poll_flush(cx)
     }
 
-    #[cfg(feature = "futures")]
     fn poll_close(
         mut self: std::pin::Pin<&mut Self>,
         cx: &mut std::task::Context<'_>,
     ) -> std::task::Poll<std::io::Result<()>> {
-        let pinned = std::pin::pin!(&mut self.inner);
-        pinned.poll_close(cx)
+        std::pin::Pin::new(&mut self.inner).poll_close(cx)
     }
 }
@@ -1,20 +1,40 @@
 #![doc = include_str!("../README.md")]
 #![allow(clippy::missing_errors_doc)]
+#![forbid(unsafe_code)]
 
 #[cfg(feature = "mutf8")]
 mod mutf;
 
+mod array;
+#[cfg(any(feature = "tokio", feature = "futures"))]
+mod async_ext;
+#[cfg(any(feature = "tokio", feature = "futures"))]
+pub mod backend;
+mod budget;
+pub mod checksum;
+#[cfg(feature = "tokio-util")]
+pub mod codec;
+mod collection;
+mod depth;
+mod detect;
+mod eof;
+mod expect;
 mod io_traits;
+mod order;
+pub mod pack;
+mod peek;
+mod records;
+mod sans_io;
+pub mod schema;
+mod slice_muncher;
 mod string;
 #[cfg(test)]
 mod tests;
+#[cfg(feature = "tokio")]
+mod timeout;
+pub mod trace;
 mod traits;
 
-#[cfg(all(feature = "tokio", feature = "futures"))]
-compile_error!(
-    "Feature 'tokio' and 'futures' are mutually exclusive (both were enabled). Please choose one. If you somehow need both, try `tokio_util::compat`"
-);
-
 /// A helpful wrapper around any [`std::io::Read`] / [`std::io::Write`] type.
 ///
 /// You can put in any `Read`/`Write` type and get access to the additional
@@ -112,14 +132,39 @@ compile_error!(
 ///   returns `bool` if it fit in or not.
 ///
 /// More destinations may come in future versions.
-pub struct Muncher<T> {
+pub struct Muncher<T, O = End> {
     inner: T,
     alloc_limit_bytes: usize,
+    eager_alloc: bool,
+    budget: budget::Budget,
+    /// Total bytes consumed from the input
+    /// (unlike the budget usage, this is never reset).
+    position: u64,
+    max_depth: usize,
+    depth: usize,
+    order: O,
+    /// Bytes pulled out of `inner` by peeking, that haven't been consumed yet.
+    /// These are always read before anything else in `inner`.
+    lookahead: peek::Lookahead,
+    /// Record of consumed bytes during a [`Muncher::try_parse`],
+    /// so they can be put back into `lookahead` on failure.
+    journal: Option<Vec<u8>>,
+    /// Checksum and trace of the bytes going through.
+    observers: io_traits::Observers,
+    #[cfg(feature = "tokio")]
+    timeouts: timeout::Timeouts,
 }
 
 impl<T> Muncher<T> {
     /// Creates a new [`Muncher`] with the default configuration:
     /// - Allocation limit of 1 GB: [`Muncher::set_max_alloc`]
+    /// - Incremental (non-eager) allocation: [`Muncher::set_eager_alloc`]
+    /// - No cumulative allocation or read budget:
+    ///   [`Muncher::set_alloc_budget`], [`Muncher::set_read_budget`]
+    /// - Max nesting depth of 128: [`Muncher::set_max_depth`]
+    /// - No async read timeout (crate feature `tokio`):
+    ///   `Muncher::set_read_timeout`, `Muncher::set_read_deadline`
+    /// - Default endianness of little endian: [`Muncher::set_end`]
     ///
     /// ... with more options coming in the future.
     ///
@@ -131,10 +176,58 @@ impl<T> Muncher<T> {
     /// Muncher::new(std::io::Cursor::new(SOMETHING))
     /// # ;
     /// ```
+    ///
+    /// If the endianness of your format is known at compile time,
+    /// see [`Muncher::with_order`].
     pub fn new(inner: T) -> Self {
+        Self::with_order(inner, End::Little)
+    }
+
+    /// Sets the default endianness of the [`Muncher`],
+    /// for formats that decide their endianness once per file
+    /// (like TIFF or ELF).
+    ///
+    /// Default: [`End::Little`]
+    ///
+    /// This is also set automatically by endianness detection
+    /// methods like [`Muncher::detect_end_from_magic`].
+    pub fn set_end(&mut self, end: End) -> &mut Self {
+        self.order = end;
+        self
+    }
+}
+
+impl<T, O: ByteOrder> Muncher<T, O> {
+    /// Creates a new [`Muncher`] with the default endianness
+    /// decided by the `order` type (see [`ByteOrder`]).
+    ///
+    /// If you pass [`LittleEndian`], [`BigEndian`] or [`NativeEndian`],
+    /// the default endianness is fixed by the type and can't be changed
+    /// (and it's decided at compile time, see [`ByteOrder`]).
+    ///
+    /// ```
+    /// # use bytemuncher::{BigEndian, Muncher};
+    /// # use std::io::Cursor;
+    /// let mut muncher = Muncher::with_order(Cursor::new([0x12, 0x34]), BigEndian);
+    /// assert_eq!(muncher.read::<u16>().unwrap(), 0x1234);
+    /// ```
+    ///
+    /// Other than that, it's the same as [`Muncher::new`].
+    pub fn with_order(inner: T, order: O) -> Self {
         Self {
             inner,
             alloc_limit_bytes: 1024 * 1024 * 1024,
+            eager_alloc: false,
+            budget: budget::Budget::default(),
+            position: 0,
+            max_depth: 128,
+            depth: 0,
+            order,
+            lookahead: peek::Lookahead::default(),
+            journal: None,
+            observers: io_traits::Observers::default(),
+            #[cfg(feature = "tokio")]
+            timeouts: timeout::Timeouts::default(),
         }
     }
 
@@ -151,21 +244,116 @@ impl<T> Muncher<T> {
         self
     }
 
-    pub(crate) fn verify_len(&mut self, len: usize) -> Result<(), std::io::Error> {
-        if len > self.alloc_limit_bytes {
-            Err(std::io::Error::new(
-                std::io::ErrorKind::InvalidData,
-                format!(
-                    "length of string is too large ({len} bytes): surpassed the default (customizable) limit of {} bytes",
-                    self.alloc_limit_bytes
-                ),
-            ))
+    /// Sets whether buffers for length-prefixed and fixed size reads
+    /// should be allocated all at once (eagerly), or grown in bounded
+    /// chunks as the data actually arrives.
+    ///
+    /// Default: `false` (grow in chunks)
+    ///
+    /// With untrusted input, a forged length (say, 900 MB) could pass the
+    /// allocation limit ([`Muncher::set_max_alloc`]) and allocate all
+    /// of that before discovering that the input is only 10 bytes long.
+    /// Growing in chunks keeps the memory used proportional to the real input.
+    ///
+    /// If your input is trusted, enabling this avoids
+    /// the (small) cost of growing the buffers.
+    pub fn set_eager_alloc(&mut self, eager: bool) -> &mut Self {
+        self.eager_alloc = eager;
+        self
+    }
+
+    /// Returns the default endianness of the [`Muncher`]
+    /// (see [`Muncher::set_end`] and [`Muncher::with_order`]).
+    #[must_use]
+    pub fn end(&self) -> End {
+        self.order.end()
+    }
+
+    /// Returns how many bytes have been consumed from the input so far
+    /// (peeked bytes and rolled back [`Muncher::try_parse`] reads don't count).
+    ///
+    /// This is the offset in the input, counted from where the inner reader
+    /// was when the [`Muncher`] was created. Unlike [`Muncher::consumed_bytes`],
+    /// it isn't reset by [`Muncher::reset_budget_usage`] or [`Muncher::with_budget`].
+    #[must_use]
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// Picks `end` if specified, otherwise the default endianness.
+    pub(crate) fn resolve_end(&self, end: impl Into<Option<End>>) -> End {
+        end.into().unwrap_or(self.order.end())
+    }
+
+    /// Picks `end` if specified, otherwise the default byte order
+    /// (without turning it into an [`End`], unlike [`Muncher::resolve_end`]).
+    pub(crate) fn resolve_order(&self, end: impl Into<Option<End>>) -> order::Resolved<O> {
+        order::Resolved::new(end, self.order)
+    }
+
+    /// How many items (of `item_size` bytes each) to allocate at a time
+    /// when reading a buffer whose length came from the input.
+    pub(crate) fn alloc_chunk(&self, item_size: usize) -> usize {
+        if self.eager_alloc {
+            usize::MAX
         } else {
-            Ok(())
+            (ALLOC_CHUNK_BYTES / item_size.max(1)).max(1)
         }
     }
+
+    /// Checks that reading `len` bytes stays within the allocation limit
+    /// (see [`Muncher::set_max_alloc`]) and budget (see [`Muncher::set_alloc_budget`]),
+    /// counting it towards the budget.
+    pub(crate) fn verify_len(&mut self, len: usize) -> Result<(), std::io::Error> {
+        self.verify_max_alloc(len)?;
+        self.charge_alloc(len)
+    }
+
+    /// Like [`Muncher::verify_len`] but without counting it towards the budget,
+    /// for containers whose elements are counted as they're read.
+    pub(crate) fn check_len(&self, len: usize) -> Result<(), std::io::Error> {
+        self.verify_max_alloc(len)?;
+        self.check_alloc(len)
+    }
+
+    /// Like [`Muncher::verify_len`] but only checks the allocation limit.
+    pub(crate) fn verify_max_alloc(&self, len: usize) -> Result<(), std::io::Error> {
+        check_max_alloc(len, self.alloc_limit_bytes)
+    }
+}
+
+/// Checks that a length that came from the input is within
+/// the allocation limit of `alloc_limit_bytes`.
+pub(crate) fn check_max_alloc(len: usize, alloc_limit_bytes: usize) -> Result<(), std::io::Error> {
+    if len > alloc_limit_bytes {
+        Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!(
+                "length of string is too large ({len} bytes): surpassed the default (customizable) limit of {alloc_limit_bytes} bytes"
+            ),
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+/// Converts a length (or count) to write into its prefix type `<E>`,
+/// failing with [`std::io::ErrorKind::InvalidInput`] if it doesn't fit.
+pub(crate) fn prefix<E: Primitive>(len: usize) -> Result<E, std::io::Error> {
+    if E::from_usize(len).into_usize() == len {
+        Ok(E::from_usize(len))
+    } else {
+        Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!("length {len} doesn't fit the length prefix type"),
+        ))
+    }
 }
 
+/// How many bytes are allocated at a time for reads whose length
+/// came from the input (see [`Muncher::set_eager_alloc`]).
+const ALLOC_CHUNK_BYTES: usize = 64 * 1024;
+
 /// The endianness of a value.
 ///
 /// Endianness represents the order
@@ -268,8 +456,14 @@ pub const IS_TARGET_LITTLE_ENDIAN: bool = true;
 #[cfg(target_endian = "big")]
 pub const IS_TARGET_LITTLE_ENDIAN: bool = false;
 
+pub use depth::DepthGuard;
+pub use detect::Bom;
 #[cfg(feature = "mutf8")]
 pub use mutf::MutfError;
+pub use order::{BigEndian, ByteOrder, LittleEndian, NativeEndian};
+pub use records::Records;
+pub use sans_io::{Parsed, PushMuncher};
+pub use slice_muncher::SliceMuncher;
 // /// Important exports of the `mutf8` crate
 // #[cfg(feature = "mutf8")]
 // pub mod mutf_8 {
@@ -278,6 +472,8 @@ pub use mutf::MutfError;
 //     pub use mutf8::{mutf8_to_utf8, utf8_to_mutf8};
 // }
 
+#[cfg(any(feature = "tokio", feature = "futures"))]
+pub use async_ext::{AsyncMunchBufRead, AsyncMunchRead, AsyncMunchWrite};
 #[cfg(any(feature = "tokio", feature = "futures"))]
 pub use traits::AsyncPrimitive;
 pub use traits::Primitive;
@@ -3,22 +3,17 @@ use std::{
     io::{BufRead, Read, Write},
 };
 
-use crate::{End, Muncher, Primitive};
+use crate::sans_io::{Bytes, Encoded, Prefixed};
+use crate::string::string;
+use crate::trace::{self, TraceKind};
+use crate::{ByteOrder, End, Muncher, Parsed, Primitive, PushMuncher, SliceMuncher};
 
 mod error;
 
 pub use error::MutfError;
 
-#[cfg(feature = "futures")]
-use futures::io::{AsyncBufRead, AsyncRead, AsyncWriteExt};
-#[cfg(feature = "tokio")]
-use tokio::io::{AsyncBufRead, AsyncRead, AsyncWriteExt};
-
-#[cfg(any(feature = "tokio", feature = "futures"))]
-use crate::traits::AsyncPrimitive;
-
 /// **Size-prefixed MUTF-8 string methods**
-impl<T: Read> Muncher<T> {
+impl<T: Read, O: ByteOrder> Muncher<T, O> {
     /// Reads a MUTF-8 string prefixed by a length (number of bytes) of type `<E>`,
     /// then converts it to a UTF-8 [`String`].
     /// This is a niche format. For most cases, this is
@@ -26,50 +21,19 @@ impl<T: Read> Muncher<T> {
     ///
     /// If you want raw MUTF-8, use [`Muncher::read_pref_bytes`].
     ///
-    /// Through the `end` argument you can choose the endianness of the length field.
+    /// Through the `end` argument you can choose the endianness of the length field,
+    /// or pass `None` to use the default endianness (see [`Muncher::set_end`]).
     ///
     /// For more info on endianness see [`crate::End`].
     ///
     /// For more info on MUTF-8 see <https://crates.io/crates/mutf8>.
-    pub fn read_pref_mutf8<E: Primitive>(&mut self, end: End) -> Result<String, MutfError> {
-        let buf = self.read_pref_bytes::<E>(end)?;
-        mutf2utf(&buf)
-    }
-
-    /// Reads `len` number of bytes into a MUTF-8 string,
-    /// then converts it to a UTF-8 [`String`].
-    /// This is a niche format. For most cases, this is
-    /// not what you need and you should instead use UTF-8.
-    ///
-    /// If you want raw MUTF-8, use [`Muncher::read_fixed_bytes`].
-    ///
-    /// For more info on MUTF-8 see <https://crates.io/crates/mutf8>.
-    pub fn read_fixed_mutf8(&mut self, len: usize) -> Result<String, MutfError> {
-        let buf = self.read_fixed_bytes(len)?;
-        mutf2utf(&buf)
-    }
-}
-
-#[cfg(any(feature = "tokio", feature = "futures"))]
-impl<T: AsyncRead + Unpin> Muncher<T> {
-    /// Reads a MUTF-8 string prefixed by a length (number of bytes) of type `<E>`,
-    /// then converts it to a UTF-8 [`String`].
-    /// This is a niche format. For most cases, this is
-    /// not what you need and you should instead use UTF-8.
-    ///
-    /// If you want raw MUTF-8, use [`Muncher::read_pref_bytes`].
-    ///
-    /// Through the `end` argument you can choose the endianness of the length field.
-    ///
-    /// For more info on endianness see [`crate::End`].
-    ///
-    /// For more info on MUTF-8 see <https://crates.io/crates/mutf8>.
-    pub async fn read_pref_mutf8_a<E: AsyncPrimitive>(
+    pub fn read_pref_mutf8<E: Primitive>(
         &mut self,
-        end: End,
+        end: impl Into<Option<End>>,
     ) -> Result<String, MutfError> {
-        let buf = self.read_pref_bytes_a::<E>(end).await?;
-        mutf2utf(&buf)
+        self.traced(TraceKind::Read, "read_pref_mutf8", string, |m| {
+            mutf2utf(&m.read_pref_bytes::<E>(end)?)
+        })
     }
 
     /// Reads `len` number of bytes into a MUTF-8 string,
@@ -80,22 +44,31 @@ impl<T: AsyncRead + Unpin> Muncher<T> {
     /// If you want raw MUTF-8, use [`Muncher::read_fixed_bytes`].
     ///
     /// For more info on MUTF-8 see <https://crates.io/crates/mutf8>.
-    pub async fn read_fixed_mutf8_a(&mut self, len: usize) -> Result<String, MutfError> {
-        let buf = self.read_fixed_bytes_a(len).await?;
-        mutf2utf(&buf)
+    pub fn read_fixed_mutf8(&mut self, len: usize) -> Result<String, MutfError> {
+        self.traced(TraceKind::Read, "read_fixed_mutf8", string, |m| {
+            mutf2utf(&m.read_fixed_bytes(len)?)
+        })
     }
 }
 
-fn mutf2utf(buf: &[u8]) -> Result<String, MutfError> {
+pub(crate) fn mutf2utf(buf: &[u8]) -> Result<String, MutfError> {
     Ok(String::from_utf8_lossy(&mutf8::mutf8_to_utf8(buf)?).to_string())
 }
 
-fn utf2mutf(s: &str) -> Result<Cow<'_, [u8]>, MutfError> {
+/// Like [`mutf2utf`], but borrows from `buf` if it didn't need converting.
+pub(crate) fn mutf2utf_cow(buf: &[u8]) -> Result<Cow<'_, str>, MutfError> {
+    Ok(match mutf8::mutf8_to_utf8(buf)? {
+        Cow::Borrowed(b) => Cow::Borrowed(crate::string::bytes2str(b)?),
+        Cow::Owned(v) => Cow::Owned(String::from_utf8_lossy(&v).into_owned()),
+    })
+}
+
+pub(crate) fn utf2mutf(s: &str) -> Result<Cow<'_, [u8]>, MutfError> {
     Ok(mutf8::utf8_to_mutf8(s.as_bytes())?)
 }
 
 /// **MUTF-8 string and buffer related methods that require `T: `[`std::io::BufRead`]**
-impl<T: BufRead> Muncher<T> {
+impl<T: BufRead, O: ByteOrder> Muncher<T, O> {
     /// Reads a C-style string (ending with `\0` null byte)
     /// in the MUTF-8 format (tries to parse, fails if invalid).
     ///
@@ -107,70 +80,112 @@ impl<T: BufRead> Muncher<T> {
     ///
     /// For more info on MUTF-8 see <https://crates.io/crates/mutf8>.
     pub fn read_cstr_mutf8(&mut self) -> Result<String, MutfError> {
-        mutf2utf(&self.read_cstr_bytes()?)
-    }
-}
-
-#[cfg(any(feature = "tokio", feature = "futures"))]
-impl<T: AsyncBufRead + Unpin> Muncher<T> {
-    pub async fn read_cstr_mutf8_a(&mut self) -> Result<String, MutfError> {
-        mutf2utf(&self.read_cstr_bytes_a().await?)
+        self.traced(TraceKind::Read, "read_cstr_mutf8", string, |m| {
+            mutf2utf(&m.read_cstr_bytes()?)
+        })
     }
 }
 
-impl<T: Write> Muncher<T> {
+impl<T: Write, O: ByteOrder> Muncher<T, O> {
     /// Writes a MUTF-8 string (converted from UTF-8)
     /// prefixed by a length (number of bytes) of type `<E>`.
     ///
-    /// Through the `end` argument you can choose the endianness of the length field.
+    /// Through the `end` argument you can choose the endianness of the length field,
+    /// or pass `None` to use the default endianness (see [`Muncher::set_end`]).
     ///
     /// For more info on endianness see [`crate::End`].
     pub fn write_pref_mutf8<P: Primitive>(
         &mut self,
         mutf: &str,
-        end: End,
+        end: impl Into<Option<End>>,
     ) -> Result<(), MutfError> {
-        self.write_pref_bytes::<P>(end, &utf2mutf(mutf)?)?;
-        Ok(())
+        let order = self.resolve_order(end);
+        self.traced(
+            TraceKind::Write,
+            "write_pref_mutf8",
+            |()| trace::describe_str(mutf),
+            |m| {
+                let buf = utf2mutf(mutf)?;
+                m.write_encoded(&Encoded::prefixed::<P>(buf.len(), buf, order)?)?;
+                Ok(())
+            },
+        )
     }
 
     /// Writes a MUTF-8 string (converted from UTF-8),
     /// with a null terminator.
     pub fn write_cstr_mutf8(&mut self, mutf: &str) -> Result<(), MutfError> {
-        let buf = utf2mutf(mutf)?;
-        self.write(&buf)?;
-        if !buf.ends_with(&[0]) {
-            self.write_le(0u8)?;
-        }
-        Ok(())
+        self.traced(
+            TraceKind::Write,
+            "write_cstr_mutf8",
+            |()| trace::describe_str(mutf),
+            |m| {
+                m.write_encoded(&Encoded::cstr(utf2mutf(mutf)?))?;
+                Ok(())
+            },
+        )
     }
 }
 
-#[cfg(any(feature = "tokio", feature = "futures"))]
-impl<T: AsyncWriteExt + Unpin> Muncher<T> {
-    /// Writes a MUTF-8 string (converted from UTF-8)
-    /// prefixed by a length (number of bytes) of type `<E>`.
+/// **MUTF-8 string methods** (push)
+impl<O: ByteOrder> PushMuncher<O> {
+    /// Reads a MUTF-8 string prefixed by a length (number of bytes) of type `<E>`,
+    /// then converts it to a UTF-8 [`String`].
     ///
-    /// Through the `end` argument you can choose the endianness of the length field.
+    /// See [`Muncher::read_pref_mutf8`] for more info.
+    pub fn read_pref_mutf8<E: Primitive>(
+        &mut self,
+        end: impl Into<Option<End>>,
+    ) -> Result<Parsed<String>, MutfError> {
+        let order = self.resolve_order(end);
+        self.decode_then(Prefixed::<E, _, _>::new(order, Bytes::new), |buf| {
+            mutf2utf(&buf)
+        })
+    }
+
+    /// Reads `len` number of bytes into a MUTF-8 string,
+    /// then converts it to a UTF-8 [`String`].
     ///
-    /// For more info on endianness see [`crate::End`].
-    pub async fn write_pref_mutf8_a<P: AsyncPrimitive>(
+    /// See [`Muncher::read_fixed_mutf8`] for more info.
+    pub fn read_fixed_mutf8(&mut self, len: usize) -> Result<Parsed<String>, MutfError> {
+        self.decode_then(Bytes::new(len), |buf| mutf2utf(&buf))
+    }
+
+    /// Reads a C-style string (ending with `\0` null byte)
+    /// in the MUTF-8 format (tries to parse, fails if invalid).
+    ///
+    /// See [`Muncher::read_cstr_mutf8`] for more info.
+    pub fn read_cstr_mutf8(&mut self) -> Result<Parsed<String>, MutfError> {
+        self.read_cstr_then(mutf2utf)
+    }
+}
+
+/// **MUTF-8 string methods** (zero-copy)
+impl<'a, O: ByteOrder> SliceMuncher<'a, O> {
+    /// Reads a MUTF-8 string prefixed by a length (number of bytes) of type `<E>`,
+    /// then converts it to UTF-8 (only allocating if it differs).
+    ///
+    /// See [`Muncher::read_pref_mutf8`] for more info.
+    pub fn read_pref_mutf8<E: Primitive>(
         &mut self,
-        mutf: &str,
-        end: End,
-    ) -> Result<(), MutfError> {
-        self.write_pref_bytes_a::<P>(end, &utf2mutf(mutf)?).await?;
-        Ok(())
+        end: impl Into<Option<End>>,
+    ) -> Result<Cow<'a, str>, MutfError> {
+        self.read_pref_with::<E, _, _>(end, mutf2utf_cow)
     }
 
-    /// Writes a MUTF-8 string (converted from UTF-8),
-    /// with a null terminator.
-    pub async fn write_cstr_mutf8_a(&mut self, mutf: &str) -> Result<(), MutfError> {
-        let buf = utf2mutf(mutf)?;
-        self.write(&buf).await?;
-        if !buf.ends_with(&[0]) {
-            self.write_le_a(0u8).await?;
-        }
-        Ok(())
+    /// Reads `len` number of bytes into a MUTF-8 string,
+    /// then converts it to UTF-8 (only allocating if it differs).
+    ///
+    /// See [`Muncher::read_fixed_mutf8`] for more info.
+    pub fn read_fixed_mutf8(&mut self, len: usize) -> Result<Cow<'a, str>, MutfError> {
+        self.read_fixed_with(len, mutf2utf_cow)
+    }
+
+    /// Reads a C-style string (ending with `\0` null byte) in the MUTF-8 format,
+    /// then converts it to UTF-8 (only allocating if it differs).
+    ///
+    /// See [`Muncher::read_cstr_mutf8`] for more info.
+    pub fn read_cstr_mutf8(&mut self) -> Result<Cow<'a, str>, MutfError> {
+        self.read_cstr_with(mutf2utf_cow)
     }
 }
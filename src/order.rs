@@ -0,0 +1,137 @@
+use crate::{End, IS_TARGET_LITTLE_ENDIAN};
+
+/// Decides the default endianness of a [`crate::Muncher`],
+/// either at compile time or at runtime.
+///
+/// This is the second generic parameter of `Muncher<T, O>`:
+/// - [`End`] (the default): The endianness is stored and checked at runtime,
+///   and can be changed with [`crate::Muncher::set_end`]
+///   or detected from the input (for formats like TIFF or ELF).
+/// - [`LittleEndian`], [`BigEndian`], [`NativeEndian`]: The endianness
+///   is fixed by the type, so it can't be changed by mistake
+///   (there's no `set_end` or endianness detection for these).
+///   Useful for formats with a known endianness (like network packets).
+///
+/// The fixed orders decide whether to swap bytes at compile time
+/// (see [`ByteOrder::SWAP`]). The methods that use the default endianness
+/// (like [`crate::Muncher::read`], or the length-prefixed ones given `None`)
+/// convert the built-in integer and float types without checking an
+/// [`End`] value. The endianness is only checked at runtime with [`End`],
+/// and in the async methods (which always take an [`End`]).
+///
+/// Use [`crate::Muncher::with_order`] to pick one.
+///
+/// Methods that take an explicit `end: End` argument (like
+/// [`crate::Muncher::read_m`]) work the same regardless of this.
+pub trait ByteOrder: Copy + Unpin {
+    /// Whether values in this byte order have their bytes reversed
+    /// compared to the target platform, if that's fixed by the type
+    /// (`None` if it's only known at runtime, like for [`End`]).
+    ///
+    /// This must agree with [`ByteOrder::end`].
+    const SWAP: Option<bool> = None;
+
+    /// Returns the endianness represented by this type.
+    fn end(self) -> End;
+
+    /// Whether values in this byte order have their bytes reversed
+    /// compared to the target platform.
+    ///
+    /// This is [`ByteOrder::SWAP`] if set, so it's a constant
+    /// for the fixed orders. Otherwise it checks [`ByteOrder::end`].
+    #[inline]
+    #[must_use]
+    fn swaps(self) -> bool {
+        match Self::SWAP {
+            Some(swap) => swap,
+            None => !self.end().is_target_endian(),
+        }
+    }
+}
+
+impl ByteOrder for End {
+    #[inline]
+    fn end(self) -> End {
+        self
+    }
+}
+
+/// Compile-time little endian [`ByteOrder`].
+///
+/// See [`End::Little`] for more info.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct LittleEndian;
+
+impl ByteOrder for LittleEndian {
+    const SWAP: Option<bool> = Some(!IS_TARGET_LITTLE_ENDIAN);
+
+    #[inline]
+    fn end(self) -> End {
+        End::Little
+    }
+}
+
+/// Compile-time big endian [`ByteOrder`].
+///
+/// See [`End::Big`] for more info.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct BigEndian;
+
+impl ByteOrder for BigEndian {
+    const SWAP: Option<bool> = Some(IS_TARGET_LITTLE_ENDIAN);
+
+    #[inline]
+    fn end(self) -> End {
+        End::Big
+    }
+}
+
+/// Compile-time native endian [`ByteOrder`]
+/// (as per the target platform).
+///
+/// See [`End::Native`] for more info.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct NativeEndian;
+
+impl ByteOrder for NativeEndian {
+    const SWAP: Option<bool> = Some(false);
+
+    #[inline]
+    fn end(self) -> End {
+        End::Native
+    }
+}
+
+/// The byte order picked by an `end: impl Into<Option<End>>` argument:
+/// the given [`End`], or the default order of the muncher for `None`
+/// (so a fixed default order stays decided at compile time).
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Resolved<O> {
+    Given(End),
+    Default(O),
+}
+
+impl<O> Resolved<O> {
+    pub(crate) fn new(end: impl Into<Option<End>>, default: O) -> Self {
+        end.into()
+            .map_or(Resolved::Default(default), Resolved::Given)
+    }
+}
+
+impl<O: ByteOrder> ByteOrder for Resolved<O> {
+    #[inline]
+    fn end(self) -> End {
+        match self {
+            Resolved::Given(end) => end,
+            Resolved::Default(order) => order.end(),
+        }
+    }
+
+    #[inline]
+    fn swaps(self) -> bool {
+        match self {
+            Resolved::Given(end) => end.swaps(),
+            Resolved::Default(order) => order.swaps(),
+        }
+    }
+}
@@ -0,0 +1,486 @@
+//! Reading and writing with format strings like Python's
+//! [`struct`](https://docs.python.org/3/library/struct.html) module
+//! (see [`Muncher::unpack`] and [`Muncher::pack`]).
+//!
+//! ```
+//! # use bytemuncher::Muncher;
+//! # use bytemuncher::pack::Value;
+//! # use std::io::Cursor;
+//! let mut data = Vec::new();
+//! let values = [Value::UInt(7), Value::Bytes(b"abc".to_vec()), Value::Bool(true)];
+//! Muncher::new(&mut data).pack("<I4s?", &values).unwrap();
+//! assert_eq!(data, b"\x07\0\0\0abc\0\x01");
+//!
+//! let mut muncher = Muncher::new(Cursor::new(data));
+//! assert_eq!(muncher.unpack("<I4s?").unwrap(), [
+//!     Value::UInt(7),
+//!     Value::Bytes(b"abc\0".to_vec()),
+//!     Value::Bool(true),
+//! ]);
+//! ```
+//!
+//! # Format strings
+//!
+//! The first character can set the byte order (and alignment):
+//!
+//! | Character | Byte order | Size | Alignment |
+//! |---|---|---|---|
+//! | `@` (default) | native | native | native |
+//! | `=` | native | standard | none |
+//! | `<` | little endian | standard | none |
+//! | `>` or `!` | big endian | standard | none |
+//!
+//! Followed by any number of these codes, each optionally preceded by a count
+//! (whitespace between them is ignored):
+//!
+//! | Code | Type | [`Value`] | Standard size |
+//! |---|---|---|---|
+//! | `x` | pad byte | (none) | 1 |
+//! | `c` | `u8` | [`Value::Bytes`] of length 1 | 1 |
+//! | `b` / `B` | `i8` / `u8` | [`Value::Int`] / [`Value::UInt`] | 1 |
+//! | `?` | `bool` | [`Value::Bool`] | 1 |
+//! | `h` / `H` | `i16` / `u16` | [`Value::Int`] / [`Value::UInt`] | 2 |
+//! | `i` / `I` | `i32` / `u32` | [`Value::Int`] / [`Value::UInt`] | 4 |
+//! | `l` / `L` | `i32` / `u32` (native: C `long`) | [`Value::Int`] / [`Value::UInt`] | 4 |
+//! | `q` / `Q` | `i64` / `u64` | [`Value::Int`] / [`Value::UInt`] | 8 |
+//! | `n` / `N` | `isize` / `usize` (native only) | [`Value::Int`] / [`Value::UInt`] | |
+//! | `P` | `usize` (native only) | [`Value::UInt`] | |
+//! | `f` / `d` | `f32` / `f64` | [`Value::Float`] | 4 / 8 |
+//! | `s` | bytes | [`Value::Bytes`] | count |
+//! | `p` | Pascal string (1 byte length, then bytes) | [`Value::Bytes`] | count |
+//!
+//! A count repeats the code that many times, except for `s` and `p`,
+//! where it's the size in bytes (for `p`, including the length byte).
+//!
+//! Half precision floats (`e`) aren't supported.
+//!
+//! Use [`crate::struct_format!`] to check a format string at compile time.
+
+use std::io::{Error, ErrorKind, Read, Write};
+
+use crate::{ByteOrder, End, Muncher};
+
+/// A dynamically typed value, read by [`Muncher::unpack`] and
+/// [`Muncher::read_schema`], or written by [`Muncher::pack`] and [`Muncher::write_schema`].
+///
+/// Format strings only use the scalar and bytes variants,
+/// while schemas also use strings, lists and records.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Bool(bool),
+    /// A signed integer.
+    Int(i64),
+    /// An unsigned integer.
+    UInt(u64),
+    Float(f64),
+    Bytes(Vec<u8>),
+    Str(String),
+    List(Vec<Value>),
+    /// The fields of a record, by name, in order.
+    Record(Vec<(String, Value)>),
+}
+
+impl Value {
+    /// Returns the field `name` if this is a [`Value::Record`] that has it.
+    #[must_use]
+    pub fn get(&self, name: &str) -> Option<&Value> {
+        match self {
+            Value::Record(fields) => fields.iter().find(|(n, _)| n == name).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+}
+
+/// Checks `format` at compile time, and returns it.
+///
+/// ```
+/// # use bytemuncher::{Muncher, struct_format};
+/// # use std::io::Cursor;
+/// const HEADER: &str = struct_format!(">4sHH");
+///
+/// let mut muncher = Muncher::new(Cursor::new(b"RIFF\x00\x01\x00\x02"));
+/// assert_eq!(muncher.unpack(HEADER).unwrap().len(), 3);
+/// ```
+///
+/// An invalid format string fails to compile:
+///
+/// ```compile_fail
+/// # use bytemuncher::struct_format;
+/// const HEADER: &str = struct_format!(">4sHz");
+/// ```
+#[macro_export]
+macro_rules! struct_format {
+    ($format:expr) => {{
+        const FORMAT: &str = $format;
+        const _: () = match $crate::pack::calcsize(FORMAT) {
+            Ok(_) => (),
+            Err(error) => panic!("{}", error),
+        };
+        FORMAT
+    }};
+}
+
+/// Returns the number of bytes that `format` reads or writes,
+/// or why it's invalid.
+///
+/// This is a `const fn`, for checking formats at compile time
+/// (see [`crate::struct_format!`]).
+pub const fn calcsize(format: &str) -> Result<usize, &'static str> {
+    let format = format.as_bytes();
+    let (mode, mut pos) = Mode::parse(format);
+    let mut size: usize = 0;
+    while let Some((item, next)) = match next_item(format, pos, mode) {
+        Ok(item) => item,
+        Err(error) => return Err(error),
+    } {
+        pos = next;
+        size = match item.end_offset(size) {
+            Some(size) => size,
+            None => return Err(TOO_LARGE),
+        };
+    }
+    Ok(size)
+}
+
+const TOO_LARGE: &str = "format is too large";
+
+/// The byte order and alignment set by the first character.
+#[derive(Debug, Clone, Copy)]
+struct Mode {
+    end: End,
+    native: bool,
+}
+
+impl Mode {
+    /// Returns the mode and where the items start.
+    const fn parse(format: &[u8]) -> (Self, usize) {
+        let (end, native) = match format.first() {
+            Some(b'@') => (End::Native, true),
+            Some(b'=') => (End::Native, false),
+            Some(b'<') => (End::Little, false),
+            Some(b'>' | b'!') => (End::Big, false),
+            _ => {
+                return (
+                    Self {
+                        end: End::Native,
+                        native: true,
+                    },
+                    0,
+                );
+            }
+        };
+        (Self { end, native }, 1)
+    }
+}
+
+/// A code and its count.
+#[derive(Debug, Clone, Copy)]
+struct Item {
+    code: u8,
+    count: usize,
+    /// The size of one value (or of the whole item for `s` and `p`).
+    size: usize,
+    /// Whether to align it to `size` (in native mode).
+    align: bool,
+}
+
+impl Item {
+    /// How many values of `size` the item has.
+    const fn times(&self) -> usize {
+        match self.code {
+            b's' | b'p' => 1,
+            _ => self.count,
+        }
+    }
+
+    /// The padding needed before the item at `offset`.
+    const fn padding(&self, offset: usize) -> usize {
+        if !self.align || self.size <= 1 {
+            return 0;
+        }
+        (self.size - offset % self.size) % self.size
+    }
+
+    /// Where the item placed at `offset` ends (after any padding),
+    /// or `None` if it overflows.
+    const fn end_offset(&self, offset: usize) -> Option<usize> {
+        let Some(padded) = offset.checked_add(self.padding(offset)) else {
+            return None;
+        };
+        let Some(total) = self.size.checked_mul(self.times()) else {
+            return None;
+        };
+        padded.checked_add(total)
+    }
+}
+
+/// Parses the item at `pos`, returning it and where the next one starts,
+/// or `None` at the end.
+const fn next_item(
+    format: &[u8],
+    mut pos: usize,
+    mode: Mode,
+) -> Result<Option<(Item, usize)>, &'static str> {
+    while pos < format.len() && format[pos].is_ascii_whitespace() {
+        pos += 1;
+    }
+    if pos == format.len() {
+        return Ok(None);
+    }
+
+    let mut count = None;
+    while pos < format.len() && format[pos].is_ascii_digit() {
+        let digit = (format[pos] - b'0') as usize;
+        count = match count {
+            None => Some(digit),
+            Some(count) => match count.checked_mul(10) {
+                Some(n) => n.checked_add(digit),
+                None => None,
+            },
+        };
+        if count.is_none() {
+            return Err("repeat count in format is too large");
+        }
+        pos += 1;
+    }
+    if pos == format.len() {
+        return Err("repeat count in format without a code");
+    }
+
+    let code = format[pos];
+    let native = mode.native;
+    let size = match code {
+        b'x' | b'c' | b'b' | b'B' | b'?' => 1,
+        b'h' | b'H' => 2,
+        b'i' | b'I' | b'f' => 4,
+        b'l' | b'L' if native => size_of::<std::ffi::c_long>(),
+        b'l' | b'L' => 4,
+        b'q' | b'Q' | b'd' => 8,
+        b'n' | b'N' | b'P' if native => size_of::<usize>(),
+        b'n' | b'N' | b'P' => return Err("format codes n, N and P are only valid in native mode"),
+        b's' | b'p' => match count {
+            Some(count) => count,
+            None => 1,
+        },
+        b'e' => return Err("half precision floats (format code e) aren't supported"),
+        b'@' | b'=' | b'<' | b'>' | b'!' => {
+            return Err("byte order in format must be the first character");
+        }
+        _ => return Err("invalid code in format"),
+    };
+    if code == b'p' && size == 0 {
+        return Err("format code p needs a size of at least 1");
+    }
+    let count = match count {
+        Some(count) => count,
+        None => 1,
+    };
+    let item = Item {
+        code,
+        count,
+        size,
+        align: native && !matches!(code, b's' | b'p'),
+    };
+    Ok(Some((item, pos + 1)))
+}
+
+/// Parses `format` into its byte order, items and total size.
+fn parse(format: &str) -> Result<(Mode, Vec<Item>, usize), Error> {
+    let invalid = |e| Error::new(ErrorKind::InvalidInput, e);
+    let format = format.as_bytes();
+    let (mode, mut pos) = Mode::parse(format);
+    let mut items = Vec::new();
+    let mut size: usize = 0;
+    while let Some((item, next)) = next_item(format, pos, mode).map_err(invalid)? {
+        size = item.end_offset(size).ok_or_else(|| invalid(TOO_LARGE))?;
+        items.push(item);
+        pos = next;
+    }
+    Ok((mode, items, size))
+}
+
+/// **Struct format strings** (see [`crate::pack`])
+impl<T: Read, O: ByteOrder> Muncher<T, O> {
+    /// Reads values as described by a Python `struct` style format string,
+    /// like `"<IHh10sx?"`.
+    ///
+    /// See the [`crate::pack`] module for the format.
+    /// Pad bytes (`x`) are skipped without returning a value.
+    ///
+    /// An invalid format fails with [`ErrorKind::InvalidInput`]
+    /// (before anything is read).
+    pub fn unpack(&mut self, format: &str) -> Result<Vec<Value>, Error> {
+        let (mode, items, _) = parse(format)?;
+        let end = mode.end;
+        let mut values = Vec::new();
+        let mut offset = 0;
+        for item in items {
+            let padding = item.padding(offset);
+            if padding > 0 {
+                self.read_fixed_bytes(padding)?;
+            }
+            // Can't overflow, as the total size was checked when parsing
+            offset += padding + item.size * item.times();
+            match item.code {
+                b'x' => {
+                    self.read_fixed_bytes(item.count)?;
+                }
+                b's' => values.push(Value::Bytes(self.read_fixed_bytes(item.size)?)),
+                b'p' => {
+                    let mut bytes = self.read_fixed_bytes(item.size)?;
+                    let len = usize::from(bytes[0]).min(item.size - 1);
+                    bytes.truncate(len + 1);
+                    bytes.remove(0);
+                    values.push(Value::Bytes(bytes));
+                }
+                _ => {
+                    for _ in 0..item.count {
+                        values.push(self.unpack_one(item, end)?);
+                    }
+                }
+            }
+        }
+        Ok(values)
+    }
+
+    fn unpack_one(&mut self, item: Item, end: End) -> Result<Value, Error> {
+        Ok(match (item.code, item.size) {
+            (b'c', _) => Value::Bytes(vec![self.read_m::<u8>(end)?]),
+            (b'?', _) => Value::Bool(self.read_m::<u8>(end)? != 0),
+            (b'b', _) => Value::Int(self.read_m::<i8>(end)?.into()),
+            (b'B', _) => Value::UInt(self.read_m::<u8>(end)?.into()),
+            (b'h', _) => Value::Int(self.read_m::<i16>(end)?.into()),
+            (b'H', _) => Value::UInt(self.read_m::<u16>(end)?.into()),
+            (b'f', _) => Value::Float(self.read_m::<f32>(end)?.into()),
+            (b'd', _) => Value::Float(self.read_m::<f64>(end)?),
+            (b'i' | b'l' | b'n', 4) => Value::Int(self.read_m::<i32>(end)?.into()),
+            (b'I' | b'L' | b'N' | b'P', 4) => Value::UInt(self.read_m::<u32>(end)?.into()),
+            (b'q' | b'l' | b'n', _) => Value::Int(self.read_m::<i64>(end)?),
+            (b'Q' | b'L' | b'N' | b'P', _) => Value::UInt(self.read_m::<u64>(end)?),
+            _ => unreachable!("format codes are checked when parsing"),
+        })
+    }
+}
+
+/// **Struct format strings** (see [`crate::pack`])
+impl<T: Write, O: ByteOrder> Muncher<T, O> {
+    /// Writes `values` as described by a Python `struct` style format string,
+    /// like `"<IHh10sx?"`.
+    ///
+    /// See the [`crate::pack`] module for the format.
+    /// Pad bytes (`x`) are written as zeros, without taking a value.
+    /// Like in Python, `s` and `p` values are cut or padded with zeros
+    /// to fit their size.
+    ///
+    /// Integers can be given as either [`Value::Int`] or [`Value::UInt`],
+    /// and floats also as integers.
+    ///
+    /// An invalid format, a format larger than the allocation limit
+    /// (see [`Muncher::set_max_alloc`]), the wrong number of values, or a value
+    /// of the wrong type (or out of range) fails with [`ErrorKind::InvalidInput`]
+    /// before anything is written.
+    pub fn pack(&mut self, format: &str, values: &[Value]) -> Result<(), Error> {
+        let (mode, items, size) = parse(format)?;
+        let expected = items
+            .iter()
+            .filter(|item| item.code != b'x')
+            .try_fold(0usize, |n, item| n.checked_add(item.times()))
+            .ok_or_else(|| Error::new(ErrorKind::InvalidInput, TOO_LARGE))?;
+        if values.len() != expected {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("format needs {expected} values, got {}", values.len()),
+            ));
+        }
+        self.verify_max_alloc(size)
+            .map_err(|e| Error::new(ErrorKind::InvalidInput, e.to_string()))?;
+        // Encoded up front, so nothing is written if a value is invalid.
+        let mut out = Muncher::new(Vec::with_capacity(size));
+        let mut values = values.iter();
+        for item in items {
+            let padding = item.padding(out.inner.len());
+            out.inner.resize(out.inner.len() + padding, 0);
+            if item.code == b'x' {
+                out.inner.resize(out.inner.len() + item.count, 0);
+                continue;
+            }
+            for value in values.by_ref().take(item.times()) {
+                pack_one(&mut out, item, value, mode.end)?;
+            }
+        }
+        self.write_all(&out.inner)
+    }
+}
+
+fn pack_one(out: &mut Muncher<Vec<u8>>, item: Item, value: &Value, end: End) -> Result<(), Error> {
+    let code = item.code;
+    let what = &format!("format code {}", code as char);
+    match (code, item.size) {
+        (b's' | b'p', _) => {
+            let Value::Bytes(bytes) = value else {
+                return Err(type_error(what, "bytes", value));
+            };
+            let mut field = vec![0; item.size];
+            if code == b's' {
+                let len = bytes.len().min(item.size);
+                field[..len].copy_from_slice(&bytes[..len]);
+            } else {
+                let len = bytes.len().min(item.size - 1).min(255);
+                field[0] = len as u8;
+                field[1..=len].copy_from_slice(&bytes[..len]);
+            }
+            out.write_all(&field)
+        }
+        (b'c', _) => match value {
+            Value::Bytes(bytes) if bytes.len() == 1 => out.write_m(bytes[0], end),
+            _ => Err(type_error(what, "bytes of length 1", value)),
+        },
+        (b'?', _) => match value {
+            Value::Bool(b) => out.write_m(u8::from(*b), end),
+            _ => Err(type_error(what, "a bool", value)),
+        },
+        (b'f', _) => out.write_m(float(what, value)? as f32, end),
+        (b'd', _) => out.write_m(float(what, value)?, end),
+        (b'b', _) => out.write_m(int::<i8>(what, value)?, end),
+        (b'B', _) => out.write_m(int::<u8>(what, value)?, end),
+        (b'h', _) => out.write_m(int::<i16>(what, value)?, end),
+        (b'H', _) => out.write_m(int::<u16>(what, value)?, end),
+        (b'i' | b'l' | b'n', 4) => out.write_m(int::<i32>(what, value)?, end),
+        (b'I' | b'L' | b'N' | b'P', 4) => out.write_m(int::<u32>(what, value)?, end),
+        (b'q' | b'l' | b'n', _) => out.write_m(int::<i64>(what, value)?, end),
+        (b'Q' | b'L' | b'N' | b'P', _) => out.write_m(int::<u64>(what, value)?, end),
+        _ => unreachable!("format codes are checked when parsing"),
+    }
+}
+
+/// Converts `value` to the integer type `I` (described by `what`), if it fits.
+pub(crate) fn int<I: TryFrom<i128>>(what: &str, value: &Value) -> Result<I, Error> {
+    let n = match value {
+        Value::Int(n) => i128::from(*n),
+        Value::UInt(n) => i128::from(*n),
+        _ => return Err(type_error(what, "an integer", value)),
+    };
+    I::try_from(n).map_err(|_| {
+        Error::new(
+            ErrorKind::InvalidInput,
+            format!("value {n} is out of range for {what}"),
+        )
+    })
+}
+
+/// Converts `value` to a float (for the type described by `what`).
+pub(crate) fn float(what: &str, value: &Value) -> Result<f64, Error> {
+    match value {
+        Value::Float(f) => Ok(*f),
+        Value::Int(n) => Ok(*n as f64),
+        Value::UInt(n) => Ok(*n as f64),
+        _ => Err(type_error(what, "a number", value)),
+    }
+}
+
+fn type_error(what: &str, expected: &str, value: &Value) -> Error {
+    Error::new(
+        ErrorKind::InvalidInput,
+        format!("{what} needs {expected}, got {value:?}"),
+    )
+}
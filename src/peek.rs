@@ -0,0 +1,169 @@
+use crate::{ByteOrder, End, Muncher, Primitive};
+use std::io::{BufRead, Error};
+use std::ops::Deref;
+
+/// Bytes pulled out of the inner reader of a [`Muncher`]
+/// that haven't been consumed yet.
+///
+/// Consuming only moves a cursor, and the consumed bytes are dropped
+/// once they're the majority (like [`crate::PushMuncher::feed`]),
+/// so consuming a big buffer in small steps doesn't shift it every time.
+#[derive(Debug, Default)]
+pub(crate) struct Lookahead {
+    buf: Vec<u8>,
+    pos: usize,
+}
+
+impl Lookahead {
+    /// Appends `bytes` to the end.
+    pub fn extend_from_slice(&mut self, bytes: &[u8]) {
+        if self.pos > self.buf.len() / 2 {
+            self.buf.drain(..self.pos);
+            self.pos = 0;
+        }
+        self.buf.extend_from_slice(bytes);
+    }
+
+    /// Removes the first `len` bytes.
+    pub fn consume(&mut self, len: usize) {
+        assert!(
+            len <= self.len(),
+            "consumed more than the look-ahead buffer has"
+        );
+        self.pos += len;
+        if self.pos == self.buf.len() {
+            self.buf.clear();
+            self.pos = 0;
+        }
+    }
+
+    /// Puts `bytes` back in front.
+    fn prepend(&mut self, mut bytes: Vec<u8>) {
+        bytes.extend_from_slice(self);
+        self.buf = bytes;
+        self.pos = 0;
+    }
+}
+
+impl Deref for Lookahead {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.buf[self.pos..]
+    }
+}
+
+/// **Look-ahead and transactional methods that require `T: `[`std::io::BufRead`]**
+impl<T: BufRead, O: ByteOrder> Muncher<T, O> {
+    /// Reads any [`crate::Primitive`] type (such as integers or floats),
+    /// with the endianness specified in the `end` argument,
+    /// **without consuming it**. The next read will return the same bytes.
+    ///
+    /// For more info on endianness see [`crate::End`].
+    pub fn peek<E: Primitive>(&mut self, end: End) -> Result<E, Error> {
+        self.rewinding(|m| m.read_m::<E>(end))
+    }
+
+    /// Returns up to `len` upcoming bytes **without consuming them**.
+    ///
+    /// If the inner reader's buffer already has enough bytes, they
+    /// are returned directly. Otherwise, the bytes are moved into an
+    /// internal buffer (growing it as needed) until there are `len` of them.
+    ///
+    /// The returned slice is only shorter than `len` if the
+    /// end of input was reached.
+    ///
+    /// ```
+    /// # use bytemuncher::Muncher;
+    /// # use std::io::{BufReader, Cursor};
+    /// let data = b"\x89PNG\r\n\x1a\n";
+    /// // A tiny buffer, smaller than what we peek
+    /// let mut muncher = Muncher::new(BufReader::with_capacity(2, Cursor::new(data)));
+    ///
+    /// assert_eq!(muncher.peek_bytes(4).unwrap(), b"\x89PNG");
+    /// assert_eq!(muncher.read_fixed_bytes(8).unwrap(), data);
+    /// ```
+    pub fn peek_bytes(&mut self, len: usize) -> Result<&[u8], Error> {
+        self.verify_max_alloc(len)?;
+        if self.lookahead.is_empty() && self.inner.fill_buf()?.len() >= len {
+            return Ok(&self.inner.fill_buf()?[..len]);
+        }
+        while self.lookahead.len() < len {
+            let buf = self.inner.fill_buf()?;
+            if buf.is_empty() {
+                break;
+            }
+            let take = buf.len().min(len - self.lookahead.len());
+            self.lookahead.extend_from_slice(&buf[..take]);
+            self.inner.consume(take);
+        }
+        let len = len.min(self.lookahead.len());
+        Ok(&self.lookahead[..len])
+    }
+
+    /// Runs the `parse` closure, and if it fails, rolls back
+    /// everything it consumed, as if it never ran.
+    ///
+    /// This is useful for format sniffing and trying
+    /// alternative branches of a format.
+    ///
+    /// Calls to `try_parse` can be nested.
+    ///
+    /// Note: bytes consumed inside the closure are kept in memory
+    /// until it returns, so avoid wrapping huge reads in this.
+    ///
+    /// ```
+    /// # use bytemuncher::{End, Muncher};
+    /// # use std::io::{BufReader, Cursor};
+    /// let mut muncher = Muncher::new(BufReader::new(Cursor::new(b"\x05hello")));
+    ///
+    /// // Not a big-endian u32 prefixed string...
+    /// assert!(muncher.try_parse(|m| m.read_pref_utf8::<u32>(End::Big)).is_err());
+    /// // ...so try something else from the same position
+    /// assert_eq!(muncher.read_pref_utf8::<u8>(End::Big).unwrap(), "hello");
+    /// ```
+    pub fn try_parse<R, Err>(
+        &mut self,
+        parse: impl FnOnce(&mut Self) -> Result<R, Err>,
+    ) -> Result<R, Err> {
+        let outer = self.journal.replace(Vec::new());
+        let checksum = self.observers.checksum.as_ref().map(|c| c.clone_box());
+        let trace = self.trace_mark();
+        let result = parse(self);
+        let consumed = std::mem::replace(&mut self.journal, outer).unwrap_or_default();
+        if result.is_err() {
+            self.unconsume(consumed);
+            self.observers.checksum = checksum;
+            self.trace_rollback(trace);
+        } else if let Some(journal) = &mut self.journal {
+            journal.extend(consumed);
+        }
+        result
+    }
+
+    /// Like [`Muncher::try_parse`] but always rolls back,
+    /// even on success.
+    fn rewinding<R, Err>(
+        &mut self,
+        parse: impl FnOnce(&mut Self) -> Result<R, Err>,
+    ) -> Result<R, Err> {
+        let outer = self.journal.replace(Vec::new());
+        let checksum = self.observers.checksum.as_ref().map(|c| c.clone_box());
+        let trace = self.trace_mark();
+        let result = parse(self);
+        let consumed = std::mem::replace(&mut self.journal, outer).unwrap_or_default();
+        self.unconsume(consumed);
+        self.observers.checksum = checksum;
+        self.trace_rollback(trace);
+        result
+    }
+
+    /// Puts `bytes` back in front of the input.
+    fn unconsume(&mut self, bytes: Vec<u8>) {
+        if bytes.is_empty() {
+            return;
+        }
+        self.record_unconsumed(bytes.len());
+        self.lookahead.prepend(bytes);
+    }
+}
@@ -0,0 +1,85 @@
+use crate::{ByteOrder, Muncher};
+use std::io::{BufRead, Error, ErrorKind};
+
+/// Iterator over consecutive records of a [`Muncher`],
+/// returned by [`Muncher::records`].
+pub struct Records<'a, T, O: ByteOrder, F> {
+    muncher: &'a mut Muncher<T, O>,
+    parse: F,
+    done: bool,
+}
+
+impl<T: BufRead, O: ByteOrder, F: FnMut(&mut Muncher<T, O>) -> Result<R, Error>, R> Iterator
+    for Records<'_, T, O, F>
+{
+    type Item = Result<R, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let start = self.muncher.position();
+        let result = match self.muncher.is_eof() {
+            Ok(true) => None,
+            Ok(false) => Some((self.parse)(self.muncher).map_err(|e| truncated(e, start))),
+            Err(e) => Some(Err(e)),
+        };
+        self.done = !matches!(result, Some(Ok(_)));
+        result
+    }
+}
+
+/// **Record iteration** (blocking)
+impl<T: BufRead, O: ByteOrder> Muncher<T, O> {
+    /// Returns an iterator that calls `parse` for each record
+    /// until the input ends, for log-structured files,
+    /// socket feeds and the like.
+    ///
+    /// The iterator ends cleanly (returns `None`) only if the input ends
+    /// right at a record boundary. If it ends in the middle of a record,
+    /// the last item is an [`ErrorKind::UnexpectedEof`] error.
+    /// After any error, the iterator returns `None`.
+    ///
+    /// The inner reader must be buffered (such as a [`std::io::BufReader`]),
+    /// to check for the end without consuming anything (see [`Muncher::is_eof`]).
+    ///
+    /// ```
+    /// # use bytemuncher::{End, Muncher};
+    /// # use std::io::{Cursor, ErrorKind};
+    /// let mut muncher = Muncher::new(Cursor::new(b"\x02hi\x03abc"));
+    /// let records: Vec<_> = muncher
+    ///     .records(|m| m.read_pref_utf8::<u8>(End::Big))
+    ///     .collect::<Result<_, _>>()
+    ///     .unwrap();
+    /// assert_eq!(records, ["hi", "abc"]);
+    ///
+    /// // Truncated input
+    /// let mut muncher = Muncher::new(Cursor::new(b"\x02hi\x03ab"));
+    /// let mut records = muncher.records(|m| m.read_pref_utf8::<u8>(End::Big));
+    /// assert_eq!(records.next().unwrap().unwrap(), "hi");
+    /// assert_eq!(records.next().unwrap().unwrap_err().kind(), ErrorKind::UnexpectedEof);
+    /// assert!(records.next().is_none());
+    /// ```
+    pub fn records<R, F: FnMut(&mut Self) -> Result<R, Error>>(
+        &mut self,
+        parse: F,
+    ) -> Records<'_, T, O, F> {
+        Records {
+            muncher: self,
+            parse,
+            done: false,
+        }
+    }
+}
+
+/// Marks end-of-input inside a record as truncation.
+pub(crate) fn truncated(e: Error, start: u64) -> Error {
+    if e.kind() == ErrorKind::UnexpectedEof {
+        Error::new(
+            ErrorKind::UnexpectedEof,
+            format!("truncated record starting at byte {start}: {e}"),
+        )
+    } else {
+        e
+    }
+}
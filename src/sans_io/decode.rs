@@ -0,0 +1,301 @@
+use std::io::{Error, ErrorKind};
+
+use crate::{ByteOrder, End, Primitive};
+
+/// What a [`Decode`] wants next.
+pub(crate) enum Want<'a> {
+    /// Check that a length that came from the input is within
+    /// the allocation limits, before the decoder allocates for it.
+    /// This is only asked once per length.
+    Check {
+        /// The number of input bytes the decoder will ask for next.
+        input: usize,
+        /// The most bytes the decoder will allocate for them.
+        alloc: usize,
+    },
+    /// Fill this buffer with the next bytes of the input
+    /// (then report how many through [`Decode::filled`]).
+    Bytes(&'a mut [u8]),
+    /// The value is complete, call [`Decode::finish`].
+    Done,
+}
+
+/// A state machine that decodes a value from bytes it's given,
+/// without doing any I/O itself.
+pub(crate) trait Decode {
+    type Output;
+
+    /// Returns what the decoder wants next.
+    ///
+    /// New buffers grow by at most `chunk` bytes at a time,
+    /// so that a forged huge length can't allocate more
+    /// memory than the input actually has.
+    fn want(&mut self, chunk: usize) -> Result<Want<'_>, Error>;
+
+    /// Marks the first `n` bytes of the last [`Want::Bytes`] buffer as filled.
+    fn filled(&mut self, n: usize) -> Result<(), Error>;
+
+    /// Returns the value, after [`Decode::want`] returned [`Want::Done`].
+    fn finish(self) -> Result<Self::Output, Error>;
+
+    /// Converts the value with `f` once it's decoded.
+    fn map<F, R>(self, f: F) -> Map<Self, F>
+    where
+        Self: Sized,
+        F: FnOnce(Self::Output) -> Result<R, Error>,
+    {
+        Map { inner: self, f }
+    }
+}
+
+/// A single [`Primitive`] value, in the byte order `O`.
+pub(crate) struct Value<E, O = End> {
+    order: O,
+    buf: Vec<u8>,
+    filled: usize,
+    value: Option<E>,
+}
+
+impl<E: Primitive, O: ByteOrder> Value<E, O> {
+    pub(crate) fn new(order: O) -> Self {
+        Self {
+            order,
+            buf: Vec::new(),
+            filled: 0,
+            value: None,
+        }
+    }
+}
+
+impl<E: Primitive, O: ByteOrder> Decode for Value<E, O> {
+    type Output = E;
+
+    fn want(&mut self, _: usize) -> Result<Want<'_>, Error> {
+        if self.value.is_some() {
+            return Ok(Want::Done);
+        }
+        // Types without a fixed size are tried one more byte at a time.
+        let size = E::SIZE.unwrap_or(self.filled + 1);
+        self.buf.resize(size, 0);
+        Ok(Want::Bytes(&mut self.buf[self.filled..]))
+    }
+
+    fn filled(&mut self, n: usize) -> Result<(), Error> {
+        self.filled += n;
+        if self.filled < self.buf.len() {
+            return Ok(());
+        }
+        match E::read_order(&mut &self.buf[..], self.order) {
+            Ok(value) => self.value = Some(value),
+            Err(e) if E::SIZE.is_none() && e.kind() == ErrorKind::UnexpectedEof => {}
+            Err(e) => return Err(e),
+        }
+        Ok(())
+    }
+
+    fn finish(self) -> Result<E, Error> {
+        Ok(self.value.expect("`finish` called before `Want::Done`"))
+    }
+}
+
+/// A fixed number of raw bytes.
+pub(crate) struct Bytes {
+    len: usize,
+    checked: bool,
+    buf: Vec<u8>,
+    filled: usize,
+}
+
+impl Bytes {
+    pub(crate) fn new(len: usize) -> Self {
+        Self {
+            len,
+            checked: false,
+            buf: Vec::new(),
+            filled: 0,
+        }
+    }
+}
+
+impl Decode for Bytes {
+    type Output = Vec<u8>;
+
+    fn want(&mut self, chunk: usize) -> Result<Want<'_>, Error> {
+        if !self.checked {
+            self.checked = true;
+            return Ok(Want::Check {
+                input: self.len,
+                alloc: self.len,
+            });
+        }
+        if self.filled == self.len {
+            return Ok(Want::Done);
+        }
+        if self.buf.len() == self.filled {
+            let grow = chunk.min(self.len - self.filled);
+            self.buf.resize(self.filled + grow, 0);
+        }
+        Ok(Want::Bytes(&mut self.buf[self.filled..]))
+    }
+
+    fn filled(&mut self, n: usize) -> Result<(), Error> {
+        self.filled += n;
+        Ok(())
+    }
+
+    fn finish(self) -> Result<Vec<u8>, Error> {
+        Ok(self.buf)
+    }
+}
+
+/// A value prefixed by its length (as a [`Primitive`] `<E>` in the byte order `O`),
+/// decoded by the `body` decoder made from that length.
+pub(crate) struct Prefixed<E, O, D, F = fn(usize) -> D> {
+    state: PrefixedState<E, O, D>,
+    body: Option<F>,
+}
+
+enum PrefixedState<E, O, D> {
+    Len(Value<E, O>),
+    Body(D),
+}
+
+impl<E: Primitive, O: ByteOrder, D: Decode, F: FnOnce(usize) -> D> Prefixed<E, O, D, F> {
+    pub(crate) fn new(order: O, body: F) -> Self {
+        Self {
+            state: PrefixedState::Len(Value::new(order)),
+            body: Some(body),
+        }
+    }
+}
+
+impl<E: Primitive, O: ByteOrder, D: Decode, F: FnOnce(usize) -> D> Decode for Prefixed<E, O, D, F> {
+    type Output = D::Output;
+
+    fn want(&mut self, chunk: usize) -> Result<Want<'_>, Error> {
+        if let PrefixedState::Len(len) = &mut self.state
+            && let Some(len) = len.value.take()
+        {
+            let body = self.body.take().expect("the body is only made once");
+            self.state = PrefixedState::Body(body(len.into_usize()));
+        }
+        match &mut self.state {
+            PrefixedState::Len(len) => len.want(chunk),
+            PrefixedState::Body(body) => body.want(chunk),
+        }
+    }
+
+    fn filled(&mut self, n: usize) -> Result<(), Error> {
+        match &mut self.state {
+            PrefixedState::Len(len) => len.filled(n),
+            PrefixedState::Body(body) => body.filled(n),
+        }
+    }
+
+    fn finish(self) -> Result<D::Output, Error> {
+        let PrefixedState::Body(body) = self.state else {
+            unreachable!("`finish` called before `Want::Done`")
+        };
+        body.finish()
+    }
+}
+
+/// See [`Decode::map`].
+pub(crate) struct Map<D, F> {
+    inner: D,
+    f: F,
+}
+
+impl<D: Decode, F: FnOnce(D::Output) -> Result<R, Error>, R> Decode for Map<D, F> {
+    type Output = R;
+
+    fn want(&mut self, chunk: usize) -> Result<Want<'_>, Error> {
+        self.inner.want(chunk)
+    }
+
+    fn filled(&mut self, n: usize) -> Result<(), Error> {
+        self.inner.filled(n)
+    }
+
+    fn finish(self) -> Result<R, Error> {
+        (self.f)(self.inner.finish()?)
+    }
+}
+
+/// Max number of 16-bit words buffered at a time by [`Ucs2`].
+#[cfg(feature = "ucs2")]
+const U16_CHUNK: usize = 8 * 1024;
+
+/// A fixed number of UCS-2 characters, converted to UTF-8.
+///
+/// The raw words are decoded in chunks of at most [`U16_CHUNK`],
+/// so the memory used for them stays bounded regardless of the length.
+#[cfg(feature = "ucs2")]
+pub(crate) struct Ucs2 {
+    remaining: usize,
+    checked: bool,
+    raw: Vec<u8>,
+    filled: usize,
+    words: Vec<u16>,
+    out: Vec<u8>,
+}
+
+#[cfg(feature = "ucs2")]
+impl Ucs2 {
+    pub(crate) fn new(char_count: usize) -> Self {
+        Self {
+            remaining: char_count,
+            checked: false,
+            raw: Vec::new(),
+            filled: 0,
+            words: Vec::new(),
+            out: Vec::new(),
+        }
+    }
+}
+
+#[cfg(feature = "ucs2")]
+impl Decode for Ucs2 {
+    type Output = String;
+
+    fn want(&mut self, chunk: usize) -> Result<Want<'_>, Error> {
+        if !self.checked {
+            self.checked = true;
+            self.out.reserve(chunk.min(self.remaining));
+            return Ok(Want::Check {
+                input: self.remaining.saturating_mul(2),
+                // Each character takes up to 3 bytes as UTF-8
+                alloc: self.remaining.saturating_mul(3),
+            });
+        }
+        if self.remaining == 0 {
+            return Ok(Want::Done);
+        }
+        if self.raw.is_empty() {
+            self.raw.resize(self.remaining.min(U16_CHUNK) * 2, 0);
+            self.filled = 0;
+        }
+        Ok(Want::Bytes(&mut self.raw[self.filled..]))
+    }
+
+    fn filled(&mut self, n: usize) -> Result<(), Error> {
+        self.filled += n;
+        if self.filled < self.raw.len() {
+            return Ok(());
+        }
+        self.words.clear();
+        self.words.extend(
+            self.raw
+                .chunks_exact(2)
+                .map(|pair| u16::from_be_bytes([pair[0], pair[1]])),
+        );
+        crate::string::decode_ucs2(&self.words, &mut self.out)?;
+        self.remaining -= self.words.len();
+        self.raw.clear();
+        Ok(())
+    }
+
+    fn finish(self) -> Result<String, Error> {
+        crate::string::bytes2utf8(self.out)
+    }
+}
@@ -0,0 +1,68 @@
+use std::borrow::Cow;
+use std::io::Error;
+
+use crate::{ByteOrder, Primitive};
+
+/// The bytes of a value to write, in order.
+///
+/// Kept in pieces so that large bodies are written
+/// straight from the caller's buffer, without copying.
+pub(crate) struct Encoded<'a> {
+    head: Vec<u8>,
+    body: Cow<'a, [u8]>,
+    tail: &'static [u8],
+}
+
+impl<'a> Encoded<'a> {
+    /// A single [`Primitive`] value, in the byte order `order`.
+    pub(crate) fn value<E: Primitive>(value: E, order: impl ByteOrder) -> Result<Self, Error> {
+        let mut head = Vec::new();
+        value.write_order(&mut head, order)?;
+        Ok(Self {
+            head,
+            body: Cow::Borrowed(&[]),
+            tail: &[],
+        })
+    }
+
+    /// `body` prefixed by `len` (as a [`Primitive`] `<E>`).
+    pub(crate) fn prefixed<E: Primitive>(
+        len: usize,
+        body: impl Into<Cow<'a, [u8]>>,
+        order: impl ByteOrder,
+    ) -> Result<Self, Error> {
+        Ok(Self {
+            body: body.into(),
+            ..Self::value(crate::prefix::<E>(len)?, order)?
+        })
+    }
+
+    /// `body` with a null terminator, unless it already ends with one.
+    pub(crate) fn cstr(body: impl Into<Cow<'a, [u8]>>) -> Self {
+        let body = body.into();
+        let tail: &[u8] = if body.ends_with(&[0]) { &[] } else { &[0] };
+        Self {
+            head: Vec::new(),
+            body,
+            tail,
+        }
+    }
+
+    /// The pieces to write, in order.
+    pub(crate) fn parts(&self) -> [&[u8]; 3] {
+        [&self.head, &self.body, self.tail]
+    }
+}
+
+/// Encodes `msg` as UCS-2 (big endian 16-bit words),
+/// returning the number of characters and the bytes.
+#[cfg(feature = "ucs2")]
+pub(crate) fn encode_ucs2(msg: &str) -> Result<(usize, Vec<u8>), Error> {
+    let mut out = Vec::new();
+    ucs2::encode_with(msg, |n| {
+        out.extend_from_slice(&n.to_be_bytes());
+        Ok(())
+    })
+    .map_err(crate::string::usc2err)?;
+    Ok((out.len() / 2, out))
+}
@@ -0,0 +1,139 @@
+//! The sans-IO core of [`crate::Muncher`].
+//!
+//! Every format is implemented once here, on in-memory bytes:
+//! - Reading is done by [`Decode`] state machines, which say what
+//!   bytes they want next (and how much memory that will take).
+//! - Writing is done by building the [`Encoded`] bytes of a value.
+//!
+//! The blocking and async methods are thin adapters around these
+//! ([`drive`] and [`drive_a`]), that only move bytes around,
+//! and so is [`PushMuncher`] (which is fed the bytes instead).
+//! So a format (or a fix to one) automatically works in every mode.
+//!
+//! Delimiter-terminated reads (`cstr`, `delim`) are the exception,
+//! they use the buffered reader's own search (`read_until`),
+//! or a search of the fed bytes.
+
+use std::io::{Error, Read, Write};
+
+use crate::{ByteOrder, Muncher};
+
+mod decode;
+mod encode;
+mod push;
+
+#[cfg(feature = "ucs2")]
+pub(crate) use decode::Ucs2;
+pub(crate) use decode::{Bytes, Decode, Prefixed, Value, Want};
+pub(crate) use encode::Encoded;
+#[cfg(feature = "ucs2")]
+pub(crate) use encode::encode_ucs2;
+pub use push::{Parsed, PushMuncher};
+
+/// Runs `decoder` to completion on a blocking reader.
+///
+/// Buffers grow by at most `chunk` bytes at a time,
+/// and `check` is called with the allocation needed for each length
+/// that came from the input, before allocating for it.
+pub(crate) fn drive<R: Read + ?Sized, D: Decode>(
+    reader: &mut R,
+    mut decoder: D,
+    chunk: usize,
+    mut check: impl FnMut(&mut R, usize) -> Result<(), Error>,
+) -> Result<D::Output, Error> {
+    loop {
+        match decoder.want(chunk)? {
+            Want::Check { alloc, .. } => check(reader, alloc)?,
+            Want::Bytes(buf) => {
+                let n = buf.len();
+                reader.read_exact(buf)?;
+                decoder.filled(n)?;
+            }
+            Want::Done => return decoder.finish(),
+        }
+    }
+}
+
+/// Runs `decoder` on the in-memory `input`,
+/// returning the value and how many bytes of `input` it used.
+///
+/// The allocations needed for lengths that came from the input are given to `check`,
+/// then the lengths are checked against what's left of `input` (before allocating anything).
+pub(crate) fn decode_slice<D: Decode>(
+    mut input: &[u8],
+    mut decoder: D,
+    mut check: impl FnMut(usize) -> Result<(), Error>,
+) -> Result<Parsed<(D::Output, usize)>, Error> {
+    let total = input.len();
+    loop {
+        // Everything is in memory already (and lengths are checked
+        // against it first), so there's no point in growing buffers in chunks.
+        match decoder.want(usize::MAX)? {
+            Want::Check { input: len, alloc } => {
+                check(alloc)?;
+                if len > input.len() {
+                    return Ok(Parsed::Incomplete {
+                        needed: len - input.len(),
+                    });
+                }
+            }
+            Want::Bytes(buf) => {
+                let Some((head, rest)) = input.split_at_checked(buf.len()) else {
+                    return Ok(Parsed::Incomplete {
+                        needed: buf.len() - input.len(),
+                    });
+                };
+                buf.copy_from_slice(head);
+                decoder.filled(head.len())?;
+                input = rest;
+            }
+            Want::Done => return Ok(Parsed::Done((decoder.finish()?, total - input.len()))),
+        }
+    }
+}
+
+/// Async version of [`drive`].
+#[cfg(any(feature = "tokio", feature = "futures"))]
+pub(crate) async fn drive_a<B, R, D>(
+    reader: &mut R,
+    mut decoder: D,
+    chunk: usize,
+    mut check: impl FnMut(&mut R, usize) -> Result<(), Error>,
+) -> Result<D::Output, Error>
+where
+    B: crate::backend::Backend,
+    R: crate::backend::AsyncSource<B> + ?Sized,
+    D: Decode,
+{
+    loop {
+        match decoder.want(chunk)? {
+            Want::Check { alloc, .. } => check(reader, alloc)?,
+            Want::Bytes(buf) => {
+                let n = buf.len();
+                reader.read_exact(buf).await?;
+                decoder.filled(n)?;
+            }
+            Want::Done => return decoder.finish(),
+        }
+    }
+}
+
+/// **Sans-IO adapters** (blocking)
+impl<T: Read, O: ByteOrder> Muncher<T, O> {
+    /// Runs `decoder` on the input, within the allocation limits
+    /// (see [`Muncher::set_max_alloc`] and [`Muncher::set_alloc_budget`]).
+    pub(crate) fn decode<D: Decode>(&mut self, decoder: D) -> Result<D::Output, Error> {
+        let chunk = self.alloc_chunk(1);
+        drive(self, decoder, chunk, Self::verify_len)
+    }
+}
+
+impl<T: Write, O: ByteOrder> Muncher<T, O> {
+    /// Writes all the bytes of `encoded`.
+    pub(crate) fn write_encoded(&mut self, encoded: &Encoded) -> Result<(), Error> {
+        for part in encoded.parts() {
+            self.write_all(part)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,337 @@
+use std::io::{Error, ErrorKind};
+
+use super::{Bytes, Decode, Prefixed, Value};
+use crate::order::Resolved;
+use crate::{ByteOrder, End, Primitive};
+
+/// The result of a read from a [`PushMuncher`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[must_use]
+pub enum Parsed<T> {
+    /// The value was read, and its bytes were consumed.
+    Done(T),
+    /// Not enough bytes have been fed yet. Nothing was consumed.
+    ///
+    /// Feed at least `needed` more bytes and try again
+    /// (it's exact for fixed size and length-prefixed values,
+    /// and a lower bound for null terminated ones).
+    Incomplete { needed: usize },
+}
+
+impl<T> Parsed<T> {
+    /// Returns the value if it was read, `None` if incomplete.
+    pub fn done(self) -> Option<T> {
+        match self {
+            Parsed::Done(value) => Some(value),
+            Parsed::Incomplete { .. } => None,
+        }
+    }
+
+    /// Converts the value with `f` if it was read.
+    pub fn map<R>(self, f: impl FnOnce(T) -> R) -> Parsed<R> {
+        match self {
+            Parsed::Done(value) => Parsed::Done(f(value)),
+            Parsed::Incomplete { needed } => Parsed::Incomplete { needed },
+        }
+    }
+}
+
+/// A push-style (sans-IO) alternative to [`crate::Muncher`],
+/// for event loops (`mio`, `io_uring`, ...) that receive bytes in
+/// arbitrary fragments and can't block or `.await` inside the parser.
+///
+/// You [`PushMuncher::feed`] it bytes as they arrive, and ask for the next value.
+/// If that value hasn't fully arrived yet, you get [`Parsed::Incomplete`]
+/// (instead of an error) and nothing is consumed, so you can simply
+/// try again after feeding more.
+///
+/// The values are decoded by the same code as [`crate::Muncher`],
+/// so any [`Primitive`] (including your own) works here too.
+///
+/// ```
+/// # use bytemuncher::{End, Parsed, PushMuncher};
+/// let mut muncher = PushMuncher::new();
+///
+/// muncher.feed(&[0x05, b'H', b'e']);
+/// assert_eq!(
+///     muncher.read_pref_utf8::<u8>(End::Big).unwrap(),
+///     Parsed::Incomplete { needed: 3 }
+/// );
+///
+/// muncher.feed(b"llo");
+/// assert_eq!(
+///     muncher.read_pref_utf8::<u8>(End::Big).unwrap(),
+///     Parsed::Done("Hello".to_owned())
+/// );
+/// ```
+///
+/// # Errors
+///
+/// Errors (like invalid UTF-8) don't consume anything either,
+/// so retrying returns the same error.
+/// Like with [`crate::Muncher`], MUTF-8 methods return [`crate::MutfError`].
+#[derive(Debug, Clone)]
+pub struct PushMuncher<O = End> {
+    pub(crate) buf: Vec<u8>,
+    /// How much of `buf` has been consumed.
+    pos: usize,
+    consumed: u64,
+    alloc_limit_bytes: usize,
+    order: O,
+}
+
+impl PushMuncher {
+    /// Creates a new [`PushMuncher`] with the default configuration:
+    /// - Allocation limit of 1 GB: [`PushMuncher::set_max_alloc`]
+    /// - Default endianness of little endian: [`PushMuncher::set_end`]
+    #[must_use]
+    pub fn new() -> Self {
+        Self::with_order(End::Little)
+    }
+
+    /// Sets the default endianness of the [`PushMuncher`].
+    ///
+    /// Default: [`End::Little`]
+    pub fn set_end(&mut self, end: End) -> &mut Self {
+        self.order = end;
+        self
+    }
+}
+
+impl Default for PushMuncher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<O: ByteOrder> PushMuncher<O> {
+    /// Creates a new [`PushMuncher`] with the default endianness
+    /// decided by the `order` type (see [`ByteOrder`]).
+    ///
+    /// Other than that, it's the same as [`PushMuncher::new`].
+    pub fn with_order(order: O) -> Self {
+        Self {
+            buf: Vec::new(),
+            pos: 0,
+            consumed: 0,
+            alloc_limit_bytes: 1024 * 1024 * 1024,
+            order,
+        }
+    }
+
+    /// Sets a custom memory allocation limit (in bytes).
+    ///
+    /// Default: 1 GB
+    ///
+    /// Lengths read from the input above this fail with
+    /// [`ErrorKind::InvalidData`], instead of asking you
+    /// to feed (and buffer) that many bytes.
+    /// The same goes for null terminated strings that haven't
+    /// ended within this many bytes.
+    pub fn set_max_alloc(&mut self, alloc_limit_bytes: usize) -> &mut Self {
+        self.alloc_limit_bytes = alloc_limit_bytes;
+        self
+    }
+
+    /// Returns the default endianness of the [`PushMuncher`]
+    /// (see [`PushMuncher::set_end`] and [`PushMuncher::with_order`]).
+    #[must_use]
+    pub fn end(&self) -> End {
+        self.order.end()
+    }
+
+    /// Picks `end` if specified, otherwise the default byte order.
+    pub(crate) fn resolve_order(&self, end: impl Into<Option<End>>) -> Resolved<O> {
+        Resolved::new(end, self.order)
+    }
+
+    /// Appends `bytes` to the end of the buffered input.
+    pub fn feed(&mut self, bytes: &[u8]) {
+        // Drop the consumed bytes once they're the majority,
+        // so the buffer stays proportional to the unread input.
+        if self.pos > self.buf.len() / 2 {
+            self.buf.drain(..self.pos);
+            self.pos = 0;
+        }
+        self.buf.extend_from_slice(bytes);
+    }
+
+    /// Returns the bytes that have been fed but not consumed yet.
+    #[must_use]
+    pub fn buffered(&self) -> &[u8] {
+        &self.buf[self.pos..]
+    }
+
+    /// Returns the total number of bytes consumed so far.
+    #[must_use]
+    pub fn consumed_bytes(&self) -> u64 {
+        self.consumed
+    }
+
+    fn consume(&mut self, len: usize) {
+        self.pos += len;
+        self.consumed += len as u64;
+    }
+
+    /// Runs `decoder` on the buffered input,
+    /// consuming its bytes only if it completes.
+    pub(crate) fn decode<D: Decode>(&mut self, decoder: D) -> Result<Parsed<D::Output>, Error> {
+        self.decode_then(decoder, Ok)
+    }
+
+    /// Like [`PushMuncher::decode`], but also runs `convert` on the value
+    /// before consuming anything (for conversions with other error types).
+    pub(crate) fn decode_then<D: Decode, R, Err: From<Error>>(
+        &mut self,
+        decoder: D,
+        convert: impl FnOnce(D::Output) -> Result<R, Err>,
+    ) -> Result<Parsed<R>, Err> {
+        let (value, len) = match super::decode_slice(self.buffered(), decoder, |len| {
+            crate::check_max_alloc(len, self.alloc_limit_bytes)
+        })? {
+            Parsed::Done(done) => done,
+            Parsed::Incomplete { needed } => return Ok(Parsed::Incomplete { needed }),
+        };
+        let value = convert(value)?;
+        self.consume(len);
+        Ok(Parsed::Done(value))
+    }
+
+    // ================================
+    // Primitives
+    // ================================
+
+    /// Reads any [`Primitive`] type (such as integers or floats),
+    /// with the endianness specified in the `end` argument.
+    ///
+    /// For more info on endianness see [`crate::End`].
+    pub fn read_m<E: Primitive>(&mut self, end: End) -> Result<Parsed<E>, Error> {
+        self.decode(Value::new(end))
+    }
+
+    /// Reads any [`Primitive`] type (such as integers or floats),
+    /// with the default endianness (see [`PushMuncher::set_end`]).
+    pub fn read<E: Primitive>(&mut self) -> Result<Parsed<E>, Error> {
+        self.decode(Value::new(self.order))
+    }
+
+    /// Reads any [`Primitive`] type (such as integers or floats),
+    /// as little endian.
+    pub fn read_le<E: Primitive>(&mut self) -> Result<Parsed<E>, Error> {
+        self.read_m(End::Little)
+    }
+
+    /// Reads any [`Primitive`] type (such as integers or floats),
+    /// as big endian.
+    pub fn read_be<E: Primitive>(&mut self) -> Result<Parsed<E>, Error> {
+        self.read_m(End::Big)
+    }
+
+    /// Reads any [`Primitive`] type (such as integers or floats),
+    /// as native endian (as per the target platform).
+    pub fn read_ne<E: Primitive>(&mut self) -> Result<Parsed<E>, Error> {
+        self.read_m(End::Native)
+    }
+
+    // ================================
+    // Strings
+    // ================================
+
+    /// Reads some bytes prefixed by a length (number of bytes) of type `<E>`.
+    ///
+    /// Through the `end` argument you can choose the endianness of the length field,
+    /// or pass `None` to use the default endianness (see [`PushMuncher::set_end`]).
+    pub fn read_pref_bytes<E: Primitive>(
+        &mut self,
+        end: impl Into<Option<End>>,
+    ) -> Result<Parsed<Vec<u8>>, Error> {
+        let order = self.resolve_order(end);
+        self.decode(Prefixed::<E, _, _>::new(order, Bytes::new))
+    }
+
+    /// Reads a UTF-8 string prefixed by a length (number of bytes) of type `<E>`
+    /// (tries to parse, fails if invalid).
+    ///
+    /// See [`PushMuncher::read_pref_bytes`] for more info.
+    pub fn read_pref_utf8<E: Primitive>(
+        &mut self,
+        end: impl Into<Option<End>>,
+    ) -> Result<Parsed<String>, Error> {
+        let order = self.resolve_order(end);
+        self.decode(Prefixed::<E, _, _>::new(order, Bytes::new).map(crate::string::bytes2utf8))
+    }
+
+    /// Reads `len` number of bytes.
+    pub fn read_fixed_bytes(&mut self, len: usize) -> Result<Parsed<Vec<u8>>, Error> {
+        self.decode(Bytes::new(len))
+    }
+
+    /// Reads `len` number of bytes into a UTF-8 string
+    /// (tries to parse, fails if invalid).
+    pub fn read_fixed_utf8(&mut self, len: usize) -> Result<Parsed<String>, Error> {
+        self.decode(Bytes::new(len).map(crate::string::bytes2utf8))
+    }
+
+    /// Reads a UCS-2 string prefixed by a length (number of characters) of type `<E>`,
+    /// and converts it to a UTF-8 [`String`].
+    ///
+    /// See [`crate::Muncher::read_pref_ucs2`] for more info.
+    #[cfg(feature = "ucs2")]
+    pub fn read_pref_ucs2<E: Primitive>(
+        &mut self,
+        end: impl Into<Option<End>>,
+    ) -> Result<Parsed<String>, Error> {
+        let order = self.resolve_order(end);
+        self.decode(Prefixed::<E, _, _>::new(order, super::Ucs2::new))
+    }
+
+    /// Reads a UCS-2 string of `char_count` characters,
+    /// and converts it to a UTF-8 [`String`].
+    ///
+    /// See [`crate::Muncher::read_fixed_ucs2`] for more info.
+    #[cfg(feature = "ucs2")]
+    pub fn read_fixed_ucs2(&mut self, char_count: usize) -> Result<Parsed<String>, Error> {
+        self.decode(super::Ucs2::new(char_count))
+    }
+
+    /// Reads a C-style string (ending with `\0` null byte)
+    /// into a buffer of bytes (`Vec<u8>`).
+    ///
+    /// **The resulting buffer does not include a null byte!
+    /// Add one on your own if necessary!**
+    pub fn read_cstr_bytes(&mut self) -> Result<Parsed<Vec<u8>>, Error> {
+        self.read_cstr_then(|buf| Ok(buf.to_vec()))
+    }
+
+    /// Reads a C-style string (ending with `\0` null byte)
+    /// in the UTF-8 format (tries to parse, fails if invalid).
+    pub fn read_cstr_utf8(&mut self) -> Result<Parsed<String>, Error> {
+        self.read_cstr_then(|buf| crate::string::bytes2utf8(buf.to_vec()))
+    }
+
+    /// Finds a C-style string in the buffered input and runs `convert` on it
+    /// (without the null byte), consuming it only if that succeeds.
+    pub(crate) fn read_cstr_then<R, Err: From<Error>>(
+        &mut self,
+        convert: impl FnOnce(&[u8]) -> Result<R, Err>,
+    ) -> Result<Parsed<R>, Err> {
+        let input = self.buffered();
+        let Some(len) = input.iter().position(|&b| b == 0) else {
+            if input.len() > self.alloc_limit_bytes {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!(
+                        "null terminated string is too large (over {} bytes): surpassed the default (customizable) limit",
+                        self.alloc_limit_bytes
+                    ),
+                )
+                .into());
+            }
+            return Ok(Parsed::Incomplete { needed: 1 });
+        };
+        crate::check_max_alloc(len, self.alloc_limit_bytes)?;
+        let value = convert(&input[..len])?;
+        self.consume(len + 1);
+        Ok(Parsed::Done(value))
+    }
+}
@@ -0,0 +1,774 @@
+//! Record layouts described in a text schema, decoded (and encoded) at runtime
+//! into a tree of [`Value`]s (see [`Schema`], [`Muncher::read_schema`]
+//! and [`Muncher::write_schema`]).
+//!
+//! This lets formats be described and decoded without writing (or recompiling)
+//! any Rust code, using the same reads as the rest of the crate.
+//!
+//! ```
+//! # use bytemuncher::Muncher;
+//! # use bytemuncher::pack::Value;
+//! # use bytemuncher::schema::Schema;
+//! # use std::io::Cursor;
+//! let schema: Schema = r#"
+//!     endian big
+//!     magic: bytes[4]
+//!     version: u16
+//!     name: utf8[pref u8]
+//!     count: u8
+//!     points: {
+//!         x: i16
+//!         y: i16
+//!     }[count]
+//!     checksum: u32 le if version >= 2
+//! "#
+//! .parse()
+//! .unwrap();
+//!
+//! let data = b"DEMO\x00\x01\x03abc\x01\xFF\xFF\x00\x02";
+//! let value = Muncher::new(Cursor::new(data)).read_schema(&schema).unwrap();
+//!
+//! assert_eq!(value.get("name"), Some(&Value::Str("abc".to_owned())));
+//! assert_eq!(value.get("points"), Some(&Value::List(vec![Value::Record(vec![
+//!     ("x".to_owned(), Value::Int(-1)),
+//!     ("y".to_owned(), Value::Int(2)),
+//! ])])));
+//! assert_eq!(value.get("checksum"), None);
+//!
+//! let mut out = Vec::new();
+//! Muncher::new(&mut out).write_schema(&schema, &value).unwrap();
+//! assert_eq!(out, data);
+//! ```
+//!
+//! # Syntax
+//!
+//! A schema is a list of fields, each `name: type`, optionally followed
+//! by a byte order and a condition. Whitespace, commas
+//! and comments (from `#` to the end of the line) are ignored.
+//!
+//! **Types:**
+//! - Numbers: `u8`, `u16`, `u32`, `u64`, `i8`, `i16`, `i32`, `i64`, `f32`, `f64`,
+//!   and `bool` (one byte, `0` is false).
+//! - Strings: `bytes`, `utf8`, `mutf8` (crate feature: `mutf8`) and
+//!   `ucs2` (crate feature: `ucs2`), followed by their length in brackets:
+//!   - `utf8[16]`: a fixed length (in bytes, or characters for UCS-2).
+//!   - `utf8[len]`: the value of an earlier integer field.
+//!   - `utf8[pref u16]`: a length prefix of the given integer type.
+//!   - `utf8[cstr]`: null terminated (not for UCS-2).
+//! - Records: fields between `{` and `}`.
+//! - Arrays: any type followed by a length in brackets (like `u16[4]`,
+//!   `{ x: u8 y: u8 }[count]` or `utf8[pref u8][pref u16]`).
+//!   Arrays of numbers are read in one go.
+//!
+//! **Byte order:** `le`, `be` or `ne` (or `little`, `big`, `native`)
+//! after a type sets it for the numbers and length prefixes in that type,
+//! unless they have their own. The `endian big` statement sets it
+//! for the rest of the record. By default the byte order of
+//! the [`Muncher`] is used (see [`Muncher::set_end`]).
+//!
+//! **Conditions:** `if` after a field makes it only present when
+//! the condition holds. The condition is an earlier field, optionally compared
+//! to a number (`==`, `!=`, `<`, `<=`, `>`, `>=`, or `&` for any common bit set),
+//! to a quoted string (`==`, `!=`), or to `true`/`false`.
+//! A field alone is true if it's not zero (or empty).
+//!
+//! Fields can refer to earlier fields of their record, or of the records around it.
+//!
+//! [`Schema::from_specs`] parses a more compact list of fields
+//! (like `u32le magic, pref_u16be_utf8 name`), handy on a command line.
+
+use std::borrow::Cow;
+use std::io::{BufRead, Error, ErrorKind, Write};
+use std::str::FromStr;
+
+use crate::pack::Value;
+use crate::{ByteOrder, End, Muncher, Primitive};
+
+mod parse;
+
+/// A record layout, parsed from the text format described in the
+/// [`crate::schema`] module (with [`Schema::parse`] or [`str::parse`]).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Schema {
+    fields: Vec<Field>,
+}
+
+impl Schema {
+    /// Parses a schema from its text.
+    ///
+    /// Fails with [`ErrorKind::InvalidInput`] if it's invalid,
+    /// with the line number in the message.
+    pub fn parse(text: &str) -> Result<Self, Error> {
+        Ok(Self {
+            fields: parse::parse(text)?,
+        })
+    }
+
+    /// Parses a schema from a list of compact field specs, separated by commas,
+    /// each a type and then a name (like `"u32le magic, pref_u16be_utf8 name"`).
+    ///
+    /// The types are:
+    /// - Numbers, with an optional byte order suffix: `u8`, `u32le`, `f64be`, `i16ne`...
+    /// - Strings: `pref_<integer>_<encoding>` (like `pref_u16be_utf8`),
+    ///   `cstr_<encoding>` (like `cstr_bytes`) and
+    ///   `fixed<length>_<encoding>` (like `fixed16_utf8`).
+    ///
+    /// Any type can be followed by an array length in the schema syntax
+    /// (like `u16le[4]` or `cstr_utf8[count]`).
+    ///
+    /// Fails with [`ErrorKind::InvalidInput`] if a spec is invalid.
+    pub fn from_specs(specs: &str) -> Result<Self, Error> {
+        Self::parse(&parse::specs(specs)?)
+    }
+}
+
+impl FromStr for Schema {
+    type Err = Error;
+
+    fn from_str(text: &str) -> Result<Self, Error> {
+        Self::parse(text)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Field {
+    name: String,
+    ty: Type,
+    cond: Option<Cond>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Type {
+    Prim(Prim, Option<End>),
+    Str(Encoding, Len),
+    Array(Box<Type>, Len),
+    Record(Vec<Field>),
+}
+
+impl Type {
+    /// Sets the byte order of everything in the type that doesn't have one yet.
+    fn fill_end(&mut self, end: End) {
+        match self {
+            Type::Prim(_, e) => {
+                e.get_or_insert(end);
+            }
+            Type::Str(_, len) => len.fill_end(end),
+            Type::Array(ty, len) => {
+                ty.fill_end(end);
+                len.fill_end(end);
+            }
+            Type::Record(fields) => {
+                for field in fields {
+                    field.ty.fill_end(end);
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Prim {
+    U8,
+    U16,
+    U32,
+    U64,
+    I8,
+    I16,
+    I32,
+    I64,
+    F32,
+    F64,
+    Bool,
+}
+
+impl Prim {
+    fn parse(name: &str) -> Option<Self> {
+        Some(match name {
+            "u8" => Prim::U8,
+            "u16" => Prim::U16,
+            "u32" => Prim::U32,
+            "u64" => Prim::U64,
+            "i8" => Prim::I8,
+            "i16" => Prim::I16,
+            "i32" => Prim::I32,
+            "i64" => Prim::I64,
+            "f32" => Prim::F32,
+            "f64" => Prim::F64,
+            "bool" => Prim::Bool,
+            _ => return None,
+        })
+    }
+
+    fn is_int(self) -> bool {
+        !matches!(self, Prim::F32 | Prim::F64 | Prim::Bool)
+    }
+}
+
+/// Runs `$body` with `$t` as the type of a [`Prim`] (other than `bool`).
+macro_rules! with_prim {
+    ($prim:expr, $t:ident => $body:expr) => {
+        match $prim {
+            Prim::U8 => {
+                type $t = u8;
+                $body
+            }
+            Prim::U16 => {
+                type $t = u16;
+                $body
+            }
+            Prim::U32 => {
+                type $t = u32;
+                $body
+            }
+            Prim::U64 => {
+                type $t = u64;
+                $body
+            }
+            Prim::I8 => {
+                type $t = i8;
+                $body
+            }
+            Prim::I16 => {
+                type $t = i16;
+                $body
+            }
+            Prim::I32 => {
+                type $t = i32;
+                $body
+            }
+            Prim::I64 => {
+                type $t = i64;
+                $body
+            }
+            Prim::F32 => {
+                type $t = f32;
+                $body
+            }
+            Prim::F64 => {
+                type $t = f64;
+                $body
+            }
+            Prim::Bool => unreachable!("bool is handled separately"),
+        }
+    };
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Encoding {
+    Bytes,
+    Utf8,
+    #[cfg(feature = "mutf8")]
+    Mutf8,
+    #[cfg(feature = "ucs2")]
+    Ucs2,
+}
+
+impl Encoding {
+    /// Returns the encoding named `name`, or an error
+    /// if its crate feature isn't enabled.
+    fn parse(name: &str) -> Option<Result<Self, &'static str>> {
+        Some(Ok(match name {
+            "bytes" => Encoding::Bytes,
+            "utf8" => Encoding::Utf8,
+            #[cfg(feature = "mutf8")]
+            "mutf8" => Encoding::Mutf8,
+            #[cfg(not(feature = "mutf8"))]
+            "mutf8" => return Some(Err("`mutf8` needs the crate feature `mutf8`")),
+            #[cfg(feature = "ucs2")]
+            "ucs2" => Encoding::Ucs2,
+            #[cfg(not(feature = "ucs2"))]
+            "ucs2" => return Some(Err("`ucs2` needs the crate feature `ucs2`")),
+            _ => return None,
+        }))
+    }
+
+    fn has_cstr(self) -> bool {
+        #[cfg(feature = "ucs2")]
+        if self == Encoding::Ucs2 {
+            return false;
+        }
+        true
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Len {
+    Fixed(usize),
+    /// The value of an earlier field.
+    Field(String),
+    Prefix(Prim, Option<End>),
+    Cstr,
+}
+
+impl Len {
+    fn fill_end(&mut self, end: End) {
+        if let Len::Prefix(_, e) = self {
+            e.get_or_insert(end);
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Cond {
+    field: String,
+    op: Option<(Op, Literal)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    And,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Literal {
+    Int(i128),
+    Bytes(Vec<u8>),
+}
+
+/// The records being read or written, innermost last,
+/// to look up the fields referred to by lengths and conditions.
+type Scopes<'a> = Vec<&'a [(String, Value)]>;
+
+fn lookup<'v>(scopes: &[&'v [(String, Value)]], name: &str) -> Result<&'v Value, Error> {
+    scopes
+        .iter()
+        .rev()
+        .find_map(|fields| fields.iter().find(|(n, _)| n == name))
+        .map(|(_, value)| value)
+        .ok_or_else(|| {
+            Error::new(
+                ErrorKind::InvalidData,
+                format!("field `{name}` is missing (its condition was false)"),
+            )
+        })
+}
+
+fn as_int(value: &Value) -> Option<i128> {
+    match value {
+        Value::Bool(b) => Some(i128::from(*b)),
+        Value::Int(n) => Some(i128::from(*n)),
+        Value::UInt(n) => Some(i128::from(*n)),
+        _ => None,
+    }
+}
+
+/// Returns the length given by the field `name`.
+fn field_len(scopes: &[&[(String, Value)]], name: &str) -> Result<usize, Error> {
+    let value = lookup(scopes, name)?;
+    as_int(value)
+        .and_then(|n| usize::try_from(n).ok())
+        .ok_or_else(|| {
+            Error::new(
+                ErrorKind::InvalidData,
+                format!("field `{name}` isn't a valid length: {value:?}"),
+            )
+        })
+}
+
+impl Cond {
+    fn holds(&self, scopes: &[&[(String, Value)]]) -> Result<bool, Error> {
+        let value = lookup(scopes, &self.field)?;
+        let Some((op, literal)) = &self.op else {
+            return Ok(match value {
+                Value::Float(f) => *f != 0.0,
+                Value::Bytes(b) => !b.is_empty(),
+                Value::Str(s) => !s.is_empty(),
+                Value::List(l) => !l.is_empty(),
+                Value::Record(_) => true,
+                _ => as_int(value) != Some(0),
+            });
+        };
+        let invalid = || {
+            Error::new(
+                ErrorKind::InvalidData,
+                format!("field `{}` can't be compared: {value:?}", self.field),
+            )
+        };
+        if let Literal::Bytes(expected) = literal {
+            let actual = match value {
+                Value::Bytes(b) => b.as_slice(),
+                Value::Str(s) => s.as_bytes(),
+                _ => return Err(invalid()),
+            };
+            return Ok((actual == expected.as_slice()) == (*op == Op::Eq));
+        }
+        let Literal::Int(expected) = *literal else {
+            unreachable!()
+        };
+        if let Value::Float(actual) = *value {
+            let expected = expected as f64;
+            return Ok(match op {
+                Op::Eq => actual == expected,
+                Op::Ne => actual != expected,
+                Op::Lt => actual < expected,
+                Op::Le => actual <= expected,
+                Op::Gt => actual > expected,
+                Op::Ge => actual >= expected,
+                Op::And => return Err(invalid()),
+            });
+        }
+        let actual = as_int(value).ok_or_else(invalid)?;
+        Ok(match op {
+            Op::Eq => actual == expected,
+            Op::Ne => actual != expected,
+            Op::Lt => actual < expected,
+            Op::Le => actual <= expected,
+            Op::Gt => actual > expected,
+            Op::Ge => actual >= expected,
+            Op::And => actual & expected != 0,
+        })
+    }
+}
+
+/// Adds the field name to an error, keeping its kind.
+fn in_field(name: &str) -> impl FnOnce(Error) -> Error {
+    move |e| Error::new(e.kind(), format!("{name}: {e}"))
+}
+
+// ================================
+// Reading
+// ================================
+
+/// **Schemas** (see [`crate::schema`])
+impl<T: BufRead, O: ByteOrder> Muncher<T, O> {
+    /// Reads a record described by `schema`, returning it
+    /// as a [`Value::Record`] of its fields in order
+    /// (without the fields whose condition was false).
+    ///
+    /// Errors keep their kind, with the path of the field
+    /// added to the message (like `"points: x: failed to fill whole buffer"`).
+    /// When tracing (see [`Muncher::start_trace`]),
+    /// the reads are labeled with the field names.
+    pub fn read_schema(&mut self, schema: &Schema) -> Result<Value, Error> {
+        read_record(self, &schema.fields, &Vec::new())
+    }
+}
+
+fn read_record<T: BufRead, O: ByteOrder>(
+    m: &mut Muncher<T, O>,
+    fields: &[Field],
+    scopes: &Scopes<'_>,
+) -> Result<Value, Error> {
+    m.nested(|m| {
+        let mut record: Vec<(String, Value)> = Vec::new();
+        for field in fields {
+            // Look up the fields through a copy of the scopes that includes
+            // the record so far, as it can't be borrowed while it grows.
+            let value = {
+                let mut inner: Scopes<'_> = scopes.clone();
+                inner.push(&record);
+                if let Some(cond) = &field.cond
+                    && !cond.holds(&inner).map_err(in_field(&field.name))?
+                {
+                    continue;
+                }
+                read_type(&mut m.trace_label(&field.name), &field.ty, &inner)
+                    .map_err(in_field(&field.name))?
+            };
+            record.push((field.name.clone(), value));
+        }
+        Ok(Value::Record(record))
+    })
+}
+
+fn read_type<T: BufRead, O: ByteOrder>(
+    m: &mut Muncher<T, O>,
+    ty: &Type,
+    scopes: &Scopes<'_>,
+) -> Result<Value, Error> {
+    match ty {
+        Type::Prim(Prim::Bool, end) => Ok(Value::Bool(m.read_m::<u8>(m.resolve_end(*end))? != 0)),
+        Type::Prim(prim, end) => {
+            let end = m.resolve_end(*end);
+            with_prim!(*prim, P => Ok(m.read_m::<P>(end)?.into_value()))
+        }
+        Type::Str(encoding, len) => read_str(m, *encoding, len, scopes),
+        Type::Array(ty, len) => {
+            let count = match len {
+                Len::Fixed(n) => *n,
+                Len::Field(name) => field_len(scopes, name)?,
+                Len::Prefix(prim, end) => {
+                    let end = m.resolve_end(*end);
+                    with_prim!(*prim, P => m.read_m::<P>(end)?.into_usize())
+                }
+                Len::Cstr => unreachable!("checked when parsing"),
+            };
+            if let Type::Prim(prim, end) = **ty
+                && prim != Prim::Bool
+            {
+                let end = m.resolve_end(end);
+                return with_prim!(prim, P => Ok(Value::List(
+                    m.read_array_m::<P>(count, end)?
+                        .into_iter()
+                        .map(Dynamic::into_value)
+                        .collect(),
+                )));
+            }
+            m.check_len(count.saturating_mul(size_of::<Value>()))?;
+            let mut values = Vec::new();
+            for _ in 0..count {
+                values.push(read_type(m, ty, scopes)?);
+            }
+            Ok(Value::List(values))
+        }
+        Type::Record(fields) => read_record(m, fields, scopes),
+    }
+}
+
+fn read_str<T: BufRead, O: ByteOrder>(
+    m: &mut Muncher<T, O>,
+    encoding: Encoding,
+    len: &Len,
+    scopes: &Scopes<'_>,
+) -> Result<Value, Error> {
+    let len = match len {
+        Len::Fixed(n) => *n,
+        Len::Field(name) => field_len(scopes, name)?,
+        Len::Prefix(prim, end) => {
+            let end = m.resolve_end(*end);
+            return with_prim!(*prim, P => Ok(match encoding {
+                Encoding::Bytes => Value::Bytes(m.read_pref_bytes::<P>(end)?),
+                Encoding::Utf8 => Value::Str(m.read_pref_utf8::<P>(end)?),
+                #[cfg(feature = "mutf8")]
+                Encoding::Mutf8 => Value::Str(m.read_pref_mutf8::<P>(end).map_err(mutf_error)?),
+                #[cfg(feature = "ucs2")]
+                Encoding::Ucs2 => Value::Str(m.read_pref_ucs2::<P>(end)?),
+            }));
+        }
+        Len::Cstr => {
+            return Ok(match encoding {
+                Encoding::Bytes => Value::Bytes(m.read_cstr_bytes()?),
+                Encoding::Utf8 => Value::Str(m.read_cstr_utf8()?),
+                #[cfg(feature = "mutf8")]
+                Encoding::Mutf8 => Value::Str(m.read_cstr_mutf8().map_err(mutf_error)?),
+                #[cfg(feature = "ucs2")]
+                Encoding::Ucs2 => unreachable!("checked when parsing"),
+            });
+        }
+    };
+    Ok(match encoding {
+        Encoding::Bytes => Value::Bytes(m.read_fixed_bytes(len)?),
+        Encoding::Utf8 => Value::Str(m.read_fixed_utf8(len)?),
+        #[cfg(feature = "mutf8")]
+        Encoding::Mutf8 => Value::Str(m.read_fixed_mutf8(len).map_err(mutf_error)?),
+        #[cfg(feature = "ucs2")]
+        Encoding::Ucs2 => Value::Str(m.read_fixed_ucs2(len)?),
+    })
+}
+
+#[cfg(feature = "mutf8")]
+fn mutf_error(e: crate::MutfError) -> Error {
+    match e {
+        crate::MutfError::Io(e) => e,
+        e @ crate::MutfError::Mutf(_) => Error::new(ErrorKind::InvalidData, e.to_string()),
+    }
+}
+
+// ================================
+// Writing
+// ================================
+
+/// **Schemas** (see [`crate::schema`])
+impl<T: Write, O: ByteOrder> Muncher<T, O> {
+    /// Writes `value` (a [`Value::Record`]) as described by `schema`,
+    /// the reverse of [`Muncher::read_schema`].
+    ///
+    /// Fields whose condition is false are skipped, so they don't need to be there.
+    /// Lengths must match the values: fixed lengths and lengths given
+    /// by other fields aren't changed to fit, while length prefixes
+    /// are written from the length of the value.
+    ///
+    /// A value that doesn't fit the schema fails with [`ErrorKind::InvalidInput`],
+    /// with the path of the field added to the message. The fields before it
+    /// have been written by then.
+    pub fn write_schema(&mut self, schema: &Schema, value: &Value) -> Result<(), Error> {
+        write_record(self, &schema.fields, value, &mut Vec::new())
+    }
+}
+
+fn invalid(msg: impl Into<String>) -> Error {
+    Error::new(ErrorKind::InvalidInput, msg.into())
+}
+
+fn write_record<'v, T: Write, O: ByteOrder>(
+    m: &mut Muncher<T, O>,
+    fields: &[Field],
+    value: &'v Value,
+    scopes: &mut Scopes<'v>,
+) -> Result<(), Error> {
+    let Value::Record(record) = value else {
+        return Err(invalid(format!("expected a record, got {value:?}")));
+    };
+    scopes.push(record);
+    for field in fields {
+        write_field(m, field, value, scopes).map_err(in_field(&field.name))?;
+    }
+    scopes.pop();
+    Ok(())
+}
+
+fn write_field<'v, T: Write, O: ByteOrder>(
+    m: &mut Muncher<T, O>,
+    field: &Field,
+    record: &'v Value,
+    scopes: &mut Scopes<'v>,
+) -> Result<(), Error> {
+    if let Some(cond) = &field.cond
+        && !cond.holds(scopes)?
+    {
+        return Ok(());
+    }
+    let value = record
+        .get(&field.name)
+        .ok_or_else(|| invalid("missing field"))?;
+    write_type(&mut m.trace_label(&field.name), &field.ty, value, scopes)
+}
+
+fn write_type<'v, T: Write, O: ByteOrder>(
+    m: &mut Muncher<T, O>,
+    ty: &Type,
+    value: &'v Value,
+    scopes: &mut Scopes<'v>,
+) -> Result<(), Error> {
+    match ty {
+        Type::Prim(Prim::Bool, end) => match value {
+            Value::Bool(b) => m.write_m(u8::from(*b), m.resolve_end(*end)),
+            _ => Err(invalid(format!("expected a bool, got {value:?}"))),
+        },
+        Type::Prim(prim, end) => {
+            let end = m.resolve_end(*end);
+            with_prim!(*prim, P => m.write_m(P::from_value(value)?, end))
+        }
+        Type::Str(encoding, len) => write_str(m, *encoding, len, value, scopes),
+        Type::Array(ty, len) => {
+            let Value::List(values) = value else {
+                return Err(invalid(format!("expected a list, got {value:?}")));
+            };
+            write_len(m, len, values.len(), scopes)?;
+            for value in values {
+                write_type(m, ty, value, scopes)?;
+            }
+            Ok(())
+        }
+        Type::Record(fields) => write_record(m, fields, value, scopes),
+    }
+}
+
+/// Writes the length prefix of `len`, or checks that `actual` matches it.
+fn write_len<T: Write, O: ByteOrder>(
+    m: &mut Muncher<T, O>,
+    len: &Len,
+    actual: usize,
+    scopes: &Scopes<'_>,
+) -> Result<(), Error> {
+    let expected = match len {
+        Len::Fixed(n) => *n,
+        Len::Field(name) => field_len(scopes, name).map_err(|e| invalid(e.to_string()))?,
+        Len::Prefix(prim, end) => {
+            let end = m.resolve_end(*end);
+            return with_prim!(*prim, P => {
+                if P::from_usize(actual).into_usize() != actual {
+                    return Err(invalid(format!("length {actual} doesn't fit the length prefix")));
+                }
+                m.write_m(P::from_usize(actual), end)
+            });
+        }
+        Len::Cstr => unreachable!("handled by the caller"),
+    };
+    if actual != expected {
+        return Err(invalid(format!(
+            "length is {actual}, but should be {expected}"
+        )));
+    }
+    Ok(())
+}
+
+fn write_str<T: Write, O: ByteOrder>(
+    m: &mut Muncher<T, O>,
+    encoding: Encoding,
+    len: &Len,
+    value: &Value,
+    scopes: &Scopes<'_>,
+) -> Result<(), Error> {
+    let bytes = match (encoding, value) {
+        (Encoding::Bytes, Value::Bytes(bytes)) => Cow::Borrowed(bytes.as_slice()),
+        (Encoding::Bytes, _) => return Err(invalid(format!("expected bytes, got {value:?}"))),
+        (_, Value::Str(s)) => encode_str(encoding, s)?,
+        _ => return Err(invalid(format!("expected a string, got {value:?}"))),
+    };
+    match (encoding, len) {
+        (_, Len::Cstr) => m.write_cstr_bytes(&bytes),
+        #[cfg(feature = "ucs2")]
+        (Encoding::Ucs2, _) => {
+            let (char_count, ucs2) = crate::sans_io::encode_ucs2(
+                std::str::from_utf8(&bytes).map_err(|e| invalid(e.to_string()))?,
+            )?;
+            write_len(m, len, char_count, scopes)?;
+            m.write_all(&ucs2)
+        }
+        _ => {
+            write_len(m, len, bytes.len(), scopes)?;
+            m.write_all(&bytes)
+        }
+    }
+}
+
+/// Converts a string to the bytes of `encoding`
+/// (other than UCS-2, which is converted when written).
+#[cfg_attr(not(feature = "mutf8"), allow(unused_variables))]
+fn encode_str(encoding: Encoding, s: &str) -> Result<Cow<'_, [u8]>, Error> {
+    #[cfg(feature = "mutf8")]
+    if encoding == Encoding::Mutf8 {
+        return crate::mutf::utf2mutf(s).map_err(mutf_error);
+    }
+    Ok(Cow::Borrowed(s.as_bytes()))
+}
+
+/// Conversions between the [`Prim`] types and [`Value`]s.
+trait Dynamic: Primitive + Sized {
+    fn into_value(self) -> Value;
+    fn from_value(value: &Value) -> Result<Self, Error>;
+}
+
+macro_rules! impl_dynamic {
+    ($variant:ident: $($t:ty),*) => {$(
+        impl Dynamic for $t {
+            fn into_value(self) -> Value {
+                Value::$variant(self.into())
+            }
+
+            fn from_value(value: &Value) -> Result<Self, Error> {
+                crate::pack::int(stringify!($t), value)
+            }
+        }
+    )*};
+}
+
+impl_dynamic!(Int: i8, i16, i32, i64);
+impl_dynamic!(UInt: u8, u16, u32, u64);
+
+impl Dynamic for f32 {
+    fn into_value(self) -> Value {
+        Value::Float(self.into())
+    }
+
+    fn from_value(value: &Value) -> Result<Self, Error> {
+        crate::pack::float("f32", value).map(|f| f as f32)
+    }
+}
+
+impl Dynamic for f64 {
+    fn into_value(self) -> Value {
+        Value::Float(self)
+    }
+
+    fn from_value(value: &Value) -> Result<Self, Error> {
+        crate::pack::float("f64", value)
+    }
+}
@@ -0,0 +1,429 @@
+use std::io::{Error, ErrorKind};
+
+use super::{Cond, Encoding, Field, Len, Literal, Op, Prim, Type};
+use crate::End;
+
+/// How deep records can be nested in a schema.
+const MAX_NESTING: usize = 64;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Tok {
+    Ident(String),
+    Int(i128),
+    Str(Vec<u8>),
+    Punct(&'static str),
+}
+
+#[derive(Debug)]
+struct Token {
+    tok: Tok,
+    line: usize,
+}
+
+const PUNCTS: [&str; 12] = [
+    "==", "!=", "<=", ">=", "<", ">", "&", ":", "{", "}", "[", "]",
+];
+
+fn error(line: usize, msg: impl std::fmt::Display) -> Error {
+    Error::new(
+        ErrorKind::InvalidInput,
+        format!("schema line {line}: {msg}"),
+    )
+}
+
+fn tokenize(text: &str) -> Result<Vec<Token>, Error> {
+    let mut tokens = Vec::new();
+    let mut line = 1;
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        if c == '\n' {
+            line += 1;
+            rest = &rest[1..];
+        } else if c.is_whitespace() || c == ',' {
+            rest = &rest[c.len_utf8()..];
+        } else if c == '#' {
+            rest = rest.find('\n').map_or("", |i| &rest[i..]);
+        } else if c.is_ascii_alphabetic() || c == '_' {
+            let len = rest
+                .find(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+                .unwrap_or(rest.len());
+            tokens.push(Token {
+                tok: Tok::Ident(rest[..len].to_owned()),
+                line,
+            });
+            rest = &rest[len..];
+        } else if c.is_ascii_digit()
+            || (c == '-' && rest[1..].starts_with(|c: char| c.is_ascii_digit()))
+        {
+            let len = rest[1..]
+                .find(|c: char| !c.is_ascii_alphanumeric())
+                .map_or(rest.len(), |i| i + 1);
+            let int = parse_int(&rest[..len])
+                .ok_or_else(|| error(line, format!("invalid number `{}`", &rest[..len])))?;
+            tokens.push(Token {
+                tok: Tok::Int(int),
+                line,
+            });
+            rest = &rest[len..];
+        } else if c == '"' {
+            let (bytes, len) = parse_str(rest).ok_or_else(|| error(line, "invalid string"))?;
+            tokens.push(Token {
+                tok: Tok::Str(bytes),
+                line,
+            });
+            rest = &rest[len..];
+        } else if let Some(punct) = PUNCTS.iter().find(|p| rest.starts_with(**p)) {
+            tokens.push(Token {
+                tok: Tok::Punct(punct),
+                line,
+            });
+            rest = &rest[punct.len()..];
+        } else {
+            return Err(error(line, format!("unexpected `{c}`")));
+        }
+    }
+    Ok(tokens)
+}
+
+fn parse_int(s: &str) -> Option<i128> {
+    let (negative, digits) = match s.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, s),
+    };
+    let n = match digits.strip_prefix("0x") {
+        Some(hex) => i128::from_str_radix(hex, 16).ok()?,
+        None => digits.parse().ok()?,
+    };
+    Some(if negative { -n } else { n })
+}
+
+/// Parses a quoted string at the start of `s`,
+/// returning its bytes and how long it was.
+fn parse_str(s: &str) -> Option<(Vec<u8>, usize)> {
+    let mut bytes = Vec::new();
+    let mut chars = s.char_indices().skip(1);
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' => return Some((bytes, i + 1)),
+            '\n' => return None,
+            '\\' => match chars.next()?.1 {
+                'n' => bytes.push(b'\n'),
+                'r' => bytes.push(b'\r'),
+                't' => bytes.push(b'\t'),
+                '0' => bytes.push(0),
+                'x' => {
+                    let hi = chars.next()?.1.to_digit(16)?;
+                    let lo = chars.next()?.1.to_digit(16)?;
+                    bytes.push((hi * 16 + lo) as u8);
+                }
+                c @ ('\\' | '"') => bytes.push(c as u8),
+                _ => return None,
+            },
+            c => bytes.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes()),
+        }
+    }
+    None
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    /// The names of the fields declared so far in each record being parsed.
+    scopes: Vec<Vec<String>>,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Tok> {
+        self.tokens.get(self.pos).map(|t| &t.tok)
+    }
+
+    fn line(&self) -> usize {
+        self.tokens
+            .get(self.pos)
+            .or(self.tokens.last())
+            .map_or(1, |t| t.line)
+    }
+
+    fn error(&self, msg: impl std::fmt::Display) -> Error {
+        error(self.line(), msg)
+    }
+
+    fn next(&mut self) -> Result<Tok, Error> {
+        let tok = self
+            .tokens
+            .get(self.pos)
+            .ok_or_else(|| self.error("unexpected end of schema"))?
+            .tok
+            .clone();
+        self.pos += 1;
+        Ok(tok)
+    }
+
+    fn eat(&mut self, punct: &str) -> bool {
+        if matches!(self.peek(), Some(Tok::Punct(p)) if *p == punct) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, punct: &str) -> Result<(), Error> {
+        if self.eat(punct) {
+            Ok(())
+        } else {
+            Err(self.error(format!("expected `{punct}`")))
+        }
+    }
+
+    fn ident(&mut self, what: &str) -> Result<String, Error> {
+        match self.next()? {
+            Tok::Ident(ident) => Ok(ident),
+            _ => {
+                self.pos -= 1;
+                Err(self.error(format!("expected {what}")))
+            }
+        }
+    }
+
+    fn peek_ident(&self, ident: &str) -> bool {
+        matches!(self.peek(), Some(Tok::Ident(i)) if i == ident)
+    }
+
+    /// Parses fields until the end of the record (or schema).
+    fn fields(&mut self, depth: usize) -> Result<Vec<Field>, Error> {
+        if depth > MAX_NESTING {
+            return Err(self.error("records are nested too deep"));
+        }
+        self.scopes.push(Vec::new());
+        let mut fields = Vec::new();
+        let mut default_end = None;
+        while self.peek().is_some() && self.peek() != Some(&Tok::Punct("}")) {
+            let is_statement = self.peek_ident("endian")
+                && self.tokens.get(self.pos + 1).map(|t| &t.tok) != Some(&Tok::Punct(":"));
+            if is_statement {
+                self.pos += 1;
+                default_end = Some(
+                    self.end()?
+                        .ok_or_else(|| self.error("expected a byte order"))?,
+                );
+                continue;
+            }
+
+            let line = self.line();
+            let name = self.ident("a field name")?;
+            if self.scopes.last().is_some_and(|s| s.contains(&name)) {
+                return Err(error(line, format!("duplicate field `{name}`")));
+            }
+            self.expect(":")?;
+            let mut ty = self.ty(depth)?;
+            if let Some(end) = self.end()? {
+                ty.fill_end(end);
+            }
+            if let Some(end) = default_end {
+                ty.fill_end(end);
+            }
+            let cond = if self.peek_ident("if") {
+                self.pos += 1;
+                Some(self.cond()?)
+            } else {
+                None
+            };
+            if let Some(scope) = self.scopes.last_mut() {
+                scope.push(name.clone());
+            }
+            fields.push(Field { name, ty, cond });
+        }
+        self.scopes.pop();
+        Ok(fields)
+    }
+
+    fn ty(&mut self, depth: usize) -> Result<Type, Error> {
+        let mut ty = if self.eat("{") {
+            let fields = self.fields(depth + 1)?;
+            self.expect("}")?;
+            Type::Record(fields)
+        } else {
+            let name = self.ident("a type")?;
+            if let Some(prim) = Prim::parse(&name) {
+                Type::Prim(prim, None)
+            } else if let Some(encoding) = Encoding::parse(&name) {
+                let encoding = encoding.map_err(|e| self.error(e))?;
+                let len = self.len()?.ok_or_else(|| {
+                    self.error(format!("`{name}` needs a length, like `{name}[16]`"))
+                })?;
+                if matches!(len, Len::Cstr) && !encoding.has_cstr() {
+                    return Err(self.error(format!("`{name}` can't be null terminated")));
+                }
+                Type::Str(encoding, len)
+            } else {
+                self.pos -= 1;
+                return Err(self.error(format!("unknown type `{name}`")));
+            }
+        };
+        while let Some(len) = self.len()? {
+            if matches!(len, Len::Cstr) {
+                return Err(self.error("arrays can't be null terminated"));
+            }
+            ty = Type::Array(Box::new(ty), len);
+        }
+        Ok(ty)
+    }
+
+    /// Parses a `[length]`, if there is one.
+    fn len(&mut self) -> Result<Option<Len>, Error> {
+        if !self.eat("[") {
+            return Ok(None);
+        }
+        let len = match self.next()? {
+            Tok::Int(n) => Len::Fixed(
+                usize::try_from(n).map_err(|_| self.error(format!("invalid length {n}")))?,
+            ),
+            Tok::Ident(ident) if ident == "cstr" => Len::Cstr,
+            Tok::Ident(ident) if ident == "pref" => {
+                let name = self.ident("a length prefix type")?;
+                let prim = Prim::parse(&name)
+                    .filter(|p| p.is_int())
+                    .ok_or_else(|| self.error(format!("invalid length prefix type `{name}`")))?;
+                Len::Prefix(prim, self.end()?)
+            }
+            Tok::Ident(field) => {
+                self.check_field(&field)?;
+                Len::Field(field)
+            }
+            _ => return Err(self.error("expected a length")),
+        };
+        self.expect("]")?;
+        Ok(Some(len))
+    }
+
+    /// Parses a byte order, if there is one
+    /// (and it's not the name of the next field).
+    fn end(&mut self) -> Result<Option<End>, Error> {
+        if matches!(self.tokens.get(self.pos + 1), Some(t) if t.tok == Tok::Punct(":")) {
+            return Ok(None);
+        }
+        let end = match self.peek() {
+            Some(Tok::Ident(i)) => match i.as_str() {
+                "le" | "little" => End::Little,
+                "be" | "big" => End::Big,
+                "ne" | "native" => End::Native,
+                _ => return Ok(None),
+            },
+            _ => return Ok(None),
+        };
+        self.pos += 1;
+        Ok(Some(end))
+    }
+
+    fn cond(&mut self) -> Result<Cond, Error> {
+        let field = self.ident("a field name")?;
+        self.check_field(&field)?;
+        let op = match self.peek() {
+            Some(Tok::Punct(p)) => match *p {
+                "==" => Op::Eq,
+                "!=" => Op::Ne,
+                "<" => Op::Lt,
+                "<=" => Op::Le,
+                ">" => Op::Gt,
+                ">=" => Op::Ge,
+                "&" => Op::And,
+                _ => return Ok(Cond { field, op: None }),
+            },
+            _ => return Ok(Cond { field, op: None }),
+        };
+        self.pos += 1;
+        let literal = match self.next()? {
+            Tok::Int(n) => Literal::Int(n),
+            Tok::Str(s) if matches!(op, Op::Eq | Op::Ne) => Literal::Bytes(s),
+            Tok::Ident(i) if i == "true" => Literal::Int(1),
+            Tok::Ident(i) if i == "false" => Literal::Int(0),
+            _ => {
+                self.pos -= 1;
+                return Err(self.error("expected a number (or a string after `==` or `!=`)"));
+            }
+        };
+        Ok(Cond {
+            field,
+            op: Some((op, literal)),
+        })
+    }
+
+    /// Checks that `field` was declared before (in this record or one around it).
+    fn check_field(&self, field: &str) -> Result<(), Error> {
+        if self.scopes.iter().any(|s| s.iter().any(|f| f == field)) {
+            Ok(())
+        } else {
+            Err(self.error(format!(
+                "unknown field `{field}` (fields can only refer to earlier fields)"
+            )))
+        }
+    }
+}
+
+pub(super) fn parse(text: &str) -> Result<Vec<Field>, Error> {
+    let mut parser = Parser {
+        tokens: tokenize(text)?,
+        pos: 0,
+        scopes: Vec::new(),
+    };
+    let fields = parser.fields(0)?;
+    if parser.peek().is_some() {
+        return Err(parser.error("unexpected `}`"));
+    }
+    Ok(fields)
+}
+
+/// Translates field specs (like `u32le magic, pref_u16be_utf8 name`)
+/// into schema text, one field per line.
+pub(super) fn specs(text: &str) -> Result<String, Error> {
+    let mut schema = String::new();
+    for (i, spec) in text.split(',').enumerate() {
+        if spec.trim().is_empty() {
+            continue;
+        }
+        let spec_error = |msg: &str| {
+            Error::new(
+                ErrorKind::InvalidInput,
+                format!("field spec {} (`{}`): {msg}", i + 1, spec.trim()),
+            )
+        };
+        let mut words = spec.split_whitespace();
+        let (Some(ty), Some(name), None) = (words.next(), words.next(), words.next()) else {
+            return Err(spec_error("expected a type and a name"));
+        };
+        // Arrays keep the schema syntax (like `u16le[4]`)
+        let (ty, array) = ty.split_at(ty.find('[').unwrap_or(ty.len()));
+        let (ty, end) = spec_type(ty).ok_or_else(|| spec_error("unknown type"))?;
+        schema.push_str(&format!("{name}: {ty}{array} {end}\n"));
+    }
+    Ok(schema)
+}
+
+/// Translates a compact type, like `u32le`, `pref_u16be_utf8`,
+/// `cstr_bytes` or `fixed16_utf8`, into its schema syntax
+/// and byte order (which goes after any array length).
+fn spec_type(ty: &str) -> Option<(String, &str)> {
+    let prim = |ty: &str| -> Option<(String, &str)> {
+        let (name, end) = ["le", "be", "ne"]
+            .iter()
+            .find_map(|end| Some((ty.strip_suffix(end)?, *end)))
+            .unwrap_or((ty, ""));
+        Prim::parse(name)?;
+        Some((name.to_owned(), end))
+    };
+    let Some((len, encoding)) = ty.rsplit_once('_') else {
+        return prim(ty);
+    };
+    // An encoding behind a disabled crate feature is explained by the schema parser
+    _ = Encoding::parse(encoding)?;
+    let len = if len == "cstr" {
+        "cstr".to_owned()
+    } else if let Some(count) = len.strip_prefix("fixed") {
+        count.parse::<usize>().ok()?.to_string()
+    } else {
+        let (prefix, end) = prim(len.strip_prefix("pref_")?)?;
+        format!("pref {prefix} {end}")
+    };
+    Some((format!("{encoding}[{}]", len.trim_end()), ""))
+}
@@ -0,0 +1,430 @@
+#[cfg(feature = "ucs2")]
+use std::borrow::Cow;
+use std::io::{Error, ErrorKind, SeekFrom};
+use std::ops::Range;
+
+use crate::order::Resolved;
+use crate::string::bytes2str;
+use crate::{ByteOrder, End, Primitive};
+
+/// A zero-copy alternative to [`crate::Muncher`], for when
+/// the whole input is already in memory (a `Vec<u8>`, an mmap, ...).
+///
+/// Strings and bytes are returned as `&'a [u8]` and `&'a str`
+/// borrowed straight from the input, instead of being copied into
+/// new buffers like `Muncher::new(Cursor::new(...))` has to.
+/// Formats that need converting (MUTF-8, UCS-2) return a [`std::borrow::Cow`],
+/// which is only owned if something actually had to be converted.
+///
+/// ```
+/// # use bytemuncher::{End, SliceMuncher};
+/// let data = b"\x05Hello\x02\x00\x34\x12world\0";
+/// let mut muncher = SliceMuncher::new(data);
+///
+/// let hello: &str = muncher.read_pref_utf8::<u8>(None).unwrap();
+/// assert_eq!(hello, "Hello");
+///
+/// // Parse a section on its own, with its own position
+/// let mut section = muncher.read_sub::<u16>(None).unwrap();
+/// assert_eq!(section.read_le::<u16>().unwrap(), 0x1234);
+/// assert!(section.is_eof());
+///
+/// assert_eq!(muncher.read_cstr_utf8().unwrap(), "world");
+/// assert_eq!(muncher.position(), data.len());
+/// ```
+///
+/// # Errors
+///
+/// Reads past the end fail with [`ErrorKind::UnexpectedEof`].
+/// A read that fails doesn't move the position.
+#[derive(Debug, Clone)]
+pub struct SliceMuncher<'a, O = End> {
+    data: &'a [u8],
+    pos: usize,
+    order: O,
+}
+
+impl<'a> SliceMuncher<'a> {
+    /// Creates a new [`SliceMuncher`] over `data`, starting at position 0,
+    /// with the default endianness of little endian (see [`SliceMuncher::set_end`]).
+    #[must_use]
+    pub fn new(data: &'a [u8]) -> Self {
+        Self::with_order(data, End::Little)
+    }
+
+    /// Sets the default endianness of the [`SliceMuncher`].
+    ///
+    /// Default: [`End::Little`]
+    pub fn set_end(&mut self, end: End) -> &mut Self {
+        self.order = end;
+        self
+    }
+}
+
+impl<'a, O: ByteOrder> SliceMuncher<'a, O> {
+    /// Creates a new [`SliceMuncher`] with the default endianness
+    /// decided by the `order` type (see [`ByteOrder`]).
+    ///
+    /// Other than that, it's the same as [`SliceMuncher::new`].
+    pub fn with_order(data: &'a [u8], order: O) -> Self {
+        Self {
+            data,
+            pos: 0,
+            order,
+        }
+    }
+
+    /// Returns the default endianness of the [`SliceMuncher`]
+    /// (see [`SliceMuncher::set_end`] and [`SliceMuncher::with_order`]).
+    #[must_use]
+    pub fn end(&self) -> End {
+        self.order.end()
+    }
+
+    // ================================
+    // Position
+    // ================================
+
+    /// Returns the whole input, including what's already been read.
+    #[must_use]
+    pub fn as_slice(&self) -> &'a [u8] {
+        self.data
+    }
+
+    /// Returns the input that hasn't been read yet.
+    #[must_use]
+    pub fn remaining(&self) -> &'a [u8] {
+        &self.data[self.pos..]
+    }
+
+    /// Returns `true` if there's nothing more to read.
+    #[must_use]
+    pub fn is_eof(&self) -> bool {
+        self.pos == self.data.len()
+    }
+
+    /// Returns the current position (in bytes, from the start of the input).
+    #[must_use]
+    pub fn position(&self) -> usize {
+        self.pos
+    }
+
+    /// Moves to `pos` (in bytes, from the start of the input).
+    ///
+    /// Fails with [`ErrorKind::InvalidInput`] if `pos` is past the end.
+    pub fn set_position(&mut self, pos: usize) -> Result<(), Error> {
+        if pos > self.data.len() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "position {pos} is past the end of the input ({} bytes)",
+                    self.data.len()
+                ),
+            ));
+        }
+        self.pos = pos;
+        Ok(())
+    }
+
+    /// Moves the position like [`std::io::Seek::seek`],
+    /// returning the new position.
+    ///
+    /// Unlike [`std::io::Seek`], seeking past the end
+    /// (or before the start) fails with [`ErrorKind::InvalidInput`].
+    pub fn seek(&mut self, pos: SeekFrom) -> Result<usize, Error> {
+        let (base, offset) = match pos {
+            SeekFrom::Start(offset) => (0, i128::from(offset)),
+            SeekFrom::End(offset) => (self.data.len(), i128::from(offset)),
+            SeekFrom::Current(offset) => (self.pos, i128::from(offset)),
+        };
+        let pos = usize::try_from(base as i128 + offset).map_err(|_| {
+            Error::new(
+                ErrorKind::InvalidInput,
+                "invalid seek to a negative or overflowing position",
+            )
+        })?;
+        self.set_position(pos)?;
+        Ok(pos)
+    }
+
+    /// Skips `len` bytes.
+    pub fn skip(&mut self, len: usize) -> Result<(), Error> {
+        self.read_fixed_bytes(len).map(|_| ())
+    }
+
+    /// Returns a new [`SliceMuncher`] over the `range` of the input
+    /// (in bytes, from the start), without moving the position.
+    ///
+    /// Useful for formats with offset tables (like ELF or TTF).
+    pub fn sub(&self, range: Range<usize>) -> Result<Self, Error> {
+        let data = self.data.get(range.clone()).ok_or_else(|| {
+            Error::new(
+                ErrorKind::UnexpectedEof,
+                format!(
+                    "range {range:?} is out of bounds of the input ({} bytes)",
+                    self.data.len()
+                ),
+            )
+        })?;
+        Ok(Self::with_order(data, self.order))
+    }
+
+    /// Reads the next `len` bytes as a new [`SliceMuncher`]
+    /// (with its own position, starting at 0).
+    pub fn read_fixed_sub(&mut self, len: usize) -> Result<Self, Error> {
+        let data = self.read_fixed_bytes(len)?;
+        Ok(Self::with_order(data, self.order))
+    }
+
+    /// Reads a section prefixed by a length (number of bytes) of type `<E>`
+    /// as a new [`SliceMuncher`] (with its own position, starting at 0).
+    ///
+    /// Through the `end` argument you can choose the endianness of the length field,
+    /// or pass `None` to use the default endianness (see [`SliceMuncher::set_end`]).
+    pub fn read_sub<E: Primitive>(&mut self, end: impl Into<Option<End>>) -> Result<Self, Error> {
+        let data = self.read_pref_bytes::<E>(end)?;
+        Ok(Self::with_order(data, self.order))
+    }
+
+    /// Runs `read` on the remaining input,
+    /// moving the position past what it consumed only if it succeeds.
+    fn advance<R, Err>(
+        &mut self,
+        read: impl FnOnce(&mut &'a [u8]) -> Result<R, Err>,
+    ) -> Result<R, Err> {
+        let mut rest = self.remaining();
+        let value = read(&mut rest)?;
+        self.pos = self.data.len() - rest.len();
+        Ok(value)
+    }
+
+    // ================================
+    // Primitives
+    // ================================
+
+    /// Reads any [`Primitive`] type (such as integers or floats),
+    /// with the endianness specified in the `end` argument.
+    ///
+    /// For more info on endianness see [`crate::End`].
+    pub fn read_m<E: Primitive>(&mut self, end: End) -> Result<E, Error> {
+        self.read_order(end)
+    }
+
+    /// Reads any [`Primitive`] type (such as integers or floats),
+    /// with the default endianness (see [`SliceMuncher::set_end`]).
+    pub fn read<E: Primitive>(&mut self) -> Result<E, Error> {
+        self.read_order(self.order)
+    }
+
+    /// Reads any [`Primitive`] type in any [`ByteOrder`].
+    fn read_order<E: Primitive>(&mut self, order: impl ByteOrder) -> Result<E, Error> {
+        self.advance(|rest| E::read_order(rest, order))
+    }
+
+    /// Reads any [`Primitive`] type (such as integers or floats),
+    /// as little endian.
+    pub fn read_le<E: Primitive>(&mut self) -> Result<E, Error> {
+        self.read_m(End::Little)
+    }
+
+    /// Reads any [`Primitive`] type (such as integers or floats),
+    /// as big endian.
+    pub fn read_be<E: Primitive>(&mut self) -> Result<E, Error> {
+        self.read_m(End::Big)
+    }
+
+    /// Reads any [`Primitive`] type (such as integers or floats),
+    /// as native endian (as per the target platform).
+    pub fn read_ne<E: Primitive>(&mut self) -> Result<E, Error> {
+        self.read_m(End::Native)
+    }
+
+    /// Reads any [`Primitive`] type (such as integers or floats),
+    /// with the endianness specified in the `end` argument,
+    /// **without consuming it**.
+    pub fn peek<E: Primitive>(&self, end: End) -> Result<E, Error> {
+        E::read_endian(&mut self.remaining(), end)
+    }
+
+    /// Returns up to `len` upcoming bytes **without consuming them**.
+    ///
+    /// The returned slice is only shorter than `len`
+    /// if the end of input was reached.
+    #[must_use]
+    pub fn peek_bytes(&self, len: usize) -> &'a [u8] {
+        let rest = self.remaining();
+        &rest[..len.min(rest.len())]
+    }
+
+    // ================================
+    // Strings
+    // ================================
+
+    /// Reads `len` number of bytes, borrowed from the input.
+    pub fn read_fixed_bytes(&mut self, len: usize) -> Result<&'a [u8], Error> {
+        self.read_fixed_with(len, Ok)
+    }
+
+    /// Reads `len` number of bytes into a UTF-8 string
+    /// (tries to parse, fails if invalid), borrowed from the input.
+    pub fn read_fixed_utf8(&mut self, len: usize) -> Result<&'a str, Error> {
+        self.read_fixed_with(len, bytes2str)
+    }
+
+    /// Reads some bytes prefixed by a length (number of bytes) of type `<E>`,
+    /// borrowed from the input.
+    ///
+    /// Through the `end` argument you can choose the endianness of the length field,
+    /// or pass `None` to use the default endianness (see [`SliceMuncher::set_end`]).
+    pub fn read_pref_bytes<E: Primitive>(
+        &mut self,
+        end: impl Into<Option<End>>,
+    ) -> Result<&'a [u8], Error> {
+        self.read_pref_with::<E, _, _>(end, Ok)
+    }
+
+    /// Reads a UTF-8 string prefixed by a length (number of bytes) of type `<E>`
+    /// (tries to parse, fails if invalid), borrowed from the input.
+    ///
+    /// See [`SliceMuncher::read_pref_bytes`] for more info.
+    pub fn read_pref_utf8<E: Primitive>(
+        &mut self,
+        end: impl Into<Option<End>>,
+    ) -> Result<&'a str, Error> {
+        self.read_pref_with::<E, _, _>(end, bytes2str)
+    }
+
+    /// Reads a C-style string (ending with `\0` null byte),
+    /// borrowed from the input.
+    ///
+    /// The null byte is consumed, but not included in the result.
+    pub fn read_cstr_bytes(&mut self) -> Result<&'a [u8], Error> {
+        self.read_cstr_with(Ok)
+    }
+
+    /// Reads a C-style string (ending with `\0` null byte)
+    /// in the UTF-8 format (tries to parse, fails if invalid),
+    /// borrowed from the input.
+    pub fn read_cstr_utf8(&mut self) -> Result<&'a str, Error> {
+        self.read_cstr_with(bytes2str)
+    }
+
+    /// Reads bytes until the specified delimiter is encountered,
+    /// borrowed from the input.
+    ///
+    /// The result includes the delimiter if found,
+    /// otherwise it's the rest of the input.
+    pub fn read_delim_bytes(&mut self, delim: u8) -> Result<&'a [u8], Error> {
+        let rest = self.remaining();
+        let len = rest
+            .iter()
+            .position(|&b| b == delim)
+            .map_or(rest.len(), |i| i + 1);
+        self.read_fixed_bytes(len)
+    }
+
+    /// Reads `len` bytes, converting them with `convert` before moving the position.
+    pub(crate) fn read_fixed_with<R, Err: From<Error>>(
+        &mut self,
+        len: usize,
+        convert: impl FnOnce(&'a [u8]) -> Result<R, Err>,
+    ) -> Result<R, Err> {
+        self.advance(|rest| {
+            let (bytes, tail) = rest
+                .split_at_checked(len)
+                .ok_or_else(|| eof(len, rest.len()))?;
+            let value = convert(bytes)?;
+            *rest = tail;
+            Ok(value)
+        })
+    }
+
+    /// Reads some bytes prefixed by a length (number of bytes) of type `<E>`,
+    /// converting them with `convert` before moving the position.
+    pub(crate) fn read_pref_with<E: Primitive, R, Err: From<Error>>(
+        &mut self,
+        end: impl Into<Option<End>>,
+        convert: impl FnOnce(&'a [u8]) -> Result<R, Err>,
+    ) -> Result<R, Err> {
+        let order = Resolved::new(end, self.order);
+        self.advance(|rest| {
+            let mut muncher = SliceMuncher::new(rest);
+            let len = muncher.read_order::<E>(order)?.into_usize();
+            let value = muncher.read_fixed_with(len, convert)?;
+            *rest = muncher.remaining();
+            Ok(value)
+        })
+    }
+
+    /// Reads a C-style string, converting it (without the null byte)
+    /// with `convert` before moving the position.
+    pub(crate) fn read_cstr_with<R, Err: From<Error>>(
+        &mut self,
+        convert: impl FnOnce(&'a [u8]) -> Result<R, Err>,
+    ) -> Result<R, Err> {
+        self.advance(|rest| {
+            let len = rest.iter().position(|&b| b == 0).ok_or_else(|| {
+                Error::new(
+                    ErrorKind::UnexpectedEof,
+                    "EOF reached before null terminator",
+                )
+            })?;
+            let value = convert(&rest[..len])?;
+            *rest = &rest[len + 1..];
+            Ok(value)
+        })
+    }
+
+    /// Reads a UCS-2 string prefixed by a length (number of characters) of type `<E>`,
+    /// and converts it to UTF-8.
+    ///
+    /// UCS-2 always needs converting,
+    /// so the result is only borrowed if it's empty.
+    ///
+    /// See [`crate::Muncher::read_pref_ucs2`] for more info.
+    #[cfg(feature = "ucs2")]
+    pub fn read_pref_ucs2<E: Primitive>(
+        &mut self,
+        end: impl Into<Option<End>>,
+    ) -> Result<Cow<'a, str>, Error> {
+        let order = Resolved::new(end, self.order);
+        self.advance(|rest| {
+            let mut muncher = SliceMuncher::new(rest);
+            let char_count = muncher.read_order::<E>(order)?.into_usize();
+            let s = muncher.read_fixed_ucs2(char_count)?;
+            *rest = muncher.remaining();
+            Ok(s)
+        })
+    }
+
+    /// Reads a UCS-2 string of `char_count` characters,
+    /// and converts it to UTF-8.
+    ///
+    /// See [`SliceMuncher::read_pref_ucs2`] for more info.
+    #[cfg(feature = "ucs2")]
+    pub fn read_fixed_ucs2(&mut self, char_count: usize) -> Result<Cow<'a, str>, Error> {
+        if char_count == 0 {
+            return Ok(Cow::Borrowed(""));
+        }
+        let decoder = crate::sans_io::Ucs2::new(char_count);
+        self.advance(|rest| {
+            // Fails before allocating anything for a forged length.
+            match crate::sans_io::decode_slice(rest, decoder, |_| Ok(()))? {
+                crate::sans_io::Parsed::Done((s, used)) => {
+                    *rest = &rest[used..];
+                    Ok(Cow::Owned(s))
+                }
+                crate::sans_io::Parsed::Incomplete { needed } => {
+                    Err(eof(rest.len() + needed, rest.len()))
+                }
+            }
+        })
+    }
+}
+
+fn eof(wanted: usize, left: usize) -> Error {
+    Error::new(
+        ErrorKind::UnexpectedEof,
+        format!("reached end of input: wanted {wanted} bytes, only {left} left"),
+    )
+}
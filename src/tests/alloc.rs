@@ -0,0 +1,78 @@
+use crate::{End, Muncher};
+use std::io::{Cursor, ErrorKind, Read};
+
+/// Reader with a few bytes of data, recording
+/// the largest buffer it has been asked to fill.
+struct Probe {
+    data: Cursor<Vec<u8>>,
+    largest_request: usize,
+}
+
+impl Probe {
+    fn new(data: &[u8]) -> Self {
+        Self {
+            data: Cursor::new(data.to_vec()),
+            largest_request: 0,
+        }
+    }
+}
+
+impl Read for Probe {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.largest_request = self.largest_request.max(buf.len());
+        self.data.read(buf)
+    }
+}
+
+#[test]
+fn test_forged_prefix_allocates_incrementally() {
+    // Claims 900 MB, but only has 10 bytes
+    let mut data = 900_000_000u32.to_be_bytes().to_vec();
+    data.extend_from_slice(b"0123456789");
+    let mut reader = Muncher::new(Probe::new(&data));
+
+    let err = reader.read_pref_bytes::<u32>(End::Big).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+    assert!(reader.inner.largest_request <= 64 * 1024);
+}
+
+#[test]
+fn test_forged_array_allocates_incrementally() {
+    let mut reader = Muncher::new(Probe::new(&[0; 10]));
+
+    let err = reader
+        .read_array_m::<u64>(100_000_000, End::Big)
+        .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+    assert!(reader.inner.largest_request <= 64 * 1024);
+}
+
+#[test]
+fn test_incremental_large_read() {
+    let data: Vec<u8> = (0..200_000u32).map(|i| i as u8).collect();
+    let mut reader = Muncher::new(Cursor::new(&data));
+
+    let result = reader.read_fixed_bytes(data.len()).unwrap();
+    assert_eq!(result, data);
+}
+
+#[test]
+fn test_eager_alloc() {
+    let data: Vec<u8> = (0..200_000u32).map(|i| i as u8).collect();
+    let mut reader = Muncher::new(Probe::new(&data));
+    reader.set_eager_alloc(true);
+
+    let result = reader.read_fixed_bytes(data.len()).unwrap();
+    assert_eq!(result, data);
+    assert_eq!(reader.inner.largest_request, data.len());
+}
+
+#[cfg(feature = "ucs2")]
+#[test]
+fn test_forged_ucs2_allocates_incrementally() {
+    let mut reader = Muncher::new(Probe::new(&[0, b'a', 0, b'b']));
+
+    let err = reader.read_fixed_ucs2(300_000_000).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+    assert!(reader.inner.largest_request <= 64 * 1024);
+}
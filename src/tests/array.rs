@@ -0,0 +1,149 @@
+use crate::{End, Muncher};
+use std::io::{Cursor, ErrorKind};
+
+#[test]
+fn test_read_array_u16() {
+    let data = [0x00, 0x01, 0x00, 0x02, 0x01, 0x00];
+    let mut reader = Muncher::new(Cursor::new(data));
+
+    let result = reader.read_array_m::<u16>(3, End::Big).unwrap();
+    assert_eq!(result, [1, 2, 0x100]);
+
+    let mut reader = Muncher::new(Cursor::new(data));
+    let result = reader.read_array_m::<u16>(3, End::Little).unwrap();
+    assert_eq!(result, [0x100, 0x200, 1]);
+}
+
+#[test]
+fn test_read_array_matches_single_reads() {
+    let data: Vec<u8> = (0..=255).collect();
+
+    for end in [End::Little, End::Big, End::Native] {
+        let mut bulk = Muncher::new(Cursor::new(&data));
+        let mut single = Muncher::new(Cursor::new(&data));
+
+        let values = bulk.read_array_m::<i32>(64, end).unwrap();
+        for value in values {
+            assert_eq!(value, single.read_m::<i32>(end).unwrap());
+        }
+    }
+}
+
+#[test]
+fn test_read_array_f32() {
+    let mut buffer = Vec::new();
+    let mut writer = Muncher::new(&mut buffer);
+    for value in [1.5f32, -2.25, 1e10] {
+        writer.write_be(value).unwrap();
+    }
+
+    let mut reader = Muncher::new(Cursor::new(buffer));
+    let result = reader.read_array_m::<f32>(3, End::Big).unwrap();
+    assert_eq!(result, [1.5, -2.25, 1e10]);
+}
+
+#[test]
+fn test_read_array_u8() {
+    let data = [1, 2, 3];
+    let mut reader = Muncher::new(Cursor::new(data));
+
+    let result = reader.read_array_m::<u8>(3, End::Big).unwrap();
+    assert_eq!(result, [1, 2, 3]);
+}
+
+#[test]
+fn test_read_array_empty() {
+    let mut reader = Muncher::new(Cursor::new([]));
+
+    let result = reader.read_array_m::<u64>(0, End::Big).unwrap();
+    assert!(result.is_empty());
+}
+
+#[test]
+fn test_read_array_insufficient() {
+    let data = [0, 1, 0];
+    let mut reader = Muncher::new(Cursor::new(data));
+
+    let err = reader.read_array_m::<u16>(2, End::Big).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+}
+
+#[test]
+fn test_read_array_alloc_limit() {
+    let data = [0; 16];
+    let mut reader = Muncher::new(Cursor::new(data));
+    reader.set_max_alloc(8);
+
+    let err = reader.read_array_m::<u32>(3, End::Big).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+    assert_eq!(reader.read_array_m::<u32>(2, End::Big).unwrap(), [0, 0]);
+
+    let err = reader
+        .read_array_m::<u64>(usize::MAX, End::Big)
+        .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+}
+
+#[test]
+fn test_read_into() {
+    let data = [0x12, 0x34, 0x56, 0x78];
+    let mut reader = Muncher::new(Cursor::new(data));
+
+    let mut out = [0u16; 2];
+    reader.read_into_m(&mut out, End::Little).unwrap();
+    assert_eq!(out, [0x3412, 0x7856]);
+}
+
+#[test]
+fn test_write_slice() {
+    let values = [0x1234u16, 0x5678];
+
+    let mut buffer = Vec::new();
+    Muncher::new(&mut buffer)
+        .write_slice_m(&values, End::Big)
+        .unwrap();
+    assert_eq!(buffer, [0x12, 0x34, 0x56, 0x78]);
+
+    let mut buffer = Vec::new();
+    Muncher::new(&mut buffer)
+        .write_slice_m(&values, End::Little)
+        .unwrap();
+    assert_eq!(buffer, [0x34, 0x12, 0x78, 0x56]);
+}
+
+#[test]
+fn test_write_slice_round_trip_large() {
+    // More than one write chunk
+    let values: Vec<f64> = (0..5000).map(|i| f64::from(i) * 0.5).collect();
+
+    for end in [End::Little, End::Big] {
+        let mut buffer = Vec::new();
+        Muncher::new(&mut buffer)
+            .write_slice_m(&values, end)
+            .unwrap();
+        assert_eq!(buffer.len(), values.len() * 8);
+
+        let mut reader = Muncher::new(Cursor::new(buffer));
+        assert_eq!(reader.read_array_m::<f64>(5000, end).unwrap(), values);
+    }
+}
+
+#[test]
+fn test_write_slice_matches_single_writes() {
+    // More than one write chunk, in both the swapped and the plain path
+    let values: Vec<i32> = (0..3000)
+        .map(|i: i32| i.wrapping_mul(0x0101_0101))
+        .collect();
+
+    for end in [End::Little, End::Big, End::Native] {
+        let mut bulk = Vec::new();
+        Muncher::new(&mut bulk).write_slice_m(&values, end).unwrap();
+
+        let mut single = Vec::new();
+        let mut writer = Muncher::new(&mut single);
+        for &value in &values {
+            writer.write_m(value, end).unwrap();
+        }
+        assert_eq!(bulk, single);
+    }
+}
@@ -0,0 +1,128 @@
+#[cfg(feature = "futures")]
+mod futures_backend {
+    use crate::{AsyncMunchBufRead, AsyncMunchRead, AsyncMunchWrite, End, Muncher};
+    use futures::executor::block_on;
+    use futures::io::Cursor;
+    use futures_util::{StreamExt, TryStreamExt};
+
+    #[test]
+    fn test_read_write() {
+        block_on(async {
+            let mut writer = Muncher::new(Cursor::new(Vec::new()));
+            writer.write_be_a(0x1234u16).await.unwrap();
+            writer
+                .write_pref_bytes_a::<u8>(End::Big, b"hi")
+                .await
+                .unwrap();
+            writer.write_cstr_bytes_a(b"abc").await.unwrap();
+
+            let bytes = writer.inner.into_inner();
+            assert_eq!(bytes, b"\x12\x34\x02hiabc\0");
+
+            let mut reader = Muncher::new(Cursor::new(bytes));
+            assert_eq!(reader.read_be_a::<u16>().await.unwrap(), 0x1234);
+            assert_eq!(reader.read_pref_utf8_a::<u8>(End::Big).await.unwrap(), "hi");
+            assert_eq!(reader.read_cstr_utf8_a().await.unwrap(), "abc");
+            assert!(reader.is_eof_a().await.unwrap());
+        });
+    }
+
+    #[test]
+    fn test_records() {
+        block_on(async {
+            let mut reader = Muncher::new(Cursor::new(b"\x02hi\x03abc"));
+            let records: Vec<String> = reader
+                .records_a(async |m| m.read_pref_utf8_a::<u8>(End::Big).await)
+                .try_collect()
+                .await
+                .unwrap();
+            assert_eq!(records, ["hi", "abc"]);
+        });
+    }
+
+    #[test]
+    fn test_records_truncated() {
+        block_on(async {
+            let mut reader = Muncher::new(Cursor::new(b"\x02hi\x01a\x03ab"));
+            let records: Vec<_> = reader
+                .records_a(async |m| {
+                    m.reset_budget_usage();
+                    m.read_pref_utf8_a::<u8>(End::Big).await
+                })
+                .collect()
+                .await;
+            assert_eq!(records.len(), 3);
+            assert_eq!(records[0].as_ref().unwrap(), "hi");
+            assert_eq!(records[1].as_ref().unwrap(), "a");
+            let err = records[2].as_ref().unwrap_err();
+            assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof);
+            assert!(err.to_string().contains("byte 5"), "{err}");
+        });
+    }
+}
+
+#[cfg(feature = "tokio")]
+mod tokio_backend {
+    use crate::{AsyncMunchBufRead, AsyncMunchRead, AsyncMunchWrite, End, Muncher};
+    use std::io::{Cursor, ErrorKind};
+
+    fn assert_send<T: Send>(_: &T) {}
+
+    #[tokio::test]
+    async fn test_read_write() {
+        let mut writer = Muncher::new(Cursor::new(Vec::new()));
+        writer.write_le_a(0x1234u16).await.unwrap();
+        writer
+            .write_pref_slice_m_a::<u8, u16>(End::Big, &[1, 2])
+            .await
+            .unwrap();
+
+        let bytes = writer.inner.into_inner();
+        assert_eq!(bytes, b"\x34\x12\x02\x00\x01\x00\x02");
+
+        let mut reader = Muncher::new(Cursor::new(bytes));
+        assert_eq!(reader.read_le_a::<u16>().await.unwrap(), 0x1234);
+        assert_eq!(
+            reader
+                .read_pref_array_m_a::<u8, u16>(End::Big)
+                .await
+                .unwrap(),
+            [1, 2]
+        );
+        assert_eq!(reader.try_read_le_a::<u8>().await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_truncated() {
+        let mut reader = Muncher::new(Cursor::new([0x12]));
+        let err = reader.read_be_a::<u16>().await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn test_futures_are_send() {
+        let mut reader = Muncher::new(Cursor::new([0x12, 0x34]));
+        assert_send(&reader.read_be_a::<u16>());
+        assert_send(&reader.read_cstr_bytes_a());
+        let mut writer = Muncher::new(Cursor::new(Vec::new()));
+        assert_send(&writer.write_pref_bytes_a::<u8>(End::Big, b"hi"));
+    }
+}
+
+/// Readers like `&[u8]` implement both backends' traits,
+/// so the backend has to be picked explicitly.
+#[cfg(all(feature = "tokio", feature = "futures"))]
+#[test]
+fn test_both_backends() {
+    use crate::backend::{Futures, Tokio};
+    use crate::{AsyncMunchRead, Muncher};
+
+    let data: &[u8] = &[0x12, 0x34, 0x56, 0x78];
+    let mut reader = Muncher::new(data);
+    futures::executor::block_on(async {
+        let a = AsyncMunchRead::<Futures>::read_be_a::<u16>(&mut reader).await;
+        let b = AsyncMunchRead::<Tokio>::read_be_a::<u16>(&mut reader).await;
+        assert_eq!(a.unwrap(), 0x1234);
+        assert_eq!(b.unwrap(), 0x5678);
+    });
+}
@@ -0,0 +1,173 @@
+use crate::{End, Muncher};
+use std::io::{BufReader, Cursor, ErrorKind};
+
+#[test]
+fn test_alloc_budget() {
+    let mut data = Vec::new();
+    for _ in 0..10 {
+        data.push(4u8);
+        data.extend_from_slice(b"abcd");
+    }
+    let mut reader = Muncher::new(Cursor::new(data));
+    reader.set_alloc_budget(Some(10));
+
+    assert_eq!(reader.read_pref_utf8::<u8>(End::Big).unwrap(), "abcd");
+    assert_eq!(reader.read_pref_utf8::<u8>(End::Big).unwrap(), "abcd");
+    assert_eq!(reader.allocated_bytes(), 8);
+
+    let err = reader.read_pref_utf8::<u8>(End::Big).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::QuotaExceeded);
+}
+
+#[test]
+fn test_alloc_budget_distinct_from_max_alloc() {
+    let data = [4u8, 1, 2, 3, 4];
+
+    let mut reader = Muncher::new(Cursor::new(data));
+    reader.set_max_alloc(2);
+    let err = reader.read_pref_bytes::<u8>(End::Big).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+
+    let mut reader = Muncher::new(Cursor::new(data));
+    reader.set_alloc_budget(Some(2));
+    let err = reader.read_pref_bytes::<u8>(End::Big).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::QuotaExceeded);
+}
+
+#[test]
+fn test_alloc_budget_cstr() {
+    let data = b"hello\0world\0";
+    let mut reader = Muncher::new(BufReader::new(Cursor::new(data)));
+    reader.set_alloc_budget(Some(8));
+
+    assert_eq!(reader.read_cstr_utf8().unwrap(), "hello");
+    let err = reader.read_cstr_utf8().unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::QuotaExceeded);
+}
+
+#[test]
+fn test_read_budget() {
+    let data = [1, 2, 3, 4, 5, 6];
+    let mut reader = Muncher::new(Cursor::new(data));
+    reader.set_read_budget(Some(5));
+
+    assert_eq!(reader.read_be::<u32>().unwrap(), 0x01020304);
+    assert_eq!(reader.consumed_bytes(), 4);
+
+    let err = reader.read_be::<u16>().unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::QuotaExceeded);
+}
+
+#[test]
+fn test_read_budget_bufread() {
+    let data = b"abcdefgh";
+    let mut reader = Muncher::new(BufReader::new(Cursor::new(data)));
+    reader.set_read_budget(Some(4));
+
+    // Stops at the budget instead of reading everything
+    let err = reader.read_delim_bytes(b'\n').unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::QuotaExceeded);
+    assert_eq!(reader.consumed_bytes(), 4);
+}
+
+#[test]
+fn test_reset_budget_usage() {
+    let data = [0u8; 8];
+    let mut reader = Muncher::new(Cursor::new(data));
+    reader.set_read_budget(Some(4)).set_alloc_budget(Some(4));
+
+    reader.read_fixed_bytes(4).unwrap();
+    assert_eq!(reader.consumed_bytes(), 4);
+    assert_eq!(reader.allocated_bytes(), 4);
+
+    reader.reset_budget_usage();
+    assert_eq!(reader.consumed_bytes(), 0);
+    assert_eq!(reader.allocated_bytes(), 0);
+    reader.read_fixed_bytes(4).unwrap();
+}
+
+#[test]
+fn test_with_budget() {
+    let data = b"\x02ab\x04abcd\x04abcd";
+    let mut reader = Muncher::new(Cursor::new(data));
+
+    let record = |m: &mut Muncher<_>| m.read_pref_bytes::<u8>(End::Big);
+
+    assert!(reader.with_budget(Some(3), None, record).is_ok());
+    assert!(reader.with_budget(Some(4), None, record).is_ok());
+    let err = reader.with_budget(Some(3), None, record).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::QuotaExceeded);
+
+    // Counted in the outer totals
+    assert_eq!(reader.allocated_bytes(), 6);
+    assert_eq!(reader.consumed_bytes(), 9);
+}
+
+#[test]
+fn test_with_budget_bounded_by_outer() {
+    let data = [0u8; 16];
+    let mut reader = Muncher::new(Cursor::new(data));
+    reader.set_read_budget(Some(6));
+    reader.read_be::<u32>().unwrap();
+
+    let result = reader.with_budget(None, Some(100), |m| m.read_be::<u32>());
+    assert_eq!(result.unwrap_err().kind(), ErrorKind::QuotaExceeded);
+}
+
+#[test]
+fn test_try_parse_rollback_uncounts() {
+    let data = [1, 2, 3, 4];
+    let mut reader = Muncher::new(BufReader::new(Cursor::new(data)));
+
+    assert!(reader.try_parse(|m| m.read_be::<u64>()).is_err());
+    assert_eq!(reader.consumed_bytes(), 0);
+    assert_eq!(reader.peek::<u16>(End::Big).unwrap(), 0x0102);
+    assert_eq!(reader.consumed_bytes(), 0);
+    reader.read_be::<u16>().unwrap();
+    assert_eq!(reader.consumed_bytes(), 2);
+}
+
+#[test]
+fn test_unterminated_cstr_stops_at_limits() {
+    // Endless input without a null terminator
+    let mut reader = Muncher::new(BufReader::new(std::io::repeat(b'a')));
+    reader.set_alloc_budget(Some(100_000));
+    let err = reader.read_cstr_bytes().unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::QuotaExceeded);
+
+    let mut reader = Muncher::new(BufReader::new(std::io::repeat(b'a')));
+    reader.set_max_alloc(100_000);
+    let err = reader.read_delim_bytes(b'\n').unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+async fn test_unterminated_cstr_stops_at_limits_a() {
+    use crate::AsyncMunchBufRead;
+
+    let mut reader = Muncher::new(tokio::io::BufReader::new(tokio::io::repeat(b'a')));
+    reader.set_alloc_budget(Some(100_000));
+    let err = reader.read_cstr_bytes_a().await.unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::QuotaExceeded);
+
+    let mut reader = Muncher::new(tokio::io::BufReader::new(tokio::io::repeat(b'a')));
+    reader.set_max_alloc(100_000);
+    let err = reader.read_delim_bytes_a(b'\n').await.unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+}
+
+#[cfg(feature = "ucs2")]
+#[test]
+fn test_alloc_budget_ucs2_worst_case() {
+    // "€€" is 2 UCS-2 characters, but 6 bytes of UTF-8
+    let data = b"\x20\xac\x20\xac";
+    let mut reader = Muncher::new(Cursor::new(data));
+    reader.set_alloc_budget(Some(4));
+    let err = reader.read_fixed_ucs2(2).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::QuotaExceeded);
+
+    let mut reader = Muncher::new(Cursor::new(data));
+    reader.set_alloc_budget(Some(6));
+    assert_eq!(reader.read_fixed_ucs2(2).unwrap(), "€€");
+}
@@ -0,0 +1,115 @@
+use crate::{AsyncMunchBufRead, AsyncMunchRead, End, Muncher};
+use futures::FutureExt;
+use std::io::ErrorKind;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// A reader that returns one byte at a time,
+/// and is pending before each of them.
+struct Trickle {
+    data: &'static [u8],
+    ready: bool,
+}
+
+impl Trickle {
+    fn new(data: &'static [u8]) -> Muncher<tokio::io::BufReader<Self>> {
+        Muncher::new(tokio::io::BufReader::with_capacity(
+            1,
+            Self { data, ready: false },
+        ))
+    }
+}
+
+impl tokio::io::AsyncRead for Trickle {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut tokio::io::ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        if !self.ready {
+            self.ready = true;
+            cx.waker().wake_by_ref();
+            return Poll::Pending;
+        }
+        self.ready = false;
+        if let Some((&byte, rest)) = self.data.split_first() {
+            buf.put_slice(&[byte]);
+            self.data = rest;
+        }
+        Poll::Ready(Ok(()))
+    }
+}
+
+/// Polls `read` once at a time, dropping (cancelling) it
+/// whenever it's pending, until it completes.
+/// Returns the output and how many times it was cancelled.
+fn cancel_until_done<M, R>(muncher: &mut M, mut read: impl AsyncFnMut(&mut M) -> R) -> (R, usize) {
+    let mut cancelled = 0;
+    loop {
+        if let Some(output) = read(muncher).now_or_never() {
+            return (output, cancelled);
+        }
+        cancelled += 1;
+    }
+}
+
+#[test]
+fn test_cancel_pref_utf8() {
+    let mut muncher = Trickle::new(b"\x05hello\x34\x12");
+
+    let (s, cancelled) =
+        cancel_until_done(&mut muncher, async |m| m.read_pref_utf8_a::<u8>(None).await);
+    assert_eq!(s.unwrap(), "hello");
+    assert!(cancelled >= 6);
+
+    let (n, _) = cancel_until_done(&mut muncher, async |m| m.read_le_a::<u16>().await);
+    assert_eq!(n.unwrap(), 0x1234);
+    assert_eq!(muncher.consumed_bytes(), 8);
+}
+
+#[test]
+fn test_cancel_then_other_read() {
+    let mut muncher = Trickle::new(b"\x00\x04abcd");
+
+    // Cancelled after getting some of the bytes...
+    for _ in 0..4 {
+        let read = muncher.read_pref_bytes_a::<u16>(End::Big).now_or_never();
+        assert!(read.is_none());
+    }
+    // ...which are still there for the next read
+    let (bytes, _) = cancel_until_done(&mut muncher, async |m| m.read_fixed_bytes_a(6).await);
+    assert_eq!(bytes.unwrap(), b"\x00\x04abcd");
+}
+
+#[test]
+fn test_cancel_cstr() {
+    let mut muncher = Trickle::new(b"abc\0de\0");
+
+    let (s, cancelled) = cancel_until_done(&mut muncher, async |m| m.read_cstr_utf8_a().await);
+    assert_eq!(s.unwrap(), "abc");
+    assert!(cancelled >= 4);
+
+    let (s, _) = cancel_until_done(&mut muncher, async |m| m.read_delim_bytes_a(0).await);
+    assert_eq!(s.unwrap(), b"de\0");
+    let (eof, _) = cancel_until_done(&mut muncher, async |m| m.is_eof_a().await);
+    assert!(eof.unwrap());
+}
+
+#[test]
+fn test_cancel_array() {
+    let mut muncher = Trickle::new(b"\x03\x00\x01\x00\x02\x00\x03");
+
+    let (values, cancelled) = cancel_until_done(&mut muncher, async |m| {
+        m.read_pref_array_m_a::<u8, u16>(End::Big).await
+    });
+    assert_eq!(values.unwrap(), [1, 2, 3]);
+    assert!(cancelled >= 7);
+}
+
+#[test]
+fn test_cancel_eof_mid_value() {
+    let mut muncher = Trickle::new(b"\x05hel");
+
+    let (s, _) = cancel_until_done(&mut muncher, async |m| m.read_pref_utf8_a::<u8>(None).await);
+    assert_eq!(s.unwrap_err().kind(), ErrorKind::UnexpectedEof);
+}
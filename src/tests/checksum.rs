@@ -0,0 +1,230 @@
+use crate::checksum::{Adler32, Checksum, Crc16, Crc32};
+use crate::{End, Muncher};
+use std::io::{BufRead, BufReader, Cursor, Read};
+
+const CHECK: &[u8] = b"123456789";
+
+fn checksum<C: Checksum>(mut checksum: C, bytes: &[u8]) -> C::Output {
+    checksum.update(bytes);
+    checksum.finish()
+}
+
+#[test]
+fn test_check_values() {
+    assert_eq!(checksum(Crc32::new(), CHECK), 0xCBF4_3926);
+    assert_eq!(checksum(Crc32::castagnoli(), CHECK), 0xE306_9283);
+    assert_eq!(checksum(Crc16::arc(), CHECK), 0xBB3D);
+    assert_eq!(checksum(Crc16::modbus(), CHECK), 0x4B37);
+    assert_eq!(checksum(Crc16::ccitt_false(), CHECK), 0x29B1);
+    assert_eq!(checksum(Crc16::xmodem(), CHECK), 0x31C3);
+    assert_eq!(checksum(Crc16::kermit(), CHECK), 0x2189);
+    assert_eq!(checksum(Crc16::x25(), CHECK), 0x906E);
+    assert_eq!(checksum(Adler32::new(), CHECK), 0x091E_01DE);
+
+    assert_eq!(checksum(Crc32::new(), b""), 0);
+    assert_eq!(checksum(Adler32::new(), b""), 1);
+}
+
+#[test]
+fn test_adler32_large() {
+    // Enough to need the modulo in the middle
+    let data = vec![0xFF; 100_000];
+    let (mut a, mut b) = (1u64, 0u64);
+    for &byte in &data {
+        a = (a + u64::from(byte)) % 65521;
+        b = (b + a) % 65521;
+    }
+
+    let mut adler = Adler32::new();
+    for part in data.chunks(7777) {
+        adler.update(part);
+    }
+    assert_eq!(u64::from(adler.finish()), (b << 16) | a);
+}
+
+#[test]
+fn test_checksum_png_chunk() {
+    let data = b"\0\0\0\x0DIHDR\0\0\0\x01\0\0\0\x01\x08\x06\0\0\0\x1f\x15\xc4\x89";
+    let mut muncher = Muncher::new(Cursor::new(data));
+    muncher.set_end(End::Big);
+
+    let len = muncher.read::<u32>().unwrap();
+    muncher.begin_checksum(Crc32::new());
+    assert_eq!(muncher.read_fixed_bytes(4).unwrap(), b"IHDR");
+    assert_eq!(muncher.read::<u32>().unwrap(), 1);
+    assert_eq!(muncher.read::<u32>().unwrap(), 1);
+    let mut rest = vec![0; len as usize - 8];
+    muncher.read_exact(&mut rest).unwrap();
+    let crc = muncher.finish_checksum::<Crc32>().unwrap();
+
+    assert_eq!(crc, 0x1F15_C489);
+    assert_eq!(muncher.read::<u32>().unwrap(), crc);
+    // Stopped after finishing
+    assert_eq!(muncher.finish_checksum::<Crc32>(), None);
+}
+
+#[test]
+fn test_checksum_buffered_reads() {
+    let data = b"skip\x05hello\x02\x00abc\0line\n";
+    let mut muncher = Muncher::new(BufReader::with_capacity(3, Cursor::new(data)));
+
+    muncher.read_fixed_bytes(4).unwrap();
+    muncher.begin_checksum(Crc32::castagnoli());
+    assert_eq!(muncher.peek_bytes(6).unwrap(), b"\x05hello");
+    assert_eq!(muncher.read_pref_utf8::<u8>(None).unwrap(), "hello");
+    assert_eq!(muncher.read_le::<u16>().unwrap(), 2);
+    assert_eq!(muncher.read_cstr_utf8().unwrap(), "abc");
+    assert_eq!(muncher.read_delim_bytes(b'\n').unwrap(), b"line\n");
+
+    let crc = muncher.finish_checksum::<Crc32>().unwrap();
+    assert_eq!(crc, checksum(Crc32::castagnoli(), &data[4..]));
+}
+
+/// A [`BufRead`] that fails if its buffer is asked for again
+/// before it's consumed.
+struct FillOnce<'a> {
+    data: &'a [u8],
+    filled: bool,
+}
+
+impl Read for FillOnce<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.data.read(buf)
+    }
+}
+
+impl BufRead for FillOnce<'_> {
+    fn fill_buf(&mut self) -> std::io::Result<&[u8]> {
+        if self.filled {
+            return Err(std::io::Error::other("filled twice"));
+        }
+        self.filled = true;
+        Ok(&self.data[..self.data.len().min(4)])
+    }
+
+    fn consume(&mut self, amount: usize) {
+        self.data = &self.data[amount..];
+        self.filled = false;
+    }
+}
+
+#[test]
+fn test_checksum_buf_read_consume() {
+    let data = b"hello world\n";
+    let mut muncher = Muncher::new(FillOnce {
+        data,
+        filled: false,
+    });
+    muncher.begin_checksum(Crc32::new());
+
+    let mut line = Vec::new();
+    muncher.read_until(b'\n', &mut line).unwrap();
+    assert_eq!(line, data);
+
+    let crc = muncher.finish_checksum::<Crc32>().unwrap();
+    assert_eq!(crc, checksum(Crc32::new(), data));
+}
+
+#[test]
+fn test_checksum_try_parse_rollback() {
+    let data = b"\x05hello";
+    let mut muncher = Muncher::new(BufReader::new(Cursor::new(data)));
+    muncher.begin_checksum(Adler32::new());
+
+    assert!(
+        muncher
+            .try_parse(|m| m.read_pref_utf8::<u32>(End::Big))
+            .is_err()
+    );
+    assert_eq!(muncher.read::<u8>().unwrap(), 5);
+    assert_eq!(muncher.read_fixed_utf8(5).unwrap(), "hello");
+
+    let adler = muncher.finish_checksum::<Adler32>().unwrap();
+    assert_eq!(adler, checksum(Adler32::new(), data));
+}
+
+#[test]
+fn test_checksum_write() {
+    let mut muncher = Muncher::new(Vec::new());
+    muncher.write_be(0x1234u16).unwrap();
+    muncher.begin_checksum(Crc16::xmodem());
+    muncher.write_be(0xABCDu16).unwrap();
+    muncher.write_slice_m(&[1u32, 2], End::Little).unwrap();
+    muncher.write_pref_bytes::<u8>(None, b"hi").unwrap();
+    muncher.write_cstr_bytes(b"abc").unwrap();
+    let crc = muncher.finish_checksum::<Crc16>().unwrap();
+    muncher.write_be(crc).unwrap();
+
+    let bytes = muncher.inner;
+    let body = &bytes[2..bytes.len() - 2];
+    assert_eq!(crc, checksum(Crc16::xmodem(), body));
+    assert_eq!(&bytes[bytes.len() - 2..], crc.to_be_bytes());
+}
+
+#[test]
+fn test_checksum_wrong_type() {
+    let mut muncher = Muncher::new(Cursor::new(CHECK));
+    muncher.begin_checksum(Crc16::arc());
+    muncher.read_fixed_bytes(4).unwrap();
+
+    // Not a `Crc32`, so it keeps going
+    assert_eq!(muncher.finish_checksum::<Crc32>(), None);
+    muncher.read_fixed_bytes(5).unwrap();
+    assert_eq!(muncher.finish_checksum::<Crc16>(), Some(0xBB3D));
+}
+
+/// A custom checksum: the XOR of all the bytes.
+#[derive(Clone, Default)]
+struct Xor(u8);
+
+impl Checksum for Xor {
+    type Output = u8;
+
+    fn update(&mut self, bytes: &[u8]) {
+        self.0 = bytes.iter().fold(self.0, |acc, b| acc ^ b);
+    }
+
+    fn finish(&self) -> u8 {
+        self.0
+    }
+}
+
+#[test]
+fn test_checksum_custom() {
+    let mut muncher = Muncher::new(Cursor::new(b"\x01\x02\x04\x07"));
+    muncher.begin_checksum(Xor::default());
+    muncher.read_fixed_bytes(3).unwrap();
+    let xor = muncher.finish_checksum::<Xor>().unwrap();
+
+    assert_eq!(muncher.read::<u8>().unwrap(), xor);
+}
+
+#[cfg(feature = "futures")]
+#[test]
+fn test_checksum_async() {
+    use crate::{AsyncMunchBufRead, AsyncMunchRead, AsyncMunchWrite};
+
+    futures::executor::block_on(async {
+        let mut writer = Muncher::new(futures::io::Cursor::new(Vec::new()));
+        writer.begin_checksum(Crc32::new());
+        writer.write_be_a(0x1234u16).await.unwrap();
+        writer
+            .write_pref_bytes_a::<u8>(End::Big, b"hi")
+            .await
+            .unwrap();
+        writer.write_cstr_bytes_a(b"abc").await.unwrap();
+        let crc = writer.finish_checksum::<Crc32>().unwrap();
+        writer.write_be_a(crc).await.unwrap();
+
+        let bytes = writer.inner.into_inner();
+        let mut reader = Muncher::new(futures::io::Cursor::new(bytes));
+        reader.begin_checksum(Crc32::new());
+        assert_eq!(reader.read_be_a::<u16>().await.unwrap(), 0x1234);
+        assert_eq!(reader.read_pref_utf8_a::<u8>(End::Big).await.unwrap(), "hi");
+        assert_eq!(reader.read_cstr_utf8_a().await.unwrap(), "abc");
+        let actual = reader.finish_checksum::<Crc32>().unwrap();
+
+        assert_eq!(actual, crc);
+        assert_eq!(reader.read_be_a::<u32>().await.unwrap(), crc);
+    });
+}
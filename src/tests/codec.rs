@@ -0,0 +1,101 @@
+use crate::codec::PrefixCodec;
+use crate::tests::sans_io::Varint;
+use crate::{BigEndian, End};
+use bytes::BytesMut;
+use std::io::ErrorKind;
+use tokio_util::codec::{Decoder, Encoder};
+
+#[test]
+fn test_codec_partial_frames() {
+    let mut codec = PrefixCodec::<u32>::new();
+    let mut buf = BytesMut::new();
+
+    buf.extend_from_slice(&[5, 0]);
+    assert_eq!(codec.decode(&mut buf).unwrap(), None);
+    buf.extend_from_slice(&[0, 0, b'h', b'e']);
+    assert_eq!(codec.decode(&mut buf).unwrap(), None);
+    assert!(buf.capacity() >= 9);
+    buf.extend_from_slice(b"llo\x01\x00\x00\x00!");
+
+    assert_eq!(&codec.decode(&mut buf).unwrap().unwrap()[..], b"hello");
+    assert_eq!(&codec.decode(&mut buf).unwrap().unwrap()[..], b"!");
+    assert_eq!(codec.decode(&mut buf).unwrap(), None);
+    assert!(buf.is_empty());
+}
+
+#[test]
+fn test_codec_roundtrip() {
+    let mut codec = PrefixCodec::<u16, _>::with_order(BigEndian);
+    let mut buf = BytesMut::new();
+    codec.encode(b"abc", &mut buf).unwrap();
+    codec.encode(Vec::new(), &mut buf).unwrap();
+    assert_eq!(&buf[..], b"\x00\x03abc\x00\x00");
+
+    assert_eq!(&codec.decode(&mut buf).unwrap().unwrap()[..], b"abc");
+    assert!(codec.decode(&mut buf).unwrap().unwrap().is_empty());
+}
+
+#[test]
+fn test_codec_varint() {
+    let mut codec = PrefixCodec::<Varint>::new();
+    let body = vec![7u8; 300];
+    let mut buf = BytesMut::new();
+    codec.encode(&body, &mut buf).unwrap();
+    assert_eq!(&buf[..2], [0xac, 0x02]);
+
+    let mut partial = buf.split_to(1);
+    assert_eq!(codec.decode(&mut partial).unwrap(), None);
+    partial.unsplit(buf);
+    assert_eq!(&codec.decode(&mut partial).unwrap().unwrap()[..], body);
+}
+
+#[test]
+fn test_codec_max_frame() {
+    let mut codec = PrefixCodec::<u32>::new();
+    codec.set_max_frame(4);
+
+    let mut buf = BytesMut::from(&[0xff, 0xff, 0xff, 0x7f][..]);
+    let err = codec.decode(&mut buf).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+    // Didn't try to reserve space for the forged length
+    assert!(buf.capacity() < 1024);
+
+    let err = codec.encode(b"hello", &mut buf).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+
+    // Too large for the prefix type
+    let mut codec = PrefixCodec::<u8>::new();
+    let err = codec.encode(vec![0; 256], &mut buf).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+}
+
+#[test]
+fn test_codec_decode_body() {
+    let mut codec = PrefixCodec::<u8>::new().decode_body(|body| {
+        let id = body.read::<u16>()?;
+        let name = body.read_pref_utf8::<u8>(None)?;
+        Ok((id, name.to_owned()))
+    });
+    codec.codec_mut().set_end(End::Big);
+
+    let mut buf = BytesMut::new();
+    codec.encode(b"\x00\x01\x02hi", &mut buf).unwrap();
+    codec.encode(b"\x00\x02\x09", &mut buf).unwrap();
+
+    assert_eq!(codec.decode(&mut buf).unwrap(), Some((1, "hi".to_owned())));
+    let err = codec.decode(&mut buf).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+}
+
+#[tokio::test]
+async fn test_codec_framed_read() {
+    use futures::StreamExt;
+    use tokio_util::codec::FramedRead;
+
+    let data: &[u8] = b"\x02hi\x03abc";
+    let frames: Vec<_> = FramedRead::new(data, PrefixCodec::<u8>::new())
+        .map(|frame| frame.unwrap().freeze())
+        .collect()
+        .await;
+    assert_eq!(frames, [&b"hi"[..], &b"abc"[..]]);
+}
@@ -0,0 +1,134 @@
+use crate::{End, Muncher};
+use std::io::{Cursor, ErrorKind};
+
+#[test]
+fn test_read_pref_vec() {
+    let data = b"\x03\x00\x01a\x02bc\x00";
+    let mut reader = Muncher::new(Cursor::new(data));
+
+    let result = reader
+        .read_pref_vec::<u16, _>(End::Little, |m| m.read_pref_utf8::<u8>(End::Little))
+        .unwrap();
+    assert_eq!(result, ["a", "bc", ""]);
+}
+
+#[test]
+fn test_read_pref_vec_empty() {
+    let data = [0u8];
+    let mut reader = Muncher::new(Cursor::new(data));
+
+    let result = reader
+        .read_pref_vec::<u8, u32>(End::Little, |_| unreachable!())
+        .unwrap();
+    assert!(result.is_empty());
+}
+
+#[test]
+fn test_read_pref_vec_alloc_limit() {
+    let data = [0xff, 0xff, 0xff, 0xff];
+    let mut reader = Muncher::new(Cursor::new(data));
+    reader.set_max_alloc(1024);
+
+    let err = reader
+        .read_pref_vec::<u32, u64>(End::Little, |m| m.read_le())
+        .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+}
+
+#[test]
+fn test_read_pref_vec_truncated() {
+    let data = [3, 1, 2];
+    let mut reader = Muncher::new(Cursor::new(data));
+
+    let err = reader
+        .read_pref_vec::<u8, u8>(End::Little, |m| m.read_le())
+        .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+}
+
+#[test]
+fn test_read_pref_array() {
+    let data = [0, 2, 0x12, 0x34, 0x56, 0x78];
+    let mut reader = Muncher::new(Cursor::new(data));
+
+    let result = reader.read_pref_array_m::<u16, u16>(End::Big).unwrap();
+    assert_eq!(result, [0x1234, 0x5678]);
+}
+
+#[test]
+fn test_read_pref_array_default_end() {
+    let data = [2, 0, 0x34, 0x12, 0x78, 0x56];
+    let mut reader = Muncher::new(Cursor::new(data));
+
+    let result = reader.read_pref_array_m::<u16, u16>(None).unwrap();
+    assert_eq!(result, [0x1234, 0x5678]);
+}
+
+#[test]
+fn test_write_pref_iter_round_trip() {
+    let names = ["alpha", "beta", "gamma"];
+
+    let mut buffer = Vec::new();
+    let mut writer = Muncher::new(&mut buffer);
+    writer
+        .write_pref_iter::<u32, _>(End::Big, names, |m, name| {
+            m.write_pref_bytes::<u8>(End::Big, name.as_bytes())
+        })
+        .unwrap();
+
+    let mut reader = Muncher::new(Cursor::new(buffer));
+    let result = reader
+        .read_pref_vec::<u32, _>(End::Big, |m| m.read_pref_utf8::<u8>(End::Big))
+        .unwrap();
+    assert_eq!(result, names);
+}
+
+#[test]
+fn test_write_pref_slice_round_trip() {
+    let values = [1.5f32, -3.0, 0.25];
+
+    let mut buffer = Vec::new();
+    let mut writer = Muncher::new(&mut buffer);
+    writer
+        .write_pref_slice_m::<u8, f32>(End::Big, &values)
+        .unwrap();
+    assert_eq!(buffer.len(), 1 + 3 * 4);
+    assert_eq!(buffer[0], 3);
+
+    let mut reader = Muncher::new(Cursor::new(buffer));
+    let result = reader.read_pref_array_m::<u8, f32>(End::Big).unwrap();
+    assert_eq!(result, values);
+}
+
+#[test]
+fn test_write_pref_count_too_large() {
+    let mut buffer = Vec::new();
+    let mut writer = Muncher::new(&mut buffer);
+
+    let err = writer
+        .write_pref_slice_m::<u8, u8>(End::Big, &[0; 300])
+        .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+    let err = writer
+        .write_pref_iter::<i8, _>(End::Big, 0..200, |m, _| m.write_be(0u8))
+        .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+    let err = writer
+        .write_pref_bytes::<u8>(End::Big, &[0; 256])
+        .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+    // Nothing was written, not even the count
+    assert!(buffer.is_empty());
+}
+
+#[test]
+fn test_read_pref_vec_budget_counts_elements_once() {
+    let data = b"\x02\x02ab\x02cd";
+    let mut reader = Muncher::new(Cursor::new(data));
+
+    let result = reader
+        .read_pref_vec::<u8, _>(End::Big, |m| m.read_pref_bytes::<u8>(End::Big))
+        .unwrap();
+    assert_eq!(result, [b"ab", b"cd"]);
+    assert_eq!(reader.allocated_bytes(), 4);
+}
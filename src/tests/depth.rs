@@ -0,0 +1,78 @@
+use crate::Muncher;
+use std::io::{Cursor, ErrorKind, Read};
+
+/// A tree node is a child count followed by that many nodes.
+fn count_nodes<T: Read>(muncher: &mut Muncher<T>) -> std::io::Result<usize> {
+    let mut muncher = muncher.enter()?;
+    let children = muncher.read_le::<u8>()?;
+    let mut total = 1;
+    for _ in 0..children {
+        total += count_nodes(&mut muncher)?;
+    }
+    Ok(total)
+}
+
+#[test]
+fn test_enter_tracks_depth() {
+    let mut reader = Muncher::new(Cursor::new([0u8; 0]));
+    assert_eq!(reader.depth(), 0);
+    {
+        let mut level1 = reader.enter().unwrap();
+        assert_eq!(level1.depth(), 1);
+        {
+            let level2 = level1.enter().unwrap();
+            assert_eq!(level2.depth(), 2);
+        }
+        assert_eq!(level1.depth(), 1);
+    }
+    assert_eq!(reader.depth(), 0);
+}
+
+#[test]
+fn test_recursive_parse() {
+    let data = [3, 0, 1, 0, 2, 0, 0];
+    let mut reader = Muncher::new(Cursor::new(data));
+
+    assert_eq!(count_nodes(&mut reader).unwrap(), 7);
+    assert_eq!(reader.depth(), 0);
+}
+
+#[test]
+fn test_default_max_depth() {
+    let data = vec![1u8; 10_000];
+    let mut reader = Muncher::new(Cursor::new(data));
+
+    let err = count_nodes(&mut reader).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+    assert_eq!(reader.depth(), 0);
+    assert_eq!(reader.inner.position(), 128);
+}
+
+#[test]
+fn test_set_max_depth() {
+    let data = [1, 1, 1, 0];
+
+    let mut reader = Muncher::new(Cursor::new(data));
+    reader.set_max_depth(3);
+    assert!(count_nodes(&mut reader).is_err());
+
+    let mut reader = Muncher::new(Cursor::new(data));
+    reader.set_max_depth(4);
+    assert_eq!(count_nodes(&mut reader).unwrap(), 4);
+}
+
+#[test]
+fn test_nested() {
+    let mut reader = Muncher::new(Cursor::new([7]));
+    reader.set_max_depth(1);
+
+    let value = reader
+        .nested(|m| {
+            assert_eq!(m.depth(), 1);
+            assert!(m.nested(|m| m.read_le::<u8>()).is_err());
+            m.read_le::<u8>()
+        })
+        .unwrap();
+    assert_eq!(value, 7);
+    assert_eq!(reader.depth(), 0);
+}
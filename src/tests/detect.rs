@@ -0,0 +1,81 @@
+use crate::{Bom, End, Muncher};
+use std::io::{BufReader, Cursor, ErrorKind};
+
+#[test]
+fn test_detect_end_tiff() {
+    let data = b"II\x2a\x00";
+    let mut reader = Muncher::new(Cursor::new(data));
+
+    assert_eq!(
+        reader.detect_end_from_magic(b"II", b"MM").unwrap(),
+        End::Little
+    );
+    assert_eq!(reader.end(), End::Little);
+    assert_eq!(reader.read::<u16>().unwrap(), 42);
+}
+
+#[test]
+fn test_detect_end_pcap() {
+    let le = [0xd4, 0xc3, 0xb2, 0xa1];
+    let be = [0xa1, 0xb2, 0xc3, 0xd4];
+
+    let mut reader = Muncher::new(Cursor::new(be));
+    assert_eq!(reader.detect_end_from_magic(&le, &be).unwrap(), End::Big);
+    assert_eq!(reader.end(), End::Big);
+
+    let mut reader = Muncher::new(Cursor::new(le));
+    reader.set_end(End::Big);
+    assert_eq!(reader.detect_end_from_magic(&le, &be).unwrap(), End::Little);
+    assert_eq!(reader.end(), End::Little);
+}
+
+#[test]
+fn test_detect_end_invalid() {
+    let data = b"XX";
+    let mut reader = Muncher::new(Cursor::new(data));
+    reader.set_end(End::Big);
+
+    let err = reader.detect_end_from_magic(b"II", b"MM").unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+    // Unchanged
+    assert_eq!(reader.end(), End::Big);
+}
+
+#[test]
+fn test_read_bom() {
+    let cases: [(&[u8], Bom); 5] = [
+        (b"\xef\xbb\xbfhi", Bom::Utf8),
+        (b"\xfe\xff\x00h", Bom::Utf16(End::Big)),
+        (b"\xff\xfeh\x00", Bom::Utf16(End::Little)),
+        (b"\x00\x00\xfe\xff\x00\x00\x00h", Bom::Utf32(End::Big)),
+        (b"\xff\xfe\x00\x00h\x00\x00\x00", Bom::Utf32(End::Little)),
+    ];
+    for (data, expected) in cases {
+        let mut reader = Muncher::new(BufReader::new(Cursor::new(data)));
+        assert_eq!(reader.read_bom().unwrap(), Some(expected));
+        let rest = reader.read_delim_bytes(0xff).unwrap();
+        assert_eq!(rest, &data[expected.as_bytes().len()..]);
+    }
+}
+
+#[test]
+fn test_read_bom_sets_end() {
+    let data = b"\xfe\xff\x12\x34";
+    let mut reader = Muncher::new(BufReader::new(Cursor::new(data)));
+
+    reader.read_bom().unwrap();
+    assert_eq!(reader.end(), End::Big);
+    assert_eq!(reader.read::<u16>().unwrap(), 0x1234);
+}
+
+#[test]
+fn test_read_bom_none() {
+    let data = b"hi";
+    let mut reader = Muncher::new(BufReader::new(Cursor::new(data)));
+
+    assert_eq!(reader.read_bom().unwrap(), None);
+    assert_eq!(reader.read_fixed_utf8(2).unwrap(), "hi");
+
+    let mut reader = Muncher::new(BufReader::new(Cursor::new([])));
+    assert_eq!(reader.read_bom().unwrap(), None);
+}
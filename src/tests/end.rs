@@ -0,0 +1,133 @@
+use crate::{BigEndian, ByteOrder, End, LittleEndian, Muncher, NativeEndian};
+use std::io::{BufReader, Cursor, Read, Write};
+
+#[test]
+fn test_default_end_is_little() {
+    let data = [0x34, 0x12];
+    let mut reader = Muncher::new(Cursor::new(data));
+
+    assert_eq!(reader.end(), End::Little);
+    assert_eq!(reader.read::<u16>().unwrap(), 0x1234);
+}
+
+#[test]
+fn test_read_write_default_end() {
+    let mut buffer = Vec::new();
+    let mut writer = Muncher::new(&mut buffer);
+    writer.set_end(End::Big);
+
+    writer.write(0x1234u16).unwrap();
+    writer.write(1.5f32).unwrap();
+    assert_eq!(buffer, [0x12, 0x34, 0x3f, 0xc0, 0x00, 0x00]);
+
+    let mut reader = Muncher::new(Cursor::new(buffer));
+    reader.set_end(End::Big);
+    assert_eq!(reader.read::<u16>().unwrap(), 0x1234);
+    assert_eq!(reader.read::<f32>().unwrap(), 1.5);
+}
+
+#[test]
+fn test_set_end_builder() {
+    let mut reader = Muncher::new(Cursor::new([0u8; 4]));
+    reader.set_max_alloc(16).set_end(End::Native);
+
+    assert_eq!(reader.end(), End::Native);
+}
+
+#[test]
+fn test_pref_default_end() {
+    let mut buffer = Vec::new();
+    let mut writer = Muncher::new(&mut buffer);
+    writer.set_end(End::Big);
+
+    writer.write_pref_bytes::<u16>(None, b"abc").unwrap();
+    writer.write_pref_bytes::<u16>(End::Little, b"de").unwrap();
+    assert_eq!(buffer, b"\x00\x03abc\x02\x00de");
+
+    let mut reader = Muncher::new(Cursor::new(buffer));
+    reader.set_end(End::Big);
+    assert_eq!(reader.read_pref_utf8::<u16>(None).unwrap(), "abc");
+    assert_eq!(reader.read_pref_bytes::<u16>(End::Little).unwrap(), b"de");
+}
+
+#[test]
+fn test_io_traits_still_usable() {
+    let mut buffer = Vec::new();
+    let mut writer = Muncher::new(&mut buffer);
+    Write::write(&mut writer, b"hi").unwrap();
+
+    let mut reader = Muncher::new(Cursor::new(buffer));
+    let mut out = [0; 2];
+    Read::read(&mut reader, &mut out).unwrap();
+    assert_eq!(&out, b"hi");
+}
+
+#[test]
+fn test_compile_time_order() {
+    let data = [0x12, 0x34, 0x12, 0x34];
+
+    let mut reader = Muncher::with_order(Cursor::new(data), BigEndian);
+    assert_eq!(reader.end(), End::Big);
+    assert_eq!(reader.read::<u16>().unwrap(), 0x1234);
+    // Explicit endianness still works
+    assert_eq!(reader.read_m::<u16>(End::Little).unwrap(), 0x3412);
+
+    let mut reader = Muncher::with_order(Cursor::new(data), LittleEndian);
+    assert_eq!(reader.end(), End::Little);
+    assert_eq!(reader.read::<u32>().unwrap(), 0x34123412);
+
+    let mut reader = Muncher::with_order(Cursor::new(data), NativeEndian);
+    assert_eq!(reader.read::<u32>().unwrap(), u32::from_ne_bytes(data));
+}
+
+#[test]
+fn test_compile_time_order_strings() {
+    let mut buffer = Vec::new();
+    let mut writer = Muncher::with_order(&mut buffer, BigEndian);
+    writer.write(7u16).unwrap();
+    writer.write_pref_bytes::<u16>(None, b"abc").unwrap();
+    assert_eq!(buffer, b"\x00\x07\x00\x03abc");
+
+    let mut reader = Muncher::with_order(BufReader::new(Cursor::new(buffer)), BigEndian);
+    assert_eq!(reader.peek::<u16>(End::Big).unwrap(), 7);
+    assert_eq!(reader.read::<u16>().unwrap(), 7);
+    assert_eq!(reader.read_pref_utf8::<u16>(None).unwrap(), "abc");
+}
+
+#[test]
+fn test_compile_time_swap_matches_end() {
+    fn check<O: ByteOrder>(order: O) {
+        assert_eq!(O::SWAP, Some(!order.end().is_target_endian()));
+        assert_eq!(order.swaps(), !order.end().is_target_endian());
+    }
+    check(LittleEndian);
+    check(BigEndian);
+    check(NativeEndian);
+
+    assert_eq!(End::SWAP, None);
+    for end in [End::Little, End::Big, End::Native] {
+        assert_eq!(end.swaps(), !end.is_target_endian());
+    }
+}
+
+#[test]
+fn test_compile_time_order_arrays() {
+    let mut buffer = Vec::new();
+    let mut writer = Muncher::with_order(&mut buffer, BigEndian);
+    writer.write_slice(&[1u16, 2]).unwrap();
+    writer.write_pref_slice_m::<u8, u16>(None, &[3]).unwrap();
+    // An explicit endianness overrides the fixed order
+    writer
+        .write_pref_slice_m::<u8, u16>(End::Little, &[4])
+        .unwrap();
+    assert_eq!(buffer, [0, 1, 0, 2, 1, 0, 3, 1, 4, 0]);
+
+    let mut reader = Muncher::with_order(Cursor::new(buffer), BigEndian);
+    assert_eq!(reader.read_array::<u16>(2).unwrap(), [1, 2]);
+    assert_eq!(reader.read_pref_array_m::<u8, u16>(None).unwrap(), [3]);
+    assert_eq!(
+        reader.read_pref_array_m::<u8, u16>(End::Little).unwrap(),
+        [4]
+    );
+    assert_eq!(reader.try_read::<u16>().unwrap(), None);
+}
@@ -0,0 +1,118 @@
+use crate::{End, Muncher};
+use std::io::{BufReader, Cursor, ErrorKind};
+
+#[test]
+fn test_is_eof() {
+    let mut reader = Muncher::new(BufReader::new(Cursor::new([1, 2, 3])));
+
+    assert!(!reader.is_eof().unwrap());
+    reader.read_le::<u16>().unwrap();
+    assert!(!reader.is_eof().unwrap());
+    reader.read_le::<u8>().unwrap();
+    assert!(reader.is_eof().unwrap());
+    assert_eq!(reader.consumed_bytes(), 3);
+}
+
+#[test]
+fn test_is_eof_lookahead() {
+    let mut reader = Muncher::new(Cursor::new([1, 2]));
+
+    reader.peek_bytes(2).unwrap();
+    assert!(!reader.is_eof().unwrap());
+    reader.read_le::<u16>().unwrap();
+    assert!(reader.is_eof().unwrap());
+}
+
+#[test]
+fn test_try_read() {
+    let mut reader = Muncher::new(Cursor::new([0x12, 0x34, 0x56, 0x78]));
+
+    assert_eq!(reader.try_read_be::<u16>().unwrap(), Some(0x1234));
+    assert_eq!(reader.try_read_le::<u16>().unwrap(), Some(0x7856));
+    assert_eq!(reader.try_read_le::<u16>().unwrap(), None);
+    assert_eq!(reader.try_read_m::<u8>(End::Big).unwrap(), None);
+}
+
+#[test]
+fn test_try_read_truncated() {
+    let mut reader = Muncher::new(Cursor::new([0x12, 0x34, 0x56]));
+
+    assert_eq!(reader.try_read_be::<u16>().unwrap(), Some(0x1234));
+    let err = reader.try_read_be::<u16>().unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+}
+
+#[test]
+fn test_try_read_strings() {
+    let data = b"\x02hi\x03ab";
+    let mut reader = Muncher::new(Cursor::new(data));
+
+    assert_eq!(
+        reader.try_read_pref_utf8::<u8>(End::Big).unwrap().unwrap(),
+        "hi"
+    );
+    let err = reader.try_read_pref_utf8::<u8>(End::Big).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+
+    let mut reader = Muncher::new(Cursor::new(b"one\0two\0"));
+    assert_eq!(reader.try_read_cstr_utf8().unwrap().unwrap(), "one");
+    assert_eq!(reader.try_read_cstr_bytes().unwrap().unwrap(), b"two");
+    assert_eq!(reader.try_read_cstr_bytes().unwrap(), None);
+}
+
+#[test]
+fn test_try_read_fixed_and_arrays() {
+    let data = b"abc\x00\x01\x00\x02\x01\x00\x03";
+    let mut reader = Muncher::new(Cursor::new(data));
+
+    assert_eq!(reader.try_read_fixed_utf8(2).unwrap().unwrap(), "ab");
+    assert_eq!(reader.try_read_fixed_bytes(1).unwrap().unwrap(), b"c");
+    assert_eq!(
+        reader
+            .try_read_array_m::<u16>(2, End::Big)
+            .unwrap()
+            .unwrap(),
+        [1, 2]
+    );
+    assert_eq!(
+        reader
+            .try_read_pref_array_m::<u8, u16>(End::Big)
+            .unwrap()
+            .unwrap(),
+        [3]
+    );
+    assert_eq!(reader.try_read_fixed_bytes(1).unwrap(), None);
+    assert_eq!(reader.try_read_array_m::<u16>(1, End::Big).unwrap(), None);
+
+    let mut reader = Muncher::new(Cursor::new(b"\x02\x00"));
+    let err = reader
+        .try_read_pref_array_m::<u8, u16>(End::Big)
+        .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+}
+
+#[cfg(feature = "ucs2")]
+#[test]
+fn test_try_read_ucs2() {
+    let mut reader = Muncher::new(Cursor::new(b"\x00h\x01\x00i"));
+    assert_eq!(reader.try_read_fixed_ucs2(1).unwrap().unwrap(), "h");
+    assert_eq!(
+        reader.try_read_pref_ucs2::<u8>(End::Big).unwrap().unwrap(),
+        "i"
+    );
+    assert_eq!(reader.try_read_fixed_ucs2(1).unwrap(), None);
+}
+
+#[cfg(feature = "mutf8")]
+#[test]
+fn test_try_read_mutf8() {
+    let mut reader = Muncher::new(Cursor::new(b"\x03a\xC0\x80b\0c"));
+    assert_eq!(
+        reader.try_read_pref_mutf8::<u8>(End::Big).unwrap().unwrap(),
+        "a\0"
+    );
+    assert_eq!(reader.try_read_cstr_mutf8().unwrap().unwrap(), "b");
+    assert_eq!(reader.try_read_fixed_mutf8(1).unwrap().unwrap(), "c");
+    assert_eq!(reader.try_read_fixed_mutf8(1).unwrap(), None);
+    assert!(reader.try_read_cstr_mutf8().unwrap().is_none());
+}
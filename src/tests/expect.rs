@@ -0,0 +1,140 @@
+use crate::{End, Muncher};
+use std::io::{Cursor, ErrorKind};
+
+#[test]
+fn test_expect_bytes() {
+    let data = b"RIFF\x24\x00\x00\x00";
+    let mut reader = Muncher::new(Cursor::new(data));
+
+    reader.expect_bytes(b"RIFF").unwrap();
+    assert_eq!(reader.read_le::<u32>().unwrap(), 0x24);
+}
+
+#[test]
+fn test_expect_bytes_mismatch() {
+    let data = b"RIFX";
+    let mut reader = Muncher::new(Cursor::new(data));
+
+    let err = reader.expect_bytes(b"RIFF").unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+    assert_eq!(
+        err.to_string(),
+        "unexpected bytes: expected [52 49 46 46], found [52 49 46 58]"
+    );
+}
+
+#[test]
+fn test_expect_bytes_insufficient() {
+    let data = b"RI";
+    let mut reader = Muncher::new(Cursor::new(data));
+
+    let err = reader.expect_bytes(b"RIFF").unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+}
+
+#[test]
+fn test_expect_value() {
+    let data = [0xa1, 0xb2, 0xc3, 0xd4, 0xd4, 0xc3, 0xb2, 0xa1];
+    let mut reader = Muncher::new(Cursor::new(data));
+
+    reader.expect(0xa1b2c3d4u32, End::Big).unwrap();
+    reader.expect(0xa1b2c3d4u32, End::Little).unwrap();
+}
+
+#[test]
+fn test_expect_value_mismatch() {
+    let data = [0xca, 0xfe, 0xba, 0xbe];
+    let mut reader = Muncher::new(Cursor::new(data));
+
+    let err = reader.expect(0xcafed00du32, End::Big).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+    assert_eq!(
+        err.to_string(),
+        "unexpected bytes: expected [ca fe d0 0d], found [ca fe ba be]"
+    );
+}
+
+#[test]
+fn test_expect_one_of() {
+    let data = b"MM\x00\x2a";
+    let mut reader = Muncher::new(Cursor::new(data));
+
+    let which = reader.expect_one_of(&[b"II", b"MM"]).unwrap();
+    assert_eq!(which, 1);
+    assert_eq!(reader.read_be::<u16>().unwrap(), 42);
+}
+
+#[test]
+fn test_expect_one_of_different_lengths() {
+    let data = b"\x7fELF\x02";
+    let mut reader = Muncher::new(Cursor::new(data));
+
+    let which = reader.expect_one_of(&[b"\x7fELF", b"MZ"]).unwrap();
+    assert_eq!(which, 0);
+    assert_eq!(reader.read_le::<u8>().unwrap(), 2);
+}
+
+#[test]
+fn test_expect_one_of_shortest_first() {
+    let data = b"MZ\x90\x00";
+    let mut reader = Muncher::new(Cursor::new(data));
+
+    let which = reader.expect_one_of(&[b"\x7fELF", b"MZ"]).unwrap();
+    assert_eq!(which, 1);
+    assert_eq!(reader.read_le::<u16>().unwrap(), 0x90);
+}
+
+#[test]
+fn test_expect_one_of_mismatch() {
+    let data = b"XX";
+    let mut reader = Muncher::new(Cursor::new(data));
+
+    let err = reader.expect_one_of(&[b"II", b"MM"]).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+    assert_eq!(
+        err.to_string(),
+        "unexpected bytes: expected one of [49 49], [4d 4d], found [58 58]"
+    );
+}
+
+#[test]
+fn test_expect_one_of_short_input() {
+    // Shorter than the longest candidate: still a mismatch, not an EOF
+    let mut reader = Muncher::new(Cursor::new(b"MZ"));
+    let err = reader.expect_one_of(&[b"\x7fELF", b"MZ\x90"]).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+    assert_eq!(
+        err.to_string(),
+        "unexpected bytes: expected one of [7f 45 4c 46], [4d 5a 90], found [4d 5a]"
+    );
+}
+
+#[test]
+fn test_expect_one_of_consumes_nothing_on_mismatch() {
+    let mut reader = Muncher::new(Cursor::new(b"\x7fEXE!"));
+    assert!(reader.expect_one_of(&[b"\x7fELF", b"MZ"]).is_err());
+    assert_eq!(reader.read_fixed_bytes(5).unwrap(), b"\x7fEXE!");
+
+    // Only as far as a candidate can still match
+    let mut reader = Muncher::new(Cursor::new(b"XYZW"));
+    let err = reader.expect_one_of(&[b"XA", b"XYZA"]).unwrap_err();
+    assert!(err.to_string().ends_with("found [58 59 5a 57]"), "{err}");
+    let mut reader = Muncher::new(Cursor::new(b"QQQQ"));
+    let err = reader.expect_one_of(&[b"XA", b"XYZA"]).unwrap_err();
+    assert!(err.to_string().ends_with("found [51 51]"), "{err}");
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+async fn test_expect_one_of_a() {
+    use crate::AsyncMunchRead;
+
+    let mut reader = Muncher::new(Cursor::new(b"MZ!"));
+    let err = reader
+        .expect_one_of_a(&[b"\x7fELF", b"MZ\x90"])
+        .await
+        .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+    assert_eq!(reader.expect_one_of_a(&[b"MZ"]).await.unwrap(), 0);
+    assert_eq!(reader.read_le_a::<u8>().await.unwrap(), b'!');
+}
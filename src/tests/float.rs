@@ -1,3 +1,6 @@
+// The literals and the per-endian branches are kept as written on purpose.
+#![allow(clippy::approx_constant, clippy::if_same_then_else)]
+
 use crate::{End, Muncher};
 use std::io::Cursor;
 
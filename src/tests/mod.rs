@@ -1,3 +1,29 @@
+mod alloc;
+mod array;
+#[cfg(any(feature = "tokio", feature = "futures"))]
+mod async_io;
+mod budget;
+#[cfg(feature = "tokio")]
+mod cancel;
+mod checksum;
+#[cfg(feature = "tokio-util")]
+mod codec;
+mod collection;
+mod depth;
+mod detect;
+mod end;
+mod eof;
+mod expect;
 mod float;
 mod int;
+mod pack;
+mod peek;
+mod push;
+mod records;
+mod sans_io;
+mod schema;
+mod slice_muncher;
 mod string;
+#[cfg(feature = "tokio")]
+mod timeout;
+mod trace;
@@ -0,0 +1,201 @@
+use crate::Muncher;
+use crate::pack::{Value, calcsize};
+use std::io::{Cursor, ErrorKind};
+
+fn pack(format: &str, values: &[Value]) -> Vec<u8> {
+    let mut data = Vec::new();
+    Muncher::new(&mut data).pack(format, values).unwrap();
+    data
+}
+
+fn unpack(format: &str, data: &[u8]) -> Vec<Value> {
+    Muncher::new(Cursor::new(data)).unpack(format).unwrap()
+}
+
+#[test]
+fn test_pack_like_python() {
+    // struct.pack('<IHh10sx?', 1, 2, -3, b'hello', True)
+    let values = [
+        Value::UInt(1),
+        Value::UInt(2),
+        Value::Int(-3),
+        Value::Bytes(b"hello".to_vec()),
+        Value::Bool(true),
+    ];
+    let data = pack("<IHh10sx?", &values);
+    assert_eq!(
+        data,
+        b"\x01\x00\x00\x00\x02\x00\xfd\xffhello\x00\x00\x00\x00\x00\x00\x01"
+    );
+
+    let read = unpack("<IHh10sx?", &data);
+    assert_eq!(read[..3], values[..3]);
+    assert_eq!(read[3], Value::Bytes(b"hello\0\0\0\0\0".to_vec()));
+    assert_eq!(read[4], Value::Bool(true));
+}
+
+#[test]
+fn test_pack_byte_orders() {
+    assert_eq!(pack(">h", &[Value::Int(1)]), b"\x00\x01");
+    assert_eq!(pack("!h", &[Value::Int(1)]), b"\x00\x01");
+    assert_eq!(pack("<h", &[Value::Int(1)]), b"\x01\x00");
+    assert_eq!(pack("=h", &[Value::Int(1)]), 1i16.to_ne_bytes());
+
+    assert_eq!(
+        unpack(">qQ", &[0xFF; 16]),
+        [Value::Int(-1), Value::UInt(u64::MAX)]
+    );
+    assert_eq!(
+        unpack("<fd", &[0, 0, 0x80, 0x3F, 0, 0, 0, 0, 0, 0, 0xF0, 0xBF]),
+        [Value::Float(1.0), Value::Float(-1.0)]
+    );
+}
+
+#[test]
+fn test_pack_counts_and_padding() {
+    assert_eq!(
+        unpack("<3B 2x c", b"\x01\x02\x03\0\0z"),
+        [
+            Value::UInt(1),
+            Value::UInt(2),
+            Value::UInt(3),
+            Value::Bytes(b"z".to_vec())
+        ]
+    );
+    assert_eq!(
+        pack("<2H", &[Value::UInt(1), Value::UInt(2)]),
+        b"\x01\x00\x02\x00"
+    );
+    assert_eq!(pack("<x2xB", &[Value::UInt(9)]), b"\0\0\0\x09");
+}
+
+#[test]
+fn test_pack_pascal_strings() {
+    assert_eq!(pack("5p", &[Value::Bytes(b"abc".to_vec())]), b"\x03abc\0");
+    assert_eq!(pack("3p", &[Value::Bytes(b"hello".to_vec())]), b"\x02he");
+    assert_eq!(unpack("5p", b"\x03abc\0"), [Value::Bytes(b"abc".to_vec())]);
+    // A length past the end is cut, like in Python
+    assert_eq!(unpack("3p", b"\x09ab"), [Value::Bytes(b"ab".to_vec())]);
+}
+
+#[test]
+fn test_pack_native_alignment() {
+    assert_eq!(calcsize("@bi"), Ok(8));
+    assert_eq!(calcsize("bi"), Ok(8));
+    assert_eq!(calcsize("@ib"), Ok(5));
+    assert_eq!(calcsize("=bi"), Ok(5));
+    assert_eq!(calcsize("@b3s"), Ok(4));
+
+    let data = pack("bi", &[Value::Int(1), Value::Int(2)]);
+    assert_eq!(data.len(), 8);
+    assert_eq!(data[1..4], [0, 0, 0]);
+    assert_eq!(unpack("bi", &data), [Value::Int(1), Value::Int(2)]);
+}
+
+#[test]
+fn test_calcsize() {
+    assert_eq!(calcsize("<IHh10sx?"), Ok(20));
+    assert_eq!(calcsize(""), Ok(0));
+    assert_eq!(calcsize("<"), Ok(0));
+    assert_eq!(calcsize(">lL"), Ok(8));
+    assert_eq!(calcsize("@n"), Ok(size_of::<isize>()));
+
+    assert!(calcsize("<z").is_err());
+    assert!(calcsize("<n").is_err());
+    assert!(calcsize("<e").is_err());
+    assert!(calcsize("<3").is_err());
+    assert!(calcsize("<0p").is_err());
+    assert!(calcsize("I<").is_err());
+    assert!(calcsize("<99999999999999999999999B").is_err());
+}
+
+#[test]
+fn test_pack_struct_format_macro() {
+    const FORMAT: &str = crate::struct_format!("<HB");
+    assert_eq!(
+        unpack(FORMAT, b"\x01\x00\x02"),
+        [Value::UInt(1), Value::UInt(2)]
+    );
+}
+
+#[test]
+fn test_pack_errors() {
+    let mut data = Vec::new();
+    let mut muncher = Muncher::new(&mut data);
+    let err = |r: std::io::Result<()>| r.unwrap_err().kind();
+
+    assert_eq!(err(muncher.pack("<z", &[])), ErrorKind::InvalidInput);
+    assert_eq!(err(muncher.pack("<H", &[])), ErrorKind::InvalidInput);
+    assert_eq!(
+        err(muncher.pack("<B", &[Value::UInt(1), Value::UInt(2)])),
+        ErrorKind::InvalidInput
+    );
+    assert_eq!(
+        err(muncher.pack("<B", &[Value::UInt(256)])),
+        ErrorKind::InvalidInput
+    );
+    assert_eq!(
+        err(muncher.pack("<I", &[Value::Int(-1)])),
+        ErrorKind::InvalidInput
+    );
+    assert_eq!(
+        err(muncher.pack("<s", &[Value::UInt(1)])),
+        ErrorKind::InvalidInput
+    );
+    assert_eq!(
+        err(muncher.pack("<c", &[Value::Bytes(b"ab".to_vec())])),
+        ErrorKind::InvalidInput
+    );
+    // Nothing was written, not even the values before the invalid one
+    assert_eq!(
+        err(muncher.pack("<BB", &[Value::UInt(1), Value::Float(1.0)])),
+        ErrorKind::InvalidInput
+    );
+    assert!(data.is_empty());
+
+    let mut muncher = Muncher::new(Cursor::new(b"\x01"));
+    assert_eq!(
+        muncher.unpack("<H").unwrap_err().kind(),
+        ErrorKind::UnexpectedEof
+    );
+    assert_eq!(
+        muncher.unpack("<Hz").unwrap_err().kind(),
+        ErrorKind::InvalidInput
+    );
+}
+
+#[test]
+fn test_pack_huge_formats() {
+    let mut muncher = Muncher::new(Cursor::new(b"\x01"));
+    assert_eq!(
+        muncher.unpack("9999999999999999999q").unwrap_err().kind(),
+        ErrorKind::InvalidInput
+    );
+    // Valid, but past the allocation limit
+    assert!(muncher.unpack("100000000000000x").is_err());
+    assert!(muncher.unpack("100000000000000s").is_err());
+
+    let mut data = Vec::new();
+    let mut muncher = Muncher::new(&mut data);
+    let err = |r: std::io::Result<()>| r.unwrap_err().kind();
+    assert_eq!(
+        err(muncher.pack("18446744073709551615B1B", &[])),
+        ErrorKind::InvalidInput
+    );
+    assert_eq!(
+        err(muncher.pack("100000000000000x", &[])),
+        ErrorKind::InvalidInput
+    );
+    assert_eq!(
+        err(muncher.pack("100000000000000s", &[Value::Bytes(vec![])])),
+        ErrorKind::InvalidInput
+    );
+    assert_eq!(
+        err(muncher.pack("100000000000000p", &[Value::Bytes(vec![])])),
+        ErrorKind::InvalidInput
+    );
+    muncher.set_max_alloc(4);
+    assert_eq!(err(muncher.pack("5x", &[])), ErrorKind::InvalidInput);
+    muncher.pack("4x", &[]).unwrap();
+    assert_eq!(data, [0; 4]);
+}
@@ -0,0 +1,129 @@
+use crate::{End, Muncher};
+use std::io::{BufReader, Cursor, ErrorKind};
+
+#[test]
+fn test_peek() {
+    let data = [0x34, 0x12, 0x78, 0x56];
+    let mut reader = Muncher::new(BufReader::new(Cursor::new(data)));
+
+    assert_eq!(reader.peek::<u16>(End::Little).unwrap(), 0x1234);
+    assert_eq!(reader.peek::<u16>(End::Big).unwrap(), 0x3412);
+    assert_eq!(reader.read_le::<u32>().unwrap(), 0x56781234);
+}
+
+#[test]
+fn test_peek_eof() {
+    let data = [0x34];
+    let mut reader = Muncher::new(BufReader::new(Cursor::new(data)));
+
+    let err = reader.peek::<u16>(End::Little).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+    // Nothing was consumed
+    assert_eq!(reader.read_le::<u8>().unwrap(), 0x34);
+}
+
+#[test]
+fn test_peek_bytes() {
+    let data = b"hello world";
+    let mut reader = Muncher::new(BufReader::new(Cursor::new(data)));
+
+    assert_eq!(reader.peek_bytes(5).unwrap(), b"hello");
+    assert_eq!(reader.read_fixed_utf8(11).unwrap(), "hello world");
+}
+
+#[test]
+fn test_peek_bytes_larger_than_buffer() {
+    let data = b"hello world";
+    let mut reader = Muncher::new(BufReader::with_capacity(3, Cursor::new(data)));
+
+    assert_eq!(reader.peek_bytes(8).unwrap(), b"hello wo");
+    assert_eq!(reader.peek_bytes(2).unwrap(), b"he");
+    assert_eq!(reader.peek_bytes(11).unwrap(), b"hello world");
+    assert_eq!(reader.read_fixed_utf8(6).unwrap(), "hello ");
+    assert_eq!(reader.peek_bytes(3).unwrap(), b"wor");
+    assert_eq!(reader.read_fixed_utf8(5).unwrap(), "world");
+}
+
+#[test]
+fn test_peek_bytes_in_small_steps() {
+    let data: Vec<u8> = (0..1000u32).map(|i| i as u8).collect();
+    let mut reader = Muncher::new(BufReader::with_capacity(7, Cursor::new(&data)));
+
+    // Consuming the look-ahead buffer bit by bit, and growing it in between
+    assert_eq!(reader.peek_bytes(600).unwrap(), &data[..600]);
+    for &b in &data[..400] {
+        assert_eq!(reader.read::<u8>().unwrap(), b);
+    }
+    assert_eq!(reader.peek_bytes(500).unwrap(), &data[400..900]);
+    for &b in &data[400..700] {
+        assert_eq!(reader.read::<u8>().unwrap(), b);
+    }
+    assert_eq!(reader.peek_bytes(1000).unwrap(), &data[700..]);
+    assert_eq!(reader.read_fixed_bytes(300).unwrap(), &data[700..]);
+    assert_eq!(reader.peek_bytes(1).unwrap(), b"");
+}
+
+#[test]
+fn test_peek_bytes_short() {
+    let data = b"hi";
+    let mut reader = Muncher::new(BufReader::new(Cursor::new(data)));
+
+    assert_eq!(reader.peek_bytes(4).unwrap(), b"hi");
+    assert_eq!(reader.read_fixed_utf8(2).unwrap(), "hi");
+    assert_eq!(reader.peek_bytes(4).unwrap(), b"");
+}
+
+#[test]
+fn test_peek_then_cstr() {
+    let data = b"abc\0def\0";
+    let mut reader = Muncher::new(BufReader::with_capacity(2, Cursor::new(data)));
+
+    assert_eq!(reader.peek_bytes(5).unwrap(), b"abc\0d");
+    assert_eq!(reader.read_cstr_utf8().unwrap(), "abc");
+    assert_eq!(reader.read_cstr_utf8().unwrap(), "def");
+}
+
+#[test]
+fn test_try_parse_success() {
+    let data = [1, 2, 3];
+    let mut reader = Muncher::new(BufReader::new(Cursor::new(data)));
+
+    let value = reader.try_parse(|m| m.read_be::<u16>()).unwrap();
+    assert_eq!(value, 0x0102);
+    assert_eq!(reader.read_le::<u8>().unwrap(), 3);
+}
+
+#[test]
+fn test_try_parse_rollback() {
+    let data = b"\x00\x05hello";
+    let mut reader = Muncher::new(BufReader::with_capacity(2, Cursor::new(data)));
+
+    let result = reader.try_parse(|m| {
+        m.expect_bytes(b"\x00\x05")?;
+        m.expect_bytes(b"world")
+    });
+    assert!(result.is_err());
+
+    assert_eq!(reader.read_pref_utf8::<u16>(End::Big).unwrap(), "hello");
+}
+
+#[test]
+fn test_try_parse_nested() {
+    let data = [1, 2, 3, 4];
+    let mut reader = Muncher::new(BufReader::new(Cursor::new(data)));
+
+    let result: std::io::Result<u8> = reader.try_parse(|m| {
+        let first = m.read_le::<u8>()?;
+        assert_eq!(first, 1);
+        // Inner failure only rolls back the inner part
+        assert!(m.try_parse(|m| m.read_le::<u64>()).is_err());
+        let second = m.try_parse(|m| m.read_le::<u8>())?;
+        assert_eq!(second, 2);
+        m.expect_bytes(&[9])?;
+        Ok(second)
+    });
+    assert!(result.is_err());
+
+    // Everything was rolled back
+    assert_eq!(reader.read_be::<u32>().unwrap(), 0x01020304);
+}
@@ -0,0 +1,182 @@
+use crate::{BigEndian, End, Parsed, PushMuncher};
+use std::io::ErrorKind;
+
+#[test]
+fn test_push_primitive() {
+    let mut muncher = PushMuncher::new();
+    assert_eq!(
+        muncher.read_be::<u32>().unwrap(),
+        Parsed::Incomplete { needed: 4 }
+    );
+
+    muncher.feed(&[0x12, 0x34]);
+    assert_eq!(
+        muncher.read_be::<u32>().unwrap(),
+        Parsed::Incomplete { needed: 2 }
+    );
+    assert_eq!(muncher.buffered(), [0x12, 0x34]);
+
+    muncher.feed(&[0x56, 0x78, 0x9a]);
+    assert_eq!(muncher.read_be::<u32>().unwrap(), Parsed::Done(0x1234_5678));
+    assert_eq!(muncher.read::<u8>().unwrap(), Parsed::Done(0x9a));
+    assert_eq!(muncher.consumed_bytes(), 5);
+    assert!(muncher.buffered().is_empty());
+}
+
+#[test]
+fn test_push_with_order() {
+    let mut muncher = PushMuncher::with_order(BigEndian);
+    muncher.feed(&[0x12, 0x34, 0x02, 0x00, b'h', b'i']);
+    assert_eq!(muncher.read::<u16>().unwrap(), Parsed::Done(0x1234));
+    assert_eq!(
+        muncher.read_pref_utf8::<u16>(End::Little).unwrap(),
+        Parsed::Done("hi".to_owned())
+    );
+}
+
+#[test]
+fn test_push_pref_needed() {
+    let mut muncher = PushMuncher::new();
+    muncher.feed(&[0x00]);
+    assert_eq!(
+        muncher.read_pref_bytes::<u16>(End::Big).unwrap(),
+        Parsed::Incomplete { needed: 1 }
+    );
+    muncher.feed(&[0x05, b'a']);
+    assert_eq!(
+        muncher.read_pref_bytes::<u16>(End::Big).unwrap(),
+        Parsed::Incomplete { needed: 4 }
+    );
+    muncher.feed(b"bcd");
+    assert_eq!(
+        muncher.read_pref_bytes::<u16>(End::Big).unwrap(),
+        Parsed::Incomplete { needed: 1 }
+    );
+    muncher.feed(b"e!");
+    assert_eq!(
+        muncher.read_pref_bytes::<u16>(End::Big).unwrap(),
+        Parsed::Done(b"abcde".to_vec())
+    );
+    assert_eq!(muncher.buffered(), b"!");
+}
+
+#[test]
+fn test_push_fragments() {
+    let data = b"\x02hi\x03abc\x00\x01!";
+    let mut muncher = PushMuncher::new();
+    let mut records = Vec::new();
+
+    // Fed one byte at a time, parsing records whenever possible.
+    for &byte in data {
+        muncher.feed(&[byte]);
+        while let Parsed::Done(record) = muncher.read_pref_utf8::<u8>(None).unwrap() {
+            records.push(record);
+        }
+    }
+    assert_eq!(records, ["hi", "abc", "", "!"]);
+    assert!(muncher.buffered().is_empty());
+}
+
+#[test]
+fn test_push_cstr() {
+    let mut muncher = PushMuncher::new();
+    muncher.feed(b"hel");
+    assert_eq!(
+        muncher.read_cstr_bytes().unwrap(),
+        Parsed::Incomplete { needed: 1 }
+    );
+    muncher.feed(b"lo\0wor");
+    assert_eq!(
+        muncher.read_cstr_bytes().unwrap(),
+        Parsed::Done(b"hello".to_vec())
+    );
+    assert_eq!(
+        muncher.read_cstr_utf8().unwrap(),
+        Parsed::Incomplete { needed: 1 }
+    );
+    muncher.feed(b"ld\0");
+    assert_eq!(
+        muncher.read_cstr_utf8().unwrap(),
+        Parsed::Done("world".to_owned())
+    );
+}
+
+#[test]
+fn test_push_errors_consume_nothing() {
+    let mut muncher = PushMuncher::new();
+    muncher.feed(&[0x02, 0xff, 0xfe]);
+    let err = muncher.read_pref_utf8::<u8>(None).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+    assert_eq!(muncher.buffered(), [0x02, 0xff, 0xfe]);
+    assert_eq!(
+        muncher.read_pref_bytes::<u8>(None).unwrap(),
+        Parsed::Done(vec![0xff, 0xfe])
+    );
+}
+
+#[test]
+fn test_push_max_alloc() {
+    let mut muncher = PushMuncher::new();
+    muncher.set_max_alloc(16);
+    muncher.feed(&[0xff, 0xff, 0xff, 0xff]);
+    let err = muncher.read_pref_bytes::<u32>(None).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+
+    let mut muncher = PushMuncher::new();
+    muncher.set_max_alloc(4);
+    muncher.feed(b"abcd");
+    assert_eq!(
+        muncher.read_cstr_bytes().unwrap(),
+        Parsed::Incomplete { needed: 1 }
+    );
+    muncher.feed(b"e");
+    let err = muncher.read_cstr_bytes().unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+}
+
+#[test]
+fn test_push_compaction() {
+    let mut muncher = PushMuncher::new();
+    for i in 0..1000u16 {
+        muncher.feed(&i.to_le_bytes());
+        assert_eq!(muncher.read::<u16>().unwrap(), Parsed::Done(i));
+    }
+    assert!(muncher.buf.len() <= 4);
+    assert_eq!(muncher.consumed_bytes(), 2000);
+}
+
+#[cfg(feature = "ucs2")]
+#[test]
+fn test_push_ucs2() {
+    let mut muncher = PushMuncher::new();
+    muncher.feed(&[0x02, 0x00, 0x48]);
+    assert_eq!(
+        muncher.read_pref_ucs2::<u8>(None).unwrap(),
+        Parsed::Incomplete { needed: 2 }
+    );
+    muncher.feed(&[0x00, 0x69]);
+    assert_eq!(
+        muncher.read_pref_ucs2::<u8>(None).unwrap(),
+        Parsed::Done("Hi".to_owned())
+    );
+}
+
+#[cfg(feature = "mutf8")]
+#[test]
+fn test_push_mutf8() {
+    let mut muncher = PushMuncher::new();
+    muncher.feed(&[0x02, 0xc0]);
+    assert_eq!(
+        muncher.read_pref_mutf8::<u8>(None).unwrap(),
+        Parsed::Incomplete { needed: 1 }
+    );
+    muncher.feed(&[0x80, b'a', 0xc0, 0x80, 0x00]);
+    assert_eq!(
+        muncher.read_pref_mutf8::<u8>(None).unwrap(),
+        Parsed::Done("\0".to_owned())
+    );
+    assert_eq!(
+        muncher.read_cstr_mutf8().unwrap(),
+        Parsed::Done("a\0".to_owned())
+    );
+}
@@ -0,0 +1,83 @@
+use crate::{End, Muncher};
+use std::io::{BufReader, Cursor, Error, ErrorKind};
+
+#[test]
+fn test_records() {
+    let data = [1, 0, 2, 0, 3, 0];
+    let mut reader = Muncher::new(Cursor::new(data));
+
+    let result: Vec<u16> = reader
+        .records(|m| m.read_le())
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(result, [1, 2, 3]);
+}
+
+#[test]
+fn test_records_empty() {
+    let mut reader = Muncher::new(Cursor::new([0u8; 0]));
+
+    assert!(reader.records(|m| m.read_le::<u8>()).next().is_none());
+}
+
+#[test]
+fn test_records_truncated() {
+    let data = [1, 0, 2];
+    let mut reader = Muncher::new(BufReader::new(Cursor::new(data)));
+
+    let mut records = reader.records(|m| m.read_le::<u16>());
+    assert_eq!(records.next().unwrap().unwrap(), 1);
+    let err = records.next().unwrap().unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+    assert!(err.to_string().contains("byte 2"));
+    assert!(records.next().is_none());
+}
+
+#[test]
+fn test_records_truncated_after_budget_reset() {
+    let data = [1, 0, 2, 0, 3];
+    let mut reader = Muncher::new(BufReader::new(Cursor::new(data)));
+
+    let mut records = reader.records(|m| {
+        m.reset_budget_usage();
+        m.read_le::<u16>()
+    });
+    assert_eq!(records.next().unwrap().unwrap(), 1);
+    assert_eq!(records.next().unwrap().unwrap(), 2);
+    let err = records.next().unwrap().unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+    assert!(err.to_string().contains("byte 4"), "{err}");
+    assert!(records.next().is_none());
+}
+
+#[test]
+fn test_records_parse_error() {
+    let data = b"\x02hi\x02\xff\xff\x02ok";
+    let mut reader = Muncher::new(Cursor::new(data));
+
+    let mut records = reader.records(|m| m.read_pref_utf8::<u8>(End::Little));
+    assert_eq!(records.next().unwrap().unwrap(), "hi");
+    let err = records.next().unwrap().unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+    assert!(records.next().is_none());
+}
+
+#[test]
+fn test_records_custom_error() {
+    let data = [1, 2, 3];
+    let mut reader = Muncher::new(Cursor::new(data));
+
+    let mut records = reader.records(|m| {
+        let value = m.read_le::<u8>()?;
+        if value == 2 {
+            return Err(Error::other("bad record"));
+        }
+        Ok(value)
+    });
+    assert_eq!(records.next().unwrap().unwrap(), 1);
+    assert_eq!(
+        records.next().unwrap().unwrap_err().kind(),
+        ErrorKind::Other
+    );
+    assert!(records.next().is_none());
+}
@@ -0,0 +1,159 @@
+use crate::{End, Muncher, Primitive};
+use std::io::{Cursor, ErrorKind, Read, Write};
+
+/// An unsigned LEB128 varint, a [`Primitive`] without a fixed size.
+#[derive(Debug, PartialEq)]
+pub(super) struct Varint(pub(super) u64);
+
+impl Primitive for Varint {
+    fn read_endian(reader: &mut impl Read, _: End) -> Result<Self, std::io::Error> {
+        let mut value = 0;
+        for shift in (0..64).step_by(7) {
+            let mut byte = [0];
+            reader.read_exact(&mut byte)?;
+            value |= u64::from(byte[0] & 0x7f) << shift;
+            if byte[0] & 0x80 == 0 {
+                return Ok(Self(value));
+            }
+        }
+        Err(std::io::Error::new(
+            ErrorKind::InvalidData,
+            "varint too long",
+        ))
+    }
+
+    fn write_endian(self, writer: &mut impl Write, _: End) -> Result<(), std::io::Error> {
+        let mut value = self.0;
+        loop {
+            let byte = (value & 0x7f) as u8;
+            value >>= 7;
+            if value == 0 {
+                return writer.write_all(&[byte]);
+            }
+            writer.write_all(&[byte | 0x80])?;
+        }
+    }
+
+    fn into_usize(self) -> usize {
+        self.0 as usize
+    }
+
+    fn from_usize(n: usize) -> Self {
+        Self(n as u64)
+    }
+}
+
+#[cfg(any(feature = "tokio", feature = "futures"))]
+impl crate::AsyncPrimitive for Varint {}
+
+#[test]
+fn test_unsized_primitive_prefix() {
+    let body = vec![b'x'; 300];
+    let mut writer = Muncher::new(Vec::new());
+    writer.write_pref_bytes::<Varint>(None, &body).unwrap();
+    assert_eq!(&writer.inner[..2], [0xac, 0x02]);
+
+    let mut reader = Muncher::new(Cursor::new(writer.inner));
+    assert_eq!(reader.read_pref_bytes::<Varint>(None).unwrap(), body);
+}
+
+#[test]
+fn test_unsized_primitive_invalid() {
+    let mut reader = Muncher::new(Cursor::new([0xff; 11]));
+    let err = reader.read_pref_bytes::<Varint>(None).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+}
+
+#[cfg(feature = "futures")]
+#[test]
+fn test_unsized_primitive_async() {
+    use crate::{AsyncMunchRead, AsyncMunchWrite};
+    use futures::executor::block_on;
+
+    block_on(async {
+        let mut writer = Muncher::new(futures::io::Cursor::new(Vec::new()));
+        writer.write_a(Varint(1 << 20)).await.unwrap();
+        writer
+            .write_pref_bytes_a::<Varint>(None, b"hi")
+            .await
+            .unwrap();
+
+        let bytes = writer.inner.into_inner();
+        assert_eq!(bytes, [0x80, 0x80, 0x40, 0x02, b'h', b'i']);
+
+        let mut reader = Muncher::new(futures::io::Cursor::new(bytes));
+        assert_eq!(reader.read_a::<Varint>().await.unwrap(), Varint(1 << 20));
+        assert_eq!(reader.read_pref_utf8_a::<Varint>(None).await.unwrap(), "hi");
+    });
+}
+
+/// A 20 byte digest, a fixed size [`Primitive`] too big for a stack buffer.
+#[cfg(feature = "futures")]
+#[derive(Debug, PartialEq)]
+struct Digest([u8; 20]);
+
+#[cfg(feature = "futures")]
+impl Primitive for Digest {
+    const SIZE: Option<usize> = Some(20);
+
+    fn read_endian(reader: &mut impl Read, _: End) -> Result<Self, std::io::Error> {
+        let mut bytes = [0; 20];
+        reader.read_exact(&mut bytes)?;
+        Ok(Self(bytes))
+    }
+
+    fn write_endian(self, writer: &mut impl Write, _: End) -> Result<(), std::io::Error> {
+        writer.write_all(&self.0)
+    }
+
+    fn into_usize(self) -> usize {
+        0
+    }
+
+    fn from_usize(_: usize) -> Self {
+        Self([0; 20])
+    }
+}
+
+#[cfg(feature = "futures")]
+impl crate::AsyncPrimitive for Digest {}
+
+#[cfg(feature = "futures")]
+#[test]
+fn test_sized_primitive_async() {
+    use crate::backend::Futures;
+    use crate::{AsyncMunchWrite, AsyncPrimitive};
+    use futures::executor::block_on;
+
+    block_on(async {
+        let mut writer = Muncher::new(futures::io::Cursor::new(Vec::new()));
+        writer.write_a(0x1234_5678u32).await.unwrap();
+        writer.write_a(Digest([7; 20])).await.unwrap();
+
+        let bytes = writer.inner.into_inner();
+        assert_eq!(bytes.len(), 24);
+
+        let mut reader = futures::io::Cursor::new(bytes);
+        let value = u32::read_endian_a::<Futures>(&mut reader, End::Little).await;
+        assert_eq!(value.unwrap(), 0x1234_5678);
+        let value = Digest::read_endian_a::<Futures>(&mut reader, End::Little).await;
+        assert_eq!(value.unwrap(), Digest([7; 20]));
+        let err = u8::read_endian_a::<Futures>(&mut reader, End::Little).await;
+        assert_eq!(err.unwrap_err().kind(), ErrorKind::UnexpectedEof);
+    });
+}
+
+#[test]
+fn test_unsized_primitive_push() {
+    let mut muncher = crate::PushMuncher::new();
+    muncher.feed(&[0x80, 0x80]);
+    assert_eq!(
+        muncher.read::<Varint>().unwrap(),
+        crate::Parsed::Incomplete { needed: 1 }
+    );
+    muncher.feed(&[0x40]);
+    assert_eq!(
+        muncher.read::<Varint>().unwrap(),
+        crate::Parsed::Done(Varint(1 << 20))
+    );
+}
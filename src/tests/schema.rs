@@ -0,0 +1,364 @@
+use crate::pack::Value;
+use crate::schema::Schema;
+use crate::{End, Muncher};
+use std::io::{Cursor, ErrorKind};
+
+fn read(schema: &str, data: &[u8]) -> Value {
+    let schema = Schema::parse(schema).unwrap();
+    Muncher::new(Cursor::new(data))
+        .read_schema(&schema)
+        .unwrap()
+}
+
+fn write(schema: &str, value: &Value) -> Vec<u8> {
+    let schema = Schema::parse(schema).unwrap();
+    let mut data = Vec::new();
+    Muncher::new(&mut data)
+        .write_schema(&schema, value)
+        .unwrap();
+    data
+}
+
+fn record(fields: &[(&str, Value)]) -> Value {
+    Value::Record(
+        fields
+            .iter()
+            .map(|(name, value)| ((*name).to_owned(), value.clone()))
+            .collect(),
+    )
+}
+
+fn roundtrip(schema: &str, data: &[u8]) -> Value {
+    let value = read(schema, data);
+    assert_eq!(write(schema, &value), data);
+    value
+}
+
+#[test]
+fn test_schema_numbers() {
+    let value = roundtrip(
+        "a: u8  b: i16 be  c: u32 le  d: f32 be  e: bool  f: i64",
+        b"\x01\xFF\xFE\x02\0\0\0\x3F\x80\0\0\x01\xFF\xFF\xFF\xFF\xFF\xFF\xFF\xFF",
+    );
+    assert_eq!(
+        value,
+        record(&[
+            ("a", Value::UInt(1)),
+            ("b", Value::Int(-2)),
+            ("c", Value::UInt(2)),
+            ("d", Value::Float(1.0)),
+            ("e", Value::Bool(true)),
+            ("f", Value::Int(-1)),
+        ])
+    );
+}
+
+#[test]
+fn test_schema_default_end() {
+    let schema = Schema::parse("a: u16").unwrap();
+    let mut muncher = Muncher::new(Cursor::new(b"\x01\x02"));
+    muncher.set_end(End::Big);
+    assert_eq!(
+        muncher.read_schema(&schema).unwrap(),
+        record(&[("a", Value::UInt(0x102))])
+    );
+
+    // The statement applies to the rest of the record
+    assert_eq!(
+        read("a: u16 endian big b: u16 c: u16 le", b"\x01\0\0\x01\x01\0"),
+        record(&[
+            ("a", Value::UInt(1)),
+            ("b", Value::UInt(1)),
+            ("c", Value::UInt(1)),
+        ])
+    );
+}
+
+#[test]
+fn test_schema_strings() {
+    let value = roundtrip(
+        r#"
+        fixed: utf8[3]
+        len: u8
+        by_field: bytes[len]
+        prefixed: utf8[pref u16 be]
+        terminated: utf8[cstr]
+        "#,
+        b"abc\x02\x01\x02\x00\x02hiyo\0",
+    );
+    assert_eq!(
+        value,
+        record(&[
+            ("fixed", Value::Str("abc".to_owned())),
+            ("len", Value::UInt(2)),
+            ("by_field", Value::Bytes(vec![1, 2])),
+            ("prefixed", Value::Str("hi".to_owned())),
+            ("terminated", Value::Str("yo".to_owned())),
+        ])
+    );
+}
+
+#[cfg(feature = "ucs2")]
+#[test]
+fn test_schema_ucs2() {
+    let value = roundtrip("name: ucs2[pref u8]", b"\x02\0h\0i");
+    assert_eq!(value, record(&[("name", Value::Str("hi".to_owned()))]));
+}
+
+#[cfg(feature = "mutf8")]
+#[test]
+fn test_schema_mutf8() {
+    let value = roundtrip("name: mutf8[pref u8]", b"\x03a\xC0\x80");
+    assert_eq!(value, record(&[("name", Value::Str("a\0".to_owned()))]));
+}
+
+#[test]
+fn test_schema_arrays_and_records() {
+    let value = roundtrip(
+        r#"
+        endian big
+        count: u8
+        points: {
+            x: i16
+            tags: u8[pref u8]
+        }[count]
+        fixed: u16[2]
+        names: utf8[pref u8][2]
+        "#,
+        b"\x02\xFF\xFF\x01\x07\x00\x05\x00\x00\x01\x00\x02\x01a\x02bc",
+    );
+    assert_eq!(
+        value.get("points"),
+        Some(&Value::List(vec![
+            record(&[
+                ("x", Value::Int(-1)),
+                ("tags", Value::List(vec![Value::UInt(7)])),
+            ]),
+            record(&[("x", Value::Int(5)), ("tags", Value::List(vec![]))]),
+        ]))
+    );
+    assert_eq!(
+        value.get("fixed"),
+        Some(&Value::List(vec![Value::UInt(1), Value::UInt(2)]))
+    );
+    assert_eq!(
+        value.get("names"),
+        Some(&Value::List(vec![
+            Value::Str("a".to_owned()),
+            Value::Str("bc".to_owned())
+        ]))
+    );
+}
+
+#[test]
+fn test_schema_outer_fields() {
+    // Fields of the records around can be referred to
+    let value = roundtrip("len: u8  inner: { data: bytes[len] }", b"\x02ab");
+    assert_eq!(
+        value.get("inner").and_then(|v| v.get("data")),
+        Some(&Value::Bytes(b"ab".to_vec()))
+    );
+}
+
+#[test]
+fn test_schema_conditions() {
+    let schema = r#"
+        magic: bytes[4]
+        version: u8
+        flags: u8
+        extra: u16 le if version >= 2
+        named: utf8[pref u8] if flags & 0x01
+        long: u32 le if magic == "LONG"
+        short: u8 if magic != "LONG"
+    "#;
+    let value = roundtrip(schema, b"LONG\x02\x01\x05\x00\x02hi\x07\0\0\0");
+    assert_eq!(value.get("extra"), Some(&Value::UInt(5)));
+    assert_eq!(value.get("named"), Some(&Value::Str("hi".to_owned())));
+    assert_eq!(value.get("long"), Some(&Value::UInt(7)));
+    assert_eq!(value.get("short"), None);
+
+    let value = roundtrip(schema, b"SHRT\x01\x00\x09");
+    assert_eq!(value.get("extra"), None);
+    assert_eq!(value.get("named"), None);
+    assert_eq!(value.get("short"), Some(&Value::UInt(9)));
+
+    let value = read("present: bool  x: u8 if present", b"\x00");
+    assert_eq!(value.get("x"), None);
+}
+
+#[test]
+fn test_schema_trace_labels() {
+    let schema = Schema::parse("a: u8  b: { c: u8 }").unwrap();
+    let mut muncher = Muncher::new(Cursor::new(b"\x01\x02"));
+    muncher.start_trace();
+    muncher.read_schema(&schema).unwrap();
+
+    let labels: Vec<_> = muncher
+        .trace()
+        .unwrap()
+        .events()
+        .iter()
+        .map(|e| e.label.as_str())
+        .collect();
+    assert_eq!(labels, ["a", "b.c"]);
+}
+
+#[test]
+fn test_schema_read_errors() {
+    let schema = Schema::parse("count: u8  items: { x: u16 }[count]").unwrap();
+    let err = Muncher::new(Cursor::new(b"\x02\x01\x00\x02"))
+        .read_schema(&schema)
+        .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+    assert!(err.to_string().starts_with("items: x: "), "{err}");
+
+    // A length from a field whose condition was false
+    let schema = Schema::parse("a: u8  len: u8 if a  data: bytes[len]").unwrap();
+    let err = Muncher::new(Cursor::new(b"\x00"))
+        .read_schema(&schema)
+        .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+
+    // A huge count with nothing to read for each element
+    let schema = Schema::parse("count: u64 le  items: {}[count]").unwrap();
+    let mut muncher = Muncher::new(Cursor::new([0xFF; 8]));
+    muncher.set_max_alloc(1024);
+    assert!(muncher.read_schema(&schema).is_err());
+}
+
+#[test]
+fn test_schema_write_errors() {
+    let schema = Schema::parse("len: u8  data: bytes[len]  tag: utf8[2]").unwrap();
+    let err = |value: &Value| {
+        let mut data = Vec::new();
+        let err = Muncher::new(&mut data)
+            .write_schema(&schema, value)
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+        err.to_string()
+    };
+
+    let ok = [
+        ("len", Value::UInt(1)),
+        ("data", Value::Bytes(vec![0])),
+        ("tag", Value::Str("ab".to_owned())),
+    ];
+    assert_eq!(
+        write("len: u8  data: bytes[len]  tag: utf8[2]", &record(&ok)),
+        b"\x01\0ab"
+    );
+
+    assert!(err(&record(&ok[..2])).starts_with("tag: missing field"));
+    assert!(
+        err(&record(&[
+            ok[0].clone(),
+            ("data", Value::Bytes(vec![])),
+            ok[2].clone()
+        ]))
+        .starts_with("data: ")
+    );
+    assert!(
+        err(&record(&[
+            ok[0].clone(),
+            ok[1].clone(),
+            ("tag", Value::Str("abc".to_owned()))
+        ]))
+        .starts_with("tag: ")
+    );
+    assert!(
+        err(&record(&[
+            ("len", Value::UInt(256)),
+            ok[1].clone(),
+            ok[2].clone()
+        ]))
+        .starts_with("len: ")
+    );
+    assert!(
+        err(&record(&[
+            ("len", Value::Str("1".to_owned())),
+            ok[1].clone(),
+            ok[2].clone()
+        ]))
+        .starts_with("len: ")
+    );
+    assert!(err(&Value::UInt(1)).starts_with("expected a record"));
+}
+
+#[test]
+fn test_schema_parse_errors() {
+    let err = |text: &str| {
+        let err = Schema::parse(text).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+        err.to_string()
+    };
+    assert_eq!(err("a: u8\nb: u7"), "schema line 2: unknown type `u7`");
+    assert_eq!(
+        err("data: bytes[len]"),
+        "schema line 1: unknown field `len` (fields can only refer to earlier fields)"
+    );
+    assert_eq!(err("a: u8 a: u8"), "schema line 1: duplicate field `a`");
+    assert!(err("name: utf8").contains("needs a length"));
+    assert!(err("a: u8[cstr]").contains("null terminated"));
+    assert!(err("a: bytes[pref f32]").contains("length prefix"));
+    assert!(err("a: { b: u8").contains("expected `}`"));
+    assert!(err("a: u8 }").contains("unexpected `}`"));
+    assert!(err("a u8").contains("expected `:`"));
+    assert!(err("a: u8 if b").contains("unknown field `b`"));
+    assert!(err("a: u8 b: u8 if a == ").contains("end of schema"));
+    assert!(err("a: utf8[\"x\"]").contains("expected a length"));
+    assert!(err(&"a: {".repeat(100)).contains("nested too deep"));
+    #[cfg(not(feature = "ucs2"))]
+    assert!(err("a: ucs2[2]").contains("crate feature"));
+}
+
+#[test]
+fn test_schema_comments_and_names() {
+    // `endian` and `le` are also fine as field names
+    let value = read(
+        "# A comment\nendian: u8 # the byte order\nle: u8, be: u8",
+        b"\x01\x02\x03",
+    );
+    assert_eq!(
+        value,
+        record(&[
+            ("endian", Value::UInt(1)),
+            ("le", Value::UInt(2)),
+            ("be", Value::UInt(3)),
+        ])
+    );
+}
+
+#[test]
+fn test_schema_from_specs() {
+    let schema = Schema::from_specs(
+        "u32le magic, pref_u16be_utf8 name, cstr_bytes raw, fixed2_utf8 tag, i16be[2] xs, bool ok,",
+    )
+    .unwrap();
+    let data = b"\x01\0\0\0\0\x02hiab\0yo\xFF\xFF\0\x01\x01";
+    let value = Muncher::new(Cursor::new(data))
+        .read_schema(&schema)
+        .unwrap();
+    assert_eq!(
+        value,
+        record(&[
+            ("magic", Value::UInt(1)),
+            ("name", Value::Str("hi".to_owned())),
+            ("raw", Value::Bytes(b"ab".to_vec())),
+            ("tag", Value::Str("yo".to_owned())),
+            ("xs", Value::List(vec![Value::Int(-1), Value::Int(1)])),
+            ("ok", Value::Bool(true)),
+        ])
+    );
+
+    let err = |specs: &str| {
+        let err = Schema::from_specs(specs).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+        err.to_string()
+    };
+    assert_eq!(err("u8 a, u33 b"), "field spec 2 (`u33 b`): unknown type");
+    assert!(err("u8").contains("expected a type and a name"));
+    assert!(err("u8 a b").contains("expected a type and a name"));
+    assert!(err("pref_f32_utf8 a").contains("length prefix"));
+    assert!(err("fixedx_utf8 a").contains("unknown type"));
+    assert!(err("u8 a, u8 a").contains("duplicate field"));
+}
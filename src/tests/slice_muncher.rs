@@ -0,0 +1,129 @@
+use crate::{BigEndian, End, SliceMuncher};
+use std::io::{ErrorKind, SeekFrom};
+
+#[test]
+fn test_slice_primitives() {
+    let data = [0x34, 0x12, 0x12, 0x34, 0x01];
+    let mut muncher = SliceMuncher::new(&data);
+    assert_eq!(muncher.read::<u16>().unwrap(), 0x1234);
+    assert_eq!(muncher.peek::<u16>(End::Big).unwrap(), 0x1234);
+    assert_eq!(muncher.read_be::<u16>().unwrap(), 0x1234);
+    assert_eq!(muncher.peek_bytes(4), [0x01]);
+    assert_eq!(muncher.read_le::<u8>().unwrap(), 1);
+    assert!(muncher.is_eof());
+
+    let err = muncher.read_le::<u8>().unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+}
+
+#[test]
+fn test_slice_borrowed_strings() {
+    let data = b"\x05Hello\x00\x02hiworld\0line\nrest";
+    let mut muncher = SliceMuncher::with_order(data, BigEndian);
+
+    let hello: &str = muncher.read_pref_utf8::<u8>(None).unwrap();
+    assert_eq!(hello, "Hello");
+    // Borrowed straight from the input
+    assert_eq!(hello.as_ptr(), data[1..].as_ptr());
+
+    assert_eq!(muncher.read_pref_bytes::<u16>(None).unwrap(), b"hi");
+    assert_eq!(muncher.read_cstr_utf8().unwrap(), "world");
+    assert_eq!(muncher.read_delim_bytes(b'\n').unwrap(), b"line\n");
+    assert_eq!(muncher.read_delim_bytes(b'\n').unwrap(), b"rest");
+    assert!(muncher.is_eof());
+}
+
+#[test]
+fn test_slice_failed_read_keeps_position() {
+    let data = b"\x06Hi\x02\xff\xfe";
+    let mut muncher = SliceMuncher::new(data);
+
+    let err = muncher.read_pref_bytes::<u8>(None).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+    assert_eq!(muncher.position(), 0);
+
+    muncher.skip(3).unwrap();
+    let err = muncher.read_pref_utf8::<u8>(None).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+    assert_eq!(muncher.position(), 3);
+
+    let err = muncher.read_cstr_bytes().unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+    assert_eq!(muncher.remaining(), b"\x02\xff\xfe");
+}
+
+#[test]
+fn test_slice_seek() {
+    let data = [1, 2, 3, 4, 5];
+    let mut muncher = SliceMuncher::new(&data);
+
+    assert_eq!(muncher.seek(SeekFrom::End(-2)).unwrap(), 3);
+    assert_eq!(muncher.read::<u8>().unwrap(), 4);
+    assert_eq!(muncher.seek(SeekFrom::Current(-3)).unwrap(), 1);
+    assert_eq!(muncher.read::<u8>().unwrap(), 2);
+    muncher.set_position(0).unwrap();
+    assert_eq!(muncher.read::<u8>().unwrap(), 1);
+
+    for pos in [SeekFrom::Start(6), SeekFrom::Current(-3), SeekFrom::End(1)] {
+        let err = muncher.seek(pos).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+    }
+    assert_eq!(muncher.position(), 1);
+}
+
+#[test]
+fn test_slice_sub() {
+    let data = b"\x03\x00abc\x01\x02rest";
+    let mut muncher = SliceMuncher::new(data);
+
+    let mut section = muncher.read_sub::<u16>(None).unwrap();
+    assert_eq!(section.read_fixed_utf8(2).unwrap(), "ab");
+    let err = section.read_fixed_bytes(2).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+
+    let mut section = muncher.read_fixed_sub(2).unwrap();
+    assert_eq!(section.read_be::<u16>().unwrap(), 0x0102);
+    assert_eq!(muncher.remaining(), b"rest");
+
+    let mut table = muncher.sub(2..5).unwrap();
+    assert_eq!(table.as_slice(), b"abc");
+    assert_eq!(
+        table.read_cstr_bytes().unwrap_err().kind(),
+        ErrorKind::UnexpectedEof
+    );
+    assert!(muncher.sub(8..13).is_err());
+    assert_eq!(muncher.position(), 7);
+}
+
+#[cfg(feature = "ucs2")]
+#[test]
+fn test_slice_ucs2() {
+    let data = [0x02, 0x00, 0x48, 0x00, 0x69, 0xff, 0x00];
+    let mut muncher = SliceMuncher::new(&data);
+    assert_eq!(muncher.read_pref_ucs2::<u8>(None).unwrap(), "Hi");
+    assert_eq!(
+        muncher.read_fixed_ucs2(0).unwrap(),
+        std::borrow::Cow::Borrowed("")
+    );
+
+    // Forged length: fails without moving
+    let err = muncher.read_pref_ucs2::<u8>(None).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+    assert_eq!(muncher.position(), 5);
+}
+
+#[cfg(feature = "mutf8")]
+#[test]
+fn test_slice_mutf8() {
+    let data = b"\x02hi\xc0\x80a\0";
+    let mut muncher = SliceMuncher::new(data);
+
+    // Plain ASCII is borrowed
+    let hi = muncher.read_pref_mutf8::<u8>(None).unwrap();
+    assert!(matches!(hi, std::borrow::Cow::Borrowed("hi")));
+
+    // An encoded null needs converting
+    let s = muncher.read_cstr_mutf8().unwrap();
+    assert!(matches!(s, std::borrow::Cow::Owned(_)));
+    assert_eq!(s, "\0a");
+}
@@ -628,6 +628,40 @@ mod ucs2_tests {
 
         assert_eq!(result, text);
     }
+
+    #[test]
+    fn test_ucs2_large() {
+        // Spans multiple read chunks, with a non-ASCII char on every boundary
+        let text: String = (0..20_000)
+            .map(|i| if i % 1024 == 1023 { '世' } else { 'a' })
+            .collect();
+        let mut buffer = Vec::new();
+        Muncher::new(&mut buffer)
+            .write_pref_ucs2::<u32>(End::Big, &text)
+            .unwrap();
+
+        let mut reader = Muncher::new(Cursor::new(buffer));
+        let result = reader.read_pref_ucs2::<u32>(End::Big).unwrap();
+        assert_eq!(result, text);
+    }
+
+    #[test]
+    fn test_read_fixed_ucs2_insufficient() {
+        let data = [0, b'h', 0, b'i'];
+        let mut reader = Muncher::new(Cursor::new(data));
+
+        let result = reader.read_fixed_ucs2(3);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_read_fixed_ucs2_too_large() {
+        let mut reader = Muncher::new(Cursor::new([]));
+        reader.set_max_alloc(16);
+
+        let result = reader.read_fixed_ucs2(usize::MAX);
+        assert!(result.is_err());
+    }
 }
 
 #[cfg(feature = "mutf8")]
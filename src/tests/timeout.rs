@@ -0,0 +1,148 @@
+use crate::{AsyncMunchBufRead, AsyncMunchRead, End, Muncher};
+use std::io::ErrorKind;
+use std::time::Duration;
+use tokio::io::{AsyncWriteExt, BufReader, DuplexStream};
+use tokio::time::{Instant, sleep};
+
+/// A [`Muncher`] with a read timeout of 5 seconds,
+/// and the other end of its stream.
+fn stream() -> (DuplexStream, Muncher<BufReader<DuplexStream>>) {
+    let (peer, stream) = tokio::io::duplex(64);
+    let mut muncher = Muncher::new(BufReader::new(stream));
+    muncher
+        .set_read_timeout(Some(Duration::from_secs(5)))
+        .set_end(End::Big);
+    (peer, muncher)
+}
+
+/// Writes each of `parts`, waiting `delay` before each of them.
+fn trickle(mut peer: DuplexStream, delay: Duration, parts: &'static [&'static [u8]]) {
+    tokio::spawn(async move {
+        for part in parts {
+            sleep(delay).await;
+            peer.write_all(part).await.unwrap();
+        }
+    });
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_timeout_stall_mid_value() {
+    let (mut peer, mut muncher) = stream();
+    peer.write_all(b"\x00\x01\x05he").await.unwrap();
+
+    assert_eq!(muncher.read_a::<u16>().await.unwrap(), 1);
+    // Doesn't change the offsets in errors
+    muncher.reset_budget_usage();
+
+    let start = Instant::now();
+    let err = muncher.read_pref_utf8_a::<u8>(None).await.unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::TimedOut);
+    assert_eq!(
+        err.to_string(),
+        "read timed out at offset 2 (3 bytes of the value had arrived, 3 of them during the read)"
+    );
+    assert_eq!(start.elapsed(), Duration::from_secs(5));
+
+    // The bytes that arrived are kept for the next read
+    peer.write_all(b"llo").await.unwrap();
+    assert_eq!(muncher.read_pref_utf8_a::<u8>(None).await.unwrap(), "hello");
+    assert_eq!(muncher.position(), 8);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_timeout_slow_but_in_time() {
+    let (peer, mut muncher) = stream();
+    trickle(peer, Duration::from_secs(1), &[b"\x00", b"\x03a", b"bc"]);
+    assert_eq!(
+        muncher.read_pref_bytes_a::<u16>(None).await.unwrap(),
+        b"abc"
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_timeout_whole_value() {
+    let (peer, mut muncher) = stream();
+    // Each part is in time, but not the whole value
+    trickle(peer, Duration::from_secs(2), &[b"\x00", b"\x03a", b"bc"]);
+
+    let err = muncher.read_pref_bytes_a::<u16>(None).await.unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::TimedOut);
+    assert_eq!(
+        muncher.read_pref_bytes_a::<u16>(None).await.unwrap(),
+        b"abc"
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_timeout_per_read() {
+    let (peer, mut muncher) = stream();
+    trickle(
+        peer,
+        Duration::from_secs(3),
+        &[b"\x00\x01", b"\x00\x02", b"\x00\x03"],
+    );
+
+    // 9 seconds in total, but each read takes 3
+    for expected in 1..=3 {
+        assert_eq!(muncher.read_a::<u16>().await.unwrap(), expected);
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_timeout_cstr_and_array() {
+    let (mut peer, mut muncher) = stream();
+    peer.write_all(b"abc").await.unwrap();
+
+    let err = muncher.read_cstr_utf8_a().await.unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::TimedOut);
+
+    peer.write_all(b"\0\x00\x01").await.unwrap();
+    assert_eq!(muncher.read_cstr_utf8_a().await.unwrap(), "abc");
+
+    let mut out = [0u16; 2];
+    let err = muncher.read_into_m_a(&mut out, End::Big).await.unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::TimedOut);
+    assert_eq!(
+        err.to_string(),
+        "read timed out at offset 4 (2 bytes of the value had arrived, 0 of them during the read)"
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_deadline() {
+    let (peer, mut muncher) = stream();
+    muncher.set_read_deadline(Some(Instant::now() + Duration::from_secs(4)));
+    assert_eq!(muncher.read_timeout(), Some(Duration::from_secs(5)));
+    trickle(peer, Duration::from_secs(3), &[b"\x00\x01", b"\x00\x02"]);
+
+    // Both reads are within the timeout, but not the deadline
+    assert_eq!(muncher.read_a::<u16>().await.unwrap(), 1);
+    let start = Instant::now();
+    let err = muncher.read_a::<u16>().await.unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::TimedOut);
+    assert_eq!(start.elapsed(), Duration::from_secs(1));
+
+    muncher.set_read_deadline(None);
+    assert_eq!(muncher.read_a::<u16>().await.unwrap(), 2);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_timeout_not_between_values() {
+    let (peer, mut muncher) = stream();
+    trickle(peer, Duration::from_secs(60), &[b"\x01", b"\x02"]);
+
+    assert_eq!(muncher.try_read_a::<u8>().await.unwrap(), Some(1));
+    assert_eq!(muncher.try_read_a::<u8>().await.unwrap(), Some(2));
+    assert_eq!(muncher.try_read_a::<u8>().await.unwrap(), None);
+}
+
+#[tokio::test]
+async fn test_no_timeout_by_default() {
+    let (mut peer, stream) = tokio::io::duplex(64);
+    let mut muncher = Muncher::new(stream);
+    assert_eq!(muncher.read_timeout(), None);
+    assert_eq!(muncher.read_deadline(), None);
+
+    peer.write_all(b"\x02\x00").await.unwrap();
+    assert_eq!(muncher.read_a::<u16>().await.unwrap(), 2);
+}
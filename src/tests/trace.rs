@@ -0,0 +1,238 @@
+use crate::trace::{TraceEvent, TraceKind};
+use crate::{End, Muncher};
+use std::io::{BufReader, Cursor, Read};
+use std::sync::{Arc, Mutex};
+
+#[test]
+fn test_trace_events() {
+    let mut muncher = Muncher::new(Cursor::new(b"\x00\x01\x05hello\x01\x02"));
+    muncher.set_end(End::Big);
+    muncher.start_trace();
+
+    assert_eq!(muncher.read::<u16>().unwrap(), 1);
+    assert_eq!(muncher.read_pref_utf8::<u8>(None).unwrap(), "hello");
+    assert!(muncher.read::<u32>().is_err());
+
+    let trace = muncher.stop_trace().unwrap();
+    let events = trace.events();
+    assert_eq!(events.len(), 3);
+    assert_eq!(
+        events[0],
+        TraceEvent {
+            kind: TraceKind::Read,
+            op: "read_m",
+            offset: 0,
+            bytes: vec![0, 1],
+            value: Some("1".to_owned()),
+            error: None,
+            label: String::new(),
+        }
+    );
+    assert_eq!(events[1].op, "read_pref_utf8");
+    assert_eq!(events[1].offset, 2);
+    assert_eq!(events[1].bytes, b"\x05hello");
+    assert_eq!(events[1].value.as_deref(), Some("\"hello\""));
+    assert_eq!(events[2].offset, 8);
+    assert_eq!(events[2].bytes, b"\x01\x02");
+    assert!(events[2].value.is_none());
+    assert!(events[2].error.is_some());
+    assert_eq!(trace.read_bytes(), b"\x00\x01\x05hello\x01\x02");
+
+    // Not traced anymore
+    assert!(muncher.trace().is_none());
+}
+
+#[test]
+fn test_trace_starts_at_offset() {
+    let mut muncher = Muncher::new(Cursor::new(b"\xAA\xBB\x07"));
+    muncher.read::<u16>().unwrap();
+    muncher.start_trace();
+    muncher.read::<u8>().unwrap();
+
+    let trace = muncher.trace().unwrap();
+    assert_eq!(trace.events()[0].offset, 2);
+    assert_eq!(trace.read_bytes(), b"\x07");
+    assert_eq!(
+        trace.hex_dump(),
+        "00000002  07                                               read_m = 7\n"
+    );
+}
+
+#[test]
+fn test_trace_labels() {
+    let mut muncher = Muncher::new(Cursor::new(b"\x01\x02\x03"));
+    muncher.start_trace();
+
+    muncher.read::<u8>().unwrap();
+    {
+        let mut header = muncher.trace_label("header");
+        header.read::<u8>().unwrap();
+        header.trace_label("flags").read::<u8>().unwrap();
+    }
+
+    let labels: Vec<_> = muncher
+        .trace()
+        .unwrap()
+        .events()
+        .iter()
+        .map(|e| e.label.as_str())
+        .collect();
+    assert_eq!(labels, ["", "header", "header.flags"]);
+}
+
+#[test]
+fn test_trace_nested_reads() {
+    let mut muncher = Muncher::new(BufReader::new(Cursor::new(b"hi\0\x01\x02")));
+    muncher.start_trace();
+
+    // Made of other traced reads, but only traced once
+    assert_eq!(muncher.read_cstr_utf8().unwrap(), "hi");
+    assert_eq!(muncher.read_array_m::<u8>(2, End::Little).unwrap(), [1, 2]);
+
+    let trace = muncher.trace().unwrap();
+    let ops: Vec<_> = trace.events().iter().map(|e| e.op).collect();
+    assert_eq!(ops, ["read_cstr_utf8", "read_array_m"]);
+    assert_eq!(trace.events()[0].bytes, b"hi\0");
+    assert_eq!(trace.events()[1].value.as_deref(), Some("[1, 2]"));
+}
+
+#[test]
+fn test_trace_writes() {
+    let mut data = Vec::new();
+    let mut muncher = Muncher::new(&mut data);
+    muncher.start_trace();
+
+    muncher.write_be::<u16>(0x102).unwrap();
+    muncher.write_pref_bytes::<u8>(None, b"ab").unwrap();
+    muncher.write_slice_m::<u8>(&[7, 8], End::Little).unwrap();
+
+    let trace = muncher.stop_trace().unwrap();
+    let events: Vec<_> = trace.events().iter().map(|e| e.to_string()).collect();
+    assert_eq!(
+        events,
+        [
+            "00000000 write_m [01 02] = 258",
+            "00000002 write_pref_bytes [02 61 62] = b\"ab\"",
+            "00000005 write_slice_m [07 08] = [7, 8]",
+        ]
+    );
+    assert!(trace.events().iter().all(|e| e.kind == TraceKind::Write));
+    assert_eq!(trace.written_bytes(), data);
+}
+
+#[test]
+fn test_trace_hex_dump() {
+    let mut input = vec![0x00, 0x01, 0x12];
+    input.extend_from_slice(b"a long enough name");
+    input.extend_from_slice(&[0xFF, 0xFE]);
+    let mut muncher = Muncher::new(Cursor::new(input));
+    muncher.set_end(End::Big);
+    muncher.start_trace();
+
+    muncher.read::<u16>().unwrap();
+    muncher
+        .trace_label("name")
+        .read_pref_utf8::<u8>(None)
+        .unwrap();
+    let mut raw = [0; 2];
+    muncher.read_exact(&mut raw).unwrap();
+
+    assert_eq!(
+        muncher.trace().unwrap().hex_dump(),
+        "\
+00000000  00 01                                            read_m = 1
+00000002  12 61 20 6c 6f 6e 67 20 65 6e 6f 75 67 68 20 6e  name: read_pref_utf8 = \"a long enough name\"
+00000012  61 6d 65
+00000015  ff fe                                            (untraced)
+"
+    );
+}
+
+#[test]
+fn test_trace_try_parse_rollback() {
+    let mut muncher = Muncher::new(Cursor::new(b"\x01\x02"));
+    muncher.start_trace();
+
+    let failed: Result<u32, _> = muncher.try_parse(|m| {
+        m.read::<u8>()?;
+        m.read::<u32>()
+    });
+    assert!(failed.is_err());
+    assert!(muncher.trace().unwrap().events().is_empty());
+
+    assert_eq!(muncher.read::<u8>().unwrap(), 1);
+    let trace = muncher.trace().unwrap();
+    assert_eq!(trace.events().len(), 1);
+    assert_eq!(trace.events()[0].offset, 0);
+    assert_eq!(trace.read_bytes(), b"\x01");
+}
+
+#[test]
+fn test_trace_sink() {
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let mut muncher = Muncher::new(Cursor::new(b"\x05\x06"));
+    let sink = seen.clone();
+    muncher.start_trace_to(move |event: &TraceEvent| {
+        sink.lock().unwrap().push(event.to_string());
+    });
+
+    muncher.read::<u8>().unwrap();
+    muncher.trace_label("second").read::<u8>().unwrap();
+
+    assert_eq!(
+        *seen.lock().unwrap(),
+        [
+            "00000000 read_m [05] = 5",
+            "00000001 read_m [06] = 6 (second)"
+        ]
+    );
+}
+
+#[test]
+fn test_trace_off() {
+    let mut muncher = Muncher::new(Cursor::new(b"\x05"));
+    // Labels do nothing when not tracing
+    muncher.trace_label("unused").read::<u8>().unwrap();
+    assert!(muncher.trace().is_none());
+    assert!(muncher.stop_trace().is_none());
+}
+
+#[cfg(feature = "futures")]
+#[test]
+fn test_trace_async() {
+    use crate::{AsyncMunchBufRead, AsyncMunchRead, AsyncMunchWrite};
+
+    futures::executor::block_on(async {
+        let mut muncher = Muncher::new(futures::io::Cursor::new(&b"\x02\x00\x03abcdef\0"[..]));
+        muncher.start_trace();
+
+        assert_eq!(muncher.read_le_a::<u16>().await.unwrap(), 2);
+        assert_eq!(muncher.read_pref_bytes_a::<u8>(None).await.unwrap(), b"abc");
+        assert_eq!(muncher.read_cstr_utf8_a().await.unwrap(), "def");
+
+        let trace = muncher.stop_trace().unwrap();
+        let events: Vec<_> = trace.events().iter().map(|e| e.to_string()).collect();
+        assert_eq!(
+            events,
+            [
+                "00000000 read_m_a [02 00] = 2",
+                "00000002 read_pref_bytes_a [03 61 62 63] = b\"abc\"",
+                "00000006 read_cstr_utf8_a [64 65 66 00] = \"def\"",
+            ]
+        );
+
+        let mut muncher = Muncher::new(futures::io::Cursor::new(Vec::new()));
+        muncher.start_trace();
+        muncher.write_be_a::<u16>(7).await.unwrap();
+        muncher.write_cstr_bytes_a(b"x").await.unwrap();
+        let trace = muncher.stop_trace().unwrap();
+        assert_eq!(
+            trace.events()[0].to_string(),
+            "00000000 write_m_a [00 07] = 7"
+        );
+        assert_eq!(
+            trace.events()[1].to_string(),
+            "00000002 write_cstr_bytes_a [78 00] = b\"x\""
+        );
+    });
+}
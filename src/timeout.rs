@@ -0,0 +1,125 @@
+use std::io::{Error, ErrorKind};
+use std::time::Duration;
+
+use tokio::time::Instant;
+
+use crate::{ByteOrder, Muncher};
+
+/// Time limits on the async reads of a [`Muncher`]
+/// (see [`Muncher::set_read_timeout`] and [`Muncher::set_read_deadline`]).
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct Timeouts {
+    /// Max time for a single read.
+    pub timeout: Option<Duration>,
+    /// Time by which every read must be done.
+    pub deadline: Option<Instant>,
+    /// When the read in progress must be done by (the earliest of the two).
+    pub current: Option<Instant>,
+    /// How many bytes were already in the look-ahead buffer
+    /// when the read in progress started.
+    pub buffered: usize,
+}
+
+/// **Async read timeouts** (crate feature: `tokio`)
+impl<T, O: ByteOrder> Muncher<T, O> {
+    /// Sets the max time that a single async read (of a primitive,
+    /// string, array, ...) may take, from when it starts until
+    /// the whole value has arrived.
+    ///
+    /// Default: `None` (no timeout)
+    ///
+    /// This stops a peer that stalls in the middle of a value
+    /// (say, after sending the length of a string) from hanging your parser.
+    /// Only the reads of a `tokio` reader are timed (using [`tokio::time`],
+    /// so they must run inside a `tokio` runtime with time enabled).
+    ///
+    /// A read that runs out of time fails with [`ErrorKind::TimedOut`],
+    /// reporting the offset it started at (see [`Muncher::position`])
+    /// and how many bytes of the value had arrived, in total
+    /// (including any that were already buffered, say by a peek)
+    /// and while it waited.
+    /// Those bytes aren't lost (the reads are cancellation safe):
+    /// reading again continues where it stopped.
+    ///
+    /// Reads made of several values (like
+    /// [`crate::AsyncMunchRead::read_pref_vec_a`]) time each value separately,
+    /// and checking for the end of input ([`crate::AsyncMunchBufRead::is_eof_a`])
+    /// isn't timed, so waiting between messages is fine.
+    /// To limit a whole message, see [`Muncher::set_read_deadline`].
+    ///
+    /// ```
+    /// # tokio::runtime::Builder::new_current_thread().enable_time().start_paused(true).build().unwrap().block_on(async {
+    /// use bytemuncher::{AsyncMunchRead, End, Muncher};
+    /// use std::io::ErrorKind;
+    /// use std::time::Duration;
+    ///
+    /// // The peer sends a length of 5, then only 2 bytes.
+    /// let (mut peer, stream) = tokio::io::duplex(64);
+    /// tokio::io::AsyncWriteExt::write_all(&mut peer, b"\x05he").await.unwrap();
+    ///
+    /// let mut muncher = Muncher::new(stream);
+    /// muncher.set_read_timeout(Some(Duration::from_secs(5)));
+    ///
+    /// let err = muncher.read_pref_utf8_a::<u8>(End::Big).await.unwrap_err();
+    /// assert_eq!(err.kind(), ErrorKind::TimedOut);
+    /// # });
+    /// ```
+    pub fn set_read_timeout(&mut self, timeout: Option<Duration>) -> &mut Self {
+        self.timeouts.timeout = timeout;
+        self
+    }
+
+    /// Sets a point in time by which every async read must be done,
+    /// for example to limit the time spent on a whole message.
+    ///
+    /// Default: `None` (no deadline)
+    ///
+    /// Reads that are still going at `deadline` (or start after it
+    /// and have to wait) fail with [`ErrorKind::TimedOut`].
+    /// If [`Muncher::set_read_timeout`] is also set, whichever ends first applies.
+    /// See [`Muncher::set_read_timeout`] for more info.
+    pub fn set_read_deadline(&mut self, deadline: Option<Instant>) -> &mut Self {
+        self.timeouts.deadline = deadline;
+        self
+    }
+
+    /// Returns the timeout of a single async read
+    /// (see [`Muncher::set_read_timeout`]).
+    #[must_use]
+    pub fn read_timeout(&self) -> Option<Duration> {
+        self.timeouts.timeout
+    }
+
+    /// Returns the deadline of the async reads
+    /// (see [`Muncher::set_read_deadline`]).
+    #[must_use]
+    pub fn read_deadline(&self) -> Option<Instant> {
+        self.timeouts.deadline
+    }
+
+    /// Starts the clock for a new read.
+    pub(crate) fn begin_read(&mut self) {
+        let timeout = self
+            .timeouts
+            .timeout
+            .and_then(|timeout| Instant::now().checked_add(timeout));
+        self.timeouts.current = match (timeout, self.timeouts.deadline) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+        self.timeouts.buffered = self.lookahead.len();
+    }
+
+    /// The error for a read that ran out of time.
+    pub(crate) fn timed_out(&self) -> Error {
+        Error::new(
+            ErrorKind::TimedOut,
+            format!(
+                "read timed out at offset {} ({} bytes of the value had arrived, {} of them during the read)",
+                self.position(),
+                self.lookahead.len(),
+                self.lookahead.len().saturating_sub(self.timeouts.buffered)
+            ),
+        )
+    }
+}
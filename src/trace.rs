@@ -0,0 +1,516 @@
+//! Tracing of the reads and writes of a [`Muncher`] (see [`Muncher::start_trace`]),
+//! to find out what was read where when a parser misbehaves.
+//!
+//! ```
+//! # use bytemuncher::{End, Muncher};
+//! # use std::io::Cursor;
+//! let mut muncher = Muncher::new(Cursor::new(b"\x00\x01\x05hello"));
+//! muncher.start_trace();
+//!
+//! let version = muncher.read_be::<u16>().unwrap();
+//! let name = muncher.trace_label("name").read_pref_utf8::<u8>(None).unwrap();
+//!
+//! let trace = muncher.stop_trace().unwrap();
+//! assert_eq!(trace.events()[1].to_string(), r#"00000002 read_pref_utf8 [05 68 65 6c 6c 6f] = "hello" (name)"#);
+//! print!("{}", trace.hex_dump());
+//! // 00000000  00 01                                            read_m = 1
+//! // 00000002  05 68 65 6c 6c 6f                                name: read_pref_utf8 = "hello"
+//! ```
+
+use std::fmt::{self, Display, Write as _};
+use std::ops::{Deref, DerefMut};
+
+use crate::{ByteOrder, End, Muncher, Primitive};
+
+/// Whether a [`TraceEvent`] is a read or a write.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TraceKind {
+    /// Bytes read from the input.
+    Read,
+    /// Bytes written to the output.
+    Write,
+}
+
+/// One traced read or write of a [`Muncher`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceEvent {
+    /// Whether it's a read or a write.
+    pub kind: TraceKind,
+    /// The name of the method (like `"read_m"` or `"write_pref_bytes_a"`).
+    pub op: &'static str,
+    /// Where the bytes start (in the input for reads, in the output for writes).
+    pub offset: u64,
+    /// The raw bytes read or written (the ones before the error, if it failed).
+    pub bytes: Vec<u8>,
+    /// The value read or written, if it can be shown
+    /// (see [`crate::Primitive::describe`]).
+    pub value: Option<String>,
+    /// The error, if it failed.
+    pub error: Option<String>,
+    /// The labels around it (see [`Muncher::trace_label`]) joined by `.`,
+    /// or empty if there are none.
+    pub label: String,
+}
+
+impl Display for TraceEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:08x} {} [{}",
+            self.offset,
+            self.op,
+            hex(&self.bytes, 16)
+        )?;
+        if self.bytes.len() > 16 {
+            write!(f, " ... ({} bytes)", self.bytes.len())?;
+        }
+        f.write_char(']')?;
+        if let Some(value) = &self.value {
+            write!(f, " = {value}")?;
+        }
+        if let Some(error) = &self.error {
+            write!(f, " failed: {error}")?;
+        }
+        if !self.label.is_empty() {
+            write!(f, " ({})", self.label)?;
+        }
+        Ok(())
+    }
+}
+
+/// Receives every [`TraceEvent`] as it happens (see [`Muncher::start_trace_to`]).
+///
+/// Implemented for closures taking a `&TraceEvent`.
+pub trait TraceSink: Send + Sync {
+    /// Called with each event once the read or write is done.
+    fn event(&mut self, event: &TraceEvent);
+}
+
+impl<F: FnMut(&TraceEvent) + Send + Sync> TraceSink for F {
+    fn event(&mut self, event: &TraceEvent) {
+        self(event);
+    }
+}
+
+/// A [`TraceSink`] that logs the events to the [`log`] facade,
+/// at the trace level with the target `bytemuncher` (crate feature: `log`).
+#[cfg(feature = "log")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LogSink;
+
+#[cfg(feature = "log")]
+impl TraceSink for LogSink {
+    fn event(&mut self, event: &TraceEvent) {
+        log::trace!(target: "bytemuncher", "{event}");
+    }
+}
+
+/// A [`TraceSink`] that emits the events to the [`tracing`] facade,
+/// at the trace level with the target `bytemuncher` (crate feature: `tracing`).
+#[cfg(feature = "tracing")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TracingSink;
+
+#[cfg(feature = "tracing")]
+impl TraceSink for TracingSink {
+    fn event(&mut self, event: &TraceEvent) {
+        tracing::trace!(
+            target: "bytemuncher",
+            kind = ?event.kind,
+            offset = event.offset,
+            bytes = %hex(&event.bytes, usize::MAX),
+            value = event.value.as_deref(),
+            error = event.error.as_deref(),
+            label = %event.label,
+            "{}",
+            event.op,
+        );
+    }
+}
+
+/// What was recorded by [`Muncher::start_trace`].
+#[derive(Debug, Clone, Default)]
+pub struct Trace {
+    /// Offset of the first byte of `read`.
+    read_base: u64,
+    read: Vec<u8>,
+    written: Vec<u8>,
+    events: Vec<TraceEvent>,
+}
+
+impl Trace {
+    /// Returns the events in the order they finished
+    /// (so a read made of other reads comes after them).
+    #[must_use]
+    pub fn events(&self) -> &[TraceEvent] {
+        &self.events
+    }
+
+    /// Returns all the bytes consumed from the input since the trace started.
+    #[must_use]
+    pub fn read_bytes(&self) -> &[u8] {
+        &self.read
+    }
+
+    /// Returns all the bytes written since the trace started.
+    #[must_use]
+    pub fn written_bytes(&self) -> &[u8] {
+        &self.written
+    }
+
+    /// Renders a hex dump of the consumed input (see [`Trace::read_bytes`]),
+    /// with each line annotated with the read that consumed it
+    /// (its label, method and value).
+    ///
+    /// Reads made of other traced reads show the inner ones,
+    /// and bytes consumed without a traced read
+    /// (like through [`std::io::Read`]) are marked as `(untraced)`.
+    #[must_use]
+    pub fn hex_dump(&self) -> String {
+        let mut reads: Vec<&TraceEvent> = self
+            .events
+            .iter()
+            .filter(|e| e.kind == TraceKind::Read)
+            .collect();
+        reads.sort_by_key(|e| (e.offset, std::cmp::Reverse(e.bytes.len())));
+
+        let mut out = String::new();
+        let mut cursor = self.read_base;
+        for (i, event) in reads.iter().enumerate() {
+            let end = event.offset + event.bytes.len() as u64;
+            // Sorted by offset, so any read inside this one comes right after it.
+            let is_leaf = reads.get(i + 1).is_none_or(|next| next.offset >= end);
+            if !is_leaf || event.offset < cursor {
+                continue;
+            }
+            if event.offset > cursor {
+                self.dump_lines(&mut out, cursor, event.offset, "(untraced)");
+            }
+            self.dump_lines(&mut out, event.offset, end, &annotation(event));
+            cursor = end;
+        }
+        let end = self.read_base + self.read.len() as u64;
+        if cursor < end {
+            self.dump_lines(&mut out, cursor, end, "(untraced)");
+        }
+        out
+    }
+
+    /// Writes the input bytes from `start` to `end` (offsets)
+    /// as lines of 16, with `note` on the first one.
+    fn dump_lines(&self, out: &mut String, start: u64, end: u64, note: &str) {
+        let from = (start - self.read_base) as usize;
+        let to = (end - self.read_base) as usize;
+        let bytes = self.read.get(from..to).unwrap_or_default();
+        let mut line = |offset: u64, bytes: &[u8], note: &str| {
+            let text = format!("{offset:08x}  {:47}  {note}", hex(bytes, 16));
+            out.push_str(text.trim_end());
+            out.push('\n');
+        };
+        if bytes.is_empty() {
+            line(start, &[], note);
+        }
+        for (i, chunk) in bytes.chunks(16).enumerate() {
+            line(start + i as u64 * 16, chunk, if i == 0 { note } else { "" });
+        }
+    }
+}
+
+fn annotation(event: &TraceEvent) -> String {
+    let mut note = String::new();
+    if !event.label.is_empty() {
+        _ = write!(note, "{}: ", event.label);
+    }
+    note.push_str(event.op);
+    if let Some(value) = &event.value {
+        _ = write!(note, " = {value}");
+    }
+    if let Some(error) = &event.error {
+        _ = write!(note, " failed: {error}");
+    }
+    note
+}
+
+/// Formats up to `max` bytes as space separated hex.
+fn hex(bytes: &[u8], max: usize) -> String {
+    let mut out = String::new();
+    for (i, b) in bytes.iter().take(max).enumerate() {
+        if i > 0 {
+            out.push(' ');
+        }
+        _ = write!(out, "{b:02x}");
+    }
+    out
+}
+
+/// The tracing state of a [`Muncher`].
+pub(crate) struct Tracer {
+    trace: Trace,
+    sink: Option<Box<dyn TraceSink>>,
+    labels: Vec<String>,
+    /// Inside a traced blocking method, so the methods it calls aren't traced.
+    busy: bool,
+}
+
+impl Tracer {
+    pub fn read(&mut self, bytes: &[u8]) {
+        self.trace.read.extend_from_slice(bytes);
+    }
+
+    pub fn written(&mut self, bytes: &[u8]) {
+        self.trace.written.extend_from_slice(bytes);
+    }
+
+    fn data(&self, kind: TraceKind) -> &[u8] {
+        match kind {
+            TraceKind::Read => &self.trace.read,
+            TraceKind::Write => &self.trace.written,
+        }
+    }
+
+    fn base(&self, kind: TraceKind) -> u64 {
+        match kind {
+            TraceKind::Read => self.trace.read_base,
+            TraceKind::Write => 0,
+        }
+    }
+}
+
+/// Where a traced method started (see [`Muncher::trace_start`]).
+pub(crate) struct TraceStart {
+    kind: TraceKind,
+    /// Index into the traced bytes.
+    index: usize,
+}
+
+/// A point to roll the trace back to (see [`Muncher::try_parse`]).
+pub(crate) struct TraceMark {
+    read: usize,
+    events: usize,
+}
+
+/// Adds a label to the traces of the reads and writes made through it,
+/// returned by [`Muncher::trace_label`].
+///
+/// It can be used just like the `Muncher` itself,
+/// and removes the label when dropped.
+pub struct TraceLabel<'a, T, O: ByteOrder = End> {
+    muncher: &'a mut Muncher<T, O>,
+    pushed: bool,
+}
+
+impl<T, O: ByteOrder> Deref for TraceLabel<'_, T, O> {
+    type Target = Muncher<T, O>;
+
+    fn deref(&self) -> &Self::Target {
+        self.muncher
+    }
+}
+
+impl<T, O: ByteOrder> DerefMut for TraceLabel<'_, T, O> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.muncher
+    }
+}
+
+impl<T, O: ByteOrder> Drop for TraceLabel<'_, T, O> {
+    fn drop(&mut self) {
+        if self.pushed
+            && let Some(tracer) = &mut self.muncher.observers.trace
+        {
+            tracer.labels.pop();
+        }
+    }
+}
+
+/// **Tracing**
+impl<T, O: ByteOrder> Muncher<T, O> {
+    /// Starts recording every read and write (the method, offset, raw bytes
+    /// and value), until [`Muncher::stop_trace`].
+    ///
+    /// This covers the read and write methods of the [`Muncher`]
+    /// (blocking and async). Bytes consumed in other ways
+    /// (like through [`std::io::Read`]) are recorded, but not as an event.
+    ///
+    /// The reads of a failed [`Muncher::try_parse`] (or a peek)
+    /// are removed from the trace when they're rolled back.
+    /// Offsets of reads count from [`Muncher::position`]
+    /// at the start of the trace, and offsets of writes from zero.
+    ///
+    /// Everything is kept in memory until the trace is stopped,
+    /// so this is meant for debugging.
+    ///
+    /// See [`crate::trace`] for an example.
+    pub fn start_trace(&mut self) {
+        self.observers.trace = Some(Box::new(Tracer {
+            trace: Trace {
+                read_base: self.position(),
+                ..Trace::default()
+            },
+            sink: None,
+            labels: Vec::new(),
+            busy: false,
+        }));
+    }
+
+    /// Like [`Muncher::start_trace`], but also gives every event to `sink`
+    /// as it happens (a closure, [`LogSink`], [`TracingSink`], ...).
+    ///
+    /// Unlike the recorded trace, the sink also gets
+    /// the events that are later rolled back.
+    pub fn start_trace_to(&mut self, sink: impl TraceSink + 'static) {
+        self.start_trace();
+        if let Some(tracer) = &mut self.observers.trace {
+            tracer.sink = Some(Box::new(sink));
+        }
+    }
+
+    /// Returns what has been recorded so far by [`Muncher::start_trace`].
+    #[must_use]
+    pub fn trace(&self) -> Option<&Trace> {
+        self.observers.trace.as_ref().map(|tracer| &tracer.trace)
+    }
+
+    /// Stops the trace started by [`Muncher::start_trace`],
+    /// and returns what it recorded.
+    pub fn stop_trace(&mut self) -> Option<Trace> {
+        self.observers.trace.take().map(|tracer| tracer.trace)
+    }
+
+    /// Labels the traced reads and writes made through the returned guard
+    /// (until it's dropped) with `label`, for example the name of the field
+    /// or structure being parsed. Labels can be nested (`header.size`).
+    ///
+    /// This does nothing unless a trace is in progress (see [`Muncher::start_trace`]).
+    pub fn trace_label(&mut self, label: &str) -> TraceLabel<'_, T, O> {
+        let pushed = if let Some(tracer) = &mut self.observers.trace {
+            tracer.labels.push(label.to_owned());
+            true
+        } else {
+            false
+        };
+        TraceLabel {
+            muncher: self,
+            pushed,
+        }
+    }
+
+    /// Whether a trace is in progress.
+    pub(crate) fn is_tracing(&self) -> bool {
+        self.observers.trace.is_some()
+    }
+
+    /// Runs the traced method `op`, recording it as an event
+    /// (with its value formatted by `describe`) if a trace is in progress.
+    pub(crate) fn traced<R, Err: Display>(
+        &mut self,
+        kind: TraceKind,
+        op: &'static str,
+        describe: impl FnOnce(&R) -> Option<String>,
+        run: impl FnOnce(&mut Self) -> Result<R, Err>,
+    ) -> Result<R, Err> {
+        let Some(start) = self.trace_start(kind) else {
+            return run(self);
+        };
+        if let Some(tracer) = &mut self.observers.trace {
+            tracer.busy = true;
+        }
+        let result = run(self);
+        if let Some(tracer) = &mut self.observers.trace {
+            tracer.busy = false;
+        }
+        let (value, error) = match &result {
+            Ok(value) => (describe(value), None),
+            Err(error) => (None, Some(error.to_string())),
+        };
+        self.trace_end(start, op, value, error);
+        result
+    }
+
+    /// Returns where a traced method starts,
+    /// or `None` if it shouldn't be traced.
+    pub(crate) fn trace_start(&self, kind: TraceKind) -> Option<TraceStart> {
+        let tracer = self.observers.trace.as_ref()?;
+        if tracer.busy {
+            return None;
+        }
+        Some(TraceStart {
+            kind,
+            index: tracer.data(kind).len(),
+        })
+    }
+
+    /// Records the event of a traced method that started at `start`.
+    pub(crate) fn trace_end(
+        &mut self,
+        start: TraceStart,
+        op: &'static str,
+        value: Option<String>,
+        error: Option<String>,
+    ) {
+        let Some(tracer) = &mut self.observers.trace else {
+            return;
+        };
+        let event = TraceEvent {
+            kind: start.kind,
+            op,
+            offset: tracer.base(start.kind) + start.index as u64,
+            bytes: tracer
+                .data(start.kind)
+                .get(start.index..)
+                .unwrap_or_default()
+                .to_vec(),
+            value,
+            error,
+            label: tracer.labels.join("."),
+        };
+        if let Some(sink) = &mut tracer.sink {
+            sink.event(&event);
+        }
+        tracer.trace.events.push(event);
+    }
+
+    /// Returns a point to roll the trace back to with [`Muncher::trace_rollback`].
+    pub(crate) fn trace_mark(&self) -> Option<TraceMark> {
+        let tracer = self.observers.trace.as_ref()?;
+        Some(TraceMark {
+            read: tracer.trace.read.len(),
+            events: tracer.trace.events.len(),
+        })
+    }
+
+    /// Removes the reads recorded since `mark`, when they're rolled back.
+    pub(crate) fn trace_rollback(&mut self, mark: Option<TraceMark>) {
+        if let (Some(tracer), Some(mark)) = (&mut self.observers.trace, mark) {
+            tracer.trace.read.truncate(mark.read);
+            tracer.trace.events.truncate(mark.events);
+        }
+    }
+}
+
+/// Formats `values` for a trace, if they can be.
+pub(crate) fn describe_all<E: Primitive>(values: &[E]) -> Option<String> {
+    const MAX: usize = 16;
+    let mut out = String::from("[");
+    for (i, value) in values.iter().take(MAX).enumerate() {
+        if i > 0 {
+            out.push_str(", ");
+        }
+        out.push_str(&value.describe()?);
+    }
+    if values.len() > MAX {
+        _ = write!(out, ", ... ({} values)", values.len());
+    }
+    out.push(']');
+    Some(out)
+}
+
+/// Formats bytes for a trace.
+pub(crate) fn describe_bytes(bytes: &[u8]) -> Option<String> {
+    Some(format!("b\"{}\"", bytes.escape_ascii()))
+}
+
+/// Formats a string for a trace.
+pub(crate) fn describe_str(s: &str) -> Option<String> {
+    Some(format!("{s:?}"))
+}
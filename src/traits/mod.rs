@@ -1,9 +1,10 @@
 use std::io::{Read, Write};
 
-use crate::{End, Muncher};
+use crate::trace::TraceKind;
+use crate::{ByteOrder, End, Muncher};
 
-mod float;
-mod int;
+mod number;
+mod slice;
 
 /// This trait allows you to specify your own primitive type
 /// that can be read through bytemuncher. It's implemented
@@ -12,6 +13,17 @@ mod int;
 ///
 /// Note: If your type is a single byte you can ignore the `end` field
 pub trait Primitive {
+    /// The size in bytes of every value of this type,
+    /// or `None` if it varies (the default).
+    ///
+    /// This lets the async and sans-IO readers ask for exactly the bytes
+    /// they need. Types without a fixed size are read one byte at a time,
+    /// retrying [`Primitive::read_endian`] until it stops failing with
+    /// [`std::io::ErrorKind::UnexpectedEof`]. Every retry decodes the value
+    /// from its first byte again, so that's quadratic in the size of the value:
+    /// fine for short encodings like varints, slow for long ones.
+    const SIZE: Option<usize> = None;
+
     fn read_endian(reader: &mut impl Read, end: End) -> Result<Self, std::io::Error>
     where
         Self: Sized;
@@ -41,39 +53,240 @@ pub trait Primitive {
     /// For an idea of how to use this, this will
     /// primarily be used for array length.
     fn from_usize(n: usize) -> Self;
+
+    /// Formats the value for a trace (see [`Muncher::start_trace`]).
+    ///
+    /// The default implementation returns `None`, so only the raw bytes are shown.
+    /// The built-in types show their value.
+    fn describe(&self) -> Option<String> {
+        None
+    }
+
+    /// Reads `out.len()` values into `out`.
+    ///
+    /// The default implementation reads the values one by one.
+    /// The built-in integer and float types instead read
+    /// the bytes in large chunks and convert them.
+    fn read_slice_endian(
+        reader: &mut impl Read,
+        out: &mut [Self],
+        end: End,
+    ) -> Result<(), std::io::Error>
+    where
+        Self: Sized,
+    {
+        for value in out {
+            *value = Self::read_endian(reader, end)?;
+        }
+        Ok(())
+    }
+
+    /// Writes all the `values`.
+    ///
+    /// The default implementation writes the values one by one.
+    /// The built-in integer and float types instead write
+    /// them in large chunks.
+    fn write_slice_endian(
+        values: &[Self],
+        writer: &mut impl Write,
+        end: End,
+    ) -> Result<(), std::io::Error>
+    where
+        Self: Sized + Clone,
+    {
+        for value in values {
+            value.clone().write_endian(writer, end)?;
+        }
+        Ok(())
+    }
+
+    /// Reads a value in the byte order `order` (see [`ByteOrder`]).
+    ///
+    /// The default implementation calls [`Primitive::read_endian`]
+    /// with `order.end()`. The built-in integer and float types instead
+    /// use [`ByteOrder::swaps`], so a fixed order (like [`crate::BigEndian`])
+    /// is decided at compile time.
+    fn read_order<B: ByteOrder>(reader: &mut impl Read, order: B) -> Result<Self, std::io::Error>
+    where
+        Self: Sized,
+    {
+        Self::read_endian(reader, order.end())
+    }
+
+    /// Writes the value in the byte order `order`
+    /// (see [`Primitive::read_order`]).
+    fn write_order<B: ByteOrder>(
+        self,
+        writer: &mut impl Write,
+        order: B,
+    ) -> Result<(), std::io::Error>
+    where
+        Self: Sized,
+    {
+        self.write_endian(writer, order.end())
+    }
+
+    /// Reads `out.len()` values into `out`, in the byte order `order`
+    /// (see [`Primitive::read_order`] and [`Primitive::read_slice_endian`]).
+    fn read_slice_order<B: ByteOrder>(
+        reader: &mut impl Read,
+        out: &mut [Self],
+        order: B,
+    ) -> Result<(), std::io::Error>
+    where
+        Self: Sized,
+    {
+        Self::read_slice_endian(reader, out, order.end())
+    }
+
+    /// Writes all the `values`, in the byte order `order`
+    /// (see [`Primitive::read_order`] and [`Primitive::write_slice_endian`]).
+    fn write_slice_order<B: ByteOrder>(
+        values: &[Self],
+        writer: &mut impl Write,
+        order: B,
+    ) -> Result<(), std::io::Error>
+    where
+        Self: Sized + Clone,
+    {
+        Self::write_slice_endian(values, writer, order.end())
+    }
 }
 
-#[cfg(feature = "futures")]
-use futures::io::{AsyncRead, AsyncWrite};
-#[cfg(feature = "tokio")]
-use tokio::io::{AsyncRead, AsyncWrite};
+#[cfg(any(feature = "tokio", feature = "futures"))]
+use crate::backend::{AsyncSink, AsyncSource, Backend};
+#[cfg(any(feature = "tokio", feature = "futures"))]
+use crate::sans_io::Value;
 
 /// Async equivalent of [`Primitive`] trait (see that for more info).
+///
+/// It's implemented for the integer and float types. For your own
+/// [`Primitive`] types, an empty `impl AsyncPrimitive for MyType {}`
+/// is enough: the default methods read the bytes asynchronously
+/// (as many as [`Primitive::SIZE`] says), then decode them from memory
+/// with the blocking methods.
+///
+/// The methods are generic over the async backend `B`
+/// (see [`crate::backend`]), so they work with both `tokio` and `futures`.
 #[cfg(any(feature = "tokio", feature = "futures"))]
 pub trait AsyncPrimitive: Primitive {
-    fn read_endian_a(
-        reader: &mut (impl AsyncRead + Unpin),
+    fn read_endian_a<B: Backend>(
+        reader: &mut impl AsyncSource<B>,
         end: End,
     ) -> impl Future<Output = std::io::Result<Self>>
     where
-        Self: Sized;
+        Self: Sized,
+    {
+        async move {
+            match Self::SIZE {
+                Some(size) if size <= STACK_BYTES => {
+                    let mut buf = [0; STACK_BYTES];
+                    reader.read_exact(&mut buf[..size]).await?;
+                    Self::read_endian(&mut &buf[..size], end)
+                }
+                _ => {
+                    crate::sans_io::drive_a(reader, Value::new(end), usize::MAX, |_, _| Ok(()))
+                        .await
+                }
+            }
+        }
+    }
 
-    fn write_endian_a(
+    fn write_endian_a<B: Backend>(
         self,
-        reader: &mut (impl AsyncWrite + Unpin),
+        writer: &mut impl AsyncSink<B>,
         end: End,
     ) -> impl Future<Output = std::io::Result<()>>
     where
-        Self: Sized;
+        Self: Sized,
+    {
+        async move {
+            if let Some(size) = Self::SIZE
+                && size <= STACK_BYTES
+            {
+                let mut buf = [0; STACK_BYTES];
+                self.write_endian(&mut &mut buf[..size], end)?;
+                return writer.write_all(&buf[..size]).await;
+            }
+            let mut bytes = Vec::new();
+            self.write_endian(&mut bytes, end)?;
+            writer.write_all(&bytes).await
+        }
+    }
+
+    fn read_slice_endian_a<B: Backend>(
+        reader: &mut impl AsyncSource<B>,
+        out: &mut [Self],
+        end: End,
+    ) -> impl Future<Output = std::io::Result<()>>
+    where
+        Self: Sized,
+    {
+        async move {
+            let Some(size) = Self::SIZE else {
+                for value in out {
+                    *value = Self::read_endian_a(reader, end).await?;
+                }
+                return Ok(());
+            };
+            let mut bytes = Vec::new();
+            for out in out.chunks_mut(ASYNC_CHUNK) {
+                bytes.resize(out.len() * size, 0);
+                reader.read_exact(&mut bytes).await?;
+                Self::read_slice_endian(&mut &bytes[..], out, end)?;
+            }
+            Ok(())
+        }
+    }
+
+    fn write_slice_endian_a<B: Backend>(
+        values: &[Self],
+        writer: &mut impl AsyncSink<B>,
+        end: End,
+    ) -> impl Future<Output = std::io::Result<()>>
+    where
+        Self: Sized + Clone,
+    {
+        async move {
+            let mut bytes = Vec::new();
+            for values in values.chunks(ASYNC_CHUNK) {
+                bytes.clear();
+                Self::write_slice_endian(values, &mut bytes, end)?;
+                writer.write_all(&bytes).await?;
+            }
+            Ok(())
+        }
+    }
 }
 
-impl<T: std::io::Read> Muncher<T> {
+/// How many values are encoded/decoded in memory at a time
+/// by the bulk async methods of [`AsyncPrimitive`].
+#[cfg(any(feature = "tokio", feature = "futures"))]
+const ASYNC_CHUNK: usize = 1024;
+
+/// The largest [`Primitive::SIZE`] that [`AsyncPrimitive`] encodes/decodes
+/// in a buffer on the stack (bigger ones go through a `Vec`).
+#[cfg(any(feature = "tokio", feature = "futures"))]
+const STACK_BYTES: usize = 16;
+
+impl<T: std::io::Read, O: ByteOrder> Muncher<T, O> {
     /// Reads any [`crate::Primitive`] type (such as integers or floats),
     /// with the endianness specified in the `end` argument.
     ///
     /// For more info on endianness see [`crate::End`].
     pub fn read_m<E: Primitive>(&mut self, end: End) -> std::io::Result<E> {
-        E::read_endian(&mut self.inner, end)
+        self.read_order(end)
+    }
+
+    /// Reads any [`crate::Primitive`] type (such as integers or floats),
+    /// with the default endianness of the [`Muncher`] (see [`Muncher::set_end`]).
+    ///
+    /// Note: This takes priority over [`std::io::Read::read`] when calling
+    /// `muncher.read(...)`. To use that, call `Read::read(&mut muncher, buf)`.
+    ///
+    /// For more info on endianness see [`crate::End`].
+    pub fn read<E: Primitive>(&mut self) -> std::io::Result<E> {
+        self.read_order(self.order)
     }
 
     /// Reads any [`crate::Primitive`] type (such as integers or floats),
@@ -99,15 +312,33 @@ impl<T: std::io::Read> Muncher<T> {
     pub fn read_ne<E: Primitive>(&mut self) -> std::io::Result<E> {
         self.read_m(End::Native)
     }
+
+    /// Reads a value in any [`ByteOrder`] (traced as [`Muncher::read_m`]).
+    pub(crate) fn read_order<E: Primitive>(&mut self, order: impl ByteOrder) -> std::io::Result<E> {
+        self.traced(TraceKind::Read, "read_m", E::describe, |m| {
+            E::read_order(m, order)
+        })
+    }
 }
 
-impl<T: Write> Muncher<T> {
+impl<T: Write, O: ByteOrder> Muncher<T, O> {
     /// Writes any [`crate::Primitive`] type (such as integers or floats),
     /// with the endianness specified in the `end` argument.
     ///
     /// For more info on endianness see [`crate::End`].
     pub fn write_m<E: Primitive>(&mut self, value: E, end: End) -> std::io::Result<()> {
-        value.write_endian(&mut self.inner, end)
+        self.write_order(value, end)
+    }
+
+    /// Writes any [`crate::Primitive`] type (such as integers or floats),
+    /// with the default endianness of the [`Muncher`] (see [`Muncher::set_end`]).
+    ///
+    /// Note: This takes priority over [`std::io::Write::write`] when calling
+    /// `muncher.write(...)`. To use that, call `Write::write(&mut muncher, buf)`.
+    ///
+    /// For more info on endianness see [`crate::End`].
+    pub fn write<E: Primitive>(&mut self, value: E) -> std::io::Result<()> {
+        self.write_order(value, self.order)
     }
 
     /// Writes any [`crate::Primitive`] type (such as integers or floats),
@@ -133,78 +364,19 @@ impl<T: Write> Muncher<T> {
     pub fn write_le<E: Primitive>(&mut self, value: E) -> std::io::Result<()> {
         self.write_m(value, End::Little)
     }
-}
-
-#[cfg(any(feature = "tokio", feature = "futures"))]
-impl<T: AsyncRead + Unpin> Muncher<T> {
-    /// Reads any [`crate::AsyncPrimitive`] type (such as integers or floats),
-    /// with the endianness specified in the `end` argument.
-    ///
-    /// For more info on endianness see [`crate::End`].
-    pub async fn read_m_a<E: AsyncPrimitive>(&mut self, end: End) -> std::io::Result<E> {
-        E::read_endian_a(&mut self.inner, end).await
-    }
-
-    /// Reads any [`crate::AsyncPrimitive`] type (such as integers or floats),
-    /// as little endian.
-    ///
-    /// For more info on endianness see [`crate::End`].
-    pub async fn read_le_a<E: AsyncPrimitive>(&mut self) -> std::io::Result<E> {
-        self.read_m_a(End::Little).await
-    }
-
-    /// Reads any [`crate::AsyncPrimitive`] type (such as integers or floats),
-    /// as big endian.
-    ///
-    /// For more info on endianness see [`crate::End`].
-    pub async fn read_be_a<E: AsyncPrimitive>(&mut self) -> std::io::Result<E> {
-        self.read_m_a(End::Big).await
-    }
-
-    /// Reads any [`crate::AsyncPrimitive`] type (such as integers or floats),
-    /// as native endian (as per the target platform).
-    ///
-    /// For more info on endianness see [`crate::End`].
-    pub async fn read_ne_a<E: AsyncPrimitive>(&mut self) -> std::io::Result<E> {
-        self.read_m_a(End::Native).await
-    }
-}
 
-#[cfg(any(feature = "tokio", feature = "futures"))]
-impl<T: AsyncWrite + Unpin> Muncher<T> {
-    /// Writes any [`crate::AsyncPrimitive`] type (such as integers or floats),
-    /// with the endianness specified in the `end` argument.
-    ///
-    /// For more info on endianness see [`crate::End`].
-    pub async fn write_m_a<E: AsyncPrimitive>(
+    /// Writes a value in any [`ByteOrder`] (traced as [`Muncher::write_m`]).
+    pub(crate) fn write_order<E: Primitive>(
         &mut self,
         value: E,
-        end: End,
+        order: impl ByteOrder,
     ) -> std::io::Result<()> {
-        value.write_endian_a(&mut self.inner, end).await
-    }
-
-    /// Writes any [`crate::AsyncPrimitive`] type (such as integers or floats),
-    /// as native endian (as per the target platform).
-    ///
-    /// For more info on endianness see [`crate::End`].
-    pub async fn write_ne_a<E: AsyncPrimitive>(&mut self, value: E) -> std::io::Result<()> {
-        self.write_m_a(value, End::Native).await
-    }
-
-    /// Writes any [`crate::AsyncPrimitive`] type (such as integers or floats),
-    /// as big endian.
-    ///
-    /// For more info on endianness see [`crate::End`].
-    pub async fn write_be_a<E: AsyncPrimitive>(&mut self, value: E) -> std::io::Result<()> {
-        self.write_m_a(value, End::Big).await
-    }
-
-    /// Writes any [`crate::AsyncPrimitive`] type (such as integers or floats),
-    /// as little endian.
-    ///
-    /// For more info on endianness see [`crate::End`].
-    pub async fn write_le_a<E: AsyncPrimitive>(&mut self, value: E) -> std::io::Result<()> {
-        self.write_m_a(value, End::Little).await
+        let described = self.is_tracing().then(|| value.describe()).flatten();
+        self.traced(
+            TraceKind::Write,
+            "write_m",
+            |()| described,
+            |m| value.write_order(m, order),
+        )
     }
 }
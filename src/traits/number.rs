@@ -0,0 +1,127 @@
+use super::Primitive;
+use super::slice::Bytewise;
+use crate::{ByteOrder, End};
+
+/// Implements [`Primitive`] for a number type with `from_le_bytes`,
+/// `to_le_bytes` and friends (all the integers and floats).
+macro_rules! impl_number {
+    ($($type:ty),*) => {$(
+        impl Primitive for $type {
+            const SIZE: Option<usize> = Some(std::mem::size_of::<Self>());
+
+            fn read_endian(
+                reader: &mut impl std::io::Read,
+                end: End,
+            ) -> Result<Self, std::io::Error> {
+                Self::read_order(reader, end)
+            }
+
+            fn write_endian(
+                self,
+                writer: &mut impl std::io::Write,
+                end: End,
+            ) -> Result<(), std::io::Error> {
+                self.write_order(writer, end)
+            }
+
+            fn read_order<B: ByteOrder>(
+                reader: &mut impl std::io::Read,
+                order: B,
+            ) -> Result<Self, std::io::Error> {
+                let mut buf = [0u8; std::mem::size_of::<Self>()];
+                reader.read_exact(&mut buf)?;
+                Ok(Self::from_bytes(&buf, order))
+            }
+
+            fn write_order<B: ByteOrder>(
+                self,
+                writer: &mut impl std::io::Write,
+                order: B,
+            ) -> Result<(), std::io::Error> {
+                let mut buf = [0u8; std::mem::size_of::<Self>()];
+                self.to_bytes(&mut buf, order);
+                writer.write_all(&buf)
+            }
+
+            #[allow(clippy::cast_sign_loss)]
+            #[allow(clippy::cast_possible_truncation)]
+            fn into_usize(self) -> usize {
+                self as usize
+            }
+
+            fn from_usize(n: usize) -> Self {
+                n as Self
+            }
+
+            fn describe(&self) -> Option<String> {
+                Some(self.to_string())
+            }
+
+            fn read_slice_endian(
+                reader: &mut impl std::io::Read,
+                out: &mut [Self],
+                end: End,
+            ) -> Result<(), std::io::Error> {
+                super::slice::read_bytewise(reader, out, end)
+            }
+
+            fn write_slice_endian(
+                values: &[Self],
+                writer: &mut impl std::io::Write,
+                end: End,
+            ) -> Result<(), std::io::Error> {
+                super::slice::write_bytewise(writer, values, end)
+            }
+
+            fn read_slice_order<B: ByteOrder>(
+                reader: &mut impl std::io::Read,
+                out: &mut [Self],
+                order: B,
+            ) -> Result<(), std::io::Error> {
+                super::slice::read_bytewise(reader, out, order)
+            }
+
+            fn write_slice_order<B: ByteOrder>(
+                values: &[Self],
+                writer: &mut impl std::io::Write,
+                order: B,
+            ) -> Result<(), std::io::Error> {
+                super::slice::write_bytewise(writer, values, order)
+            }
+        }
+
+        impl Bytewise for $type {
+            const SIZE: usize = std::mem::size_of::<Self>();
+
+            fn from_native(bytes: &[u8]) -> Self {
+                Self::from_ne_bytes(bytes.try_into().expect("chunks have the size of the type"))
+            }
+
+            fn to_native(self, out: &mut [u8]) {
+                out.copy_from_slice(&self.to_ne_bytes());
+            }
+
+            fn swapped(self) -> Self {
+                let mut bytes = self.to_ne_bytes();
+                bytes.reverse();
+                Self::from_ne_bytes(bytes)
+            }
+        }
+
+        #[cfg(any(feature = "tokio", feature = "futures"))]
+        impl super::AsyncPrimitive for $type {}
+    )*};
+}
+
+// Single bytes go through the same code,
+// endianness just doesn't change anything for them.
+impl_number!(u8, i8);
+impl_number!(u16, i16, u32, i32, u64, i64, u128, i128);
+impl_number!(f32, f64);
+
+// Not implemented, because if you're parsing binary formats
+// you better know the type size beforehand!
+// impl_number!(usize, isize);
+
+// Not stable yet:
+// impl_number!(f16, f128);
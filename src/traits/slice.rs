@@ -0,0 +1,101 @@
+//! Bulk reading/writing of the built-in primitive types,
+//! converting them from and to bytes a chunk at a time.
+
+use std::io::{Read, Write};
+
+use crate::ByteOrder;
+
+/// How many bytes are converted at a time
+/// (in a buffer on the stack).
+const CHUNK: usize = 4096;
+
+/// Number types with a fixed size, that convert from and to bytes.
+pub(crate) trait Bytewise: Copy {
+    const SIZE: usize;
+
+    /// Converts `SIZE` bytes in the target's byte order into a value.
+    fn from_native(bytes: &[u8]) -> Self;
+
+    /// Converts the value into `SIZE` bytes in the target's byte order.
+    fn to_native(self, out: &mut [u8]);
+
+    /// Reverses the byte order of the value.
+    #[must_use]
+    fn swapped(self) -> Self;
+
+    /// Converts `SIZE` bytes in the byte order `order` into a value.
+    fn from_bytes(bytes: &[u8], order: impl ByteOrder) -> Self {
+        let value = Self::from_native(bytes);
+        if order.swaps() {
+            value.swapped()
+        } else {
+            value
+        }
+    }
+
+    /// Converts the value into `SIZE` bytes in the byte order `order`.
+    fn to_bytes(self, out: &mut [u8], order: impl ByteOrder) {
+        let value = if order.swaps() { self.swapped() } else { self };
+        value.to_native(out);
+    }
+}
+
+pub(crate) fn read_bytewise<P: Bytewise>(
+    reader: &mut impl Read,
+    out: &mut [P],
+    order: impl ByteOrder,
+) -> std::io::Result<()> {
+    if order.swaps() {
+        read_chunks::<P, true>(reader, out)
+    } else {
+        read_chunks::<P, false>(reader, out)
+    }
+}
+
+pub(crate) fn write_bytewise<P: Bytewise>(
+    writer: &mut impl Write,
+    values: &[P],
+    order: impl ByteOrder,
+) -> std::io::Result<()> {
+    if order.swaps() {
+        write_chunks::<P, true>(writer, values)
+    } else {
+        write_chunks::<P, false>(writer, values)
+    }
+}
+
+/// Reads `out` a chunk at a time, swapping the bytes of
+/// every value only if `SWAP` (so a plain copy otherwise).
+fn read_chunks<P: Bytewise, const SWAP: bool>(
+    reader: &mut impl Read,
+    out: &mut [P],
+) -> std::io::Result<()> {
+    let mut buf = [0u8; CHUNK];
+    for values in out.chunks_mut(CHUNK / P::SIZE) {
+        let bytes = &mut buf[..values.len() * P::SIZE];
+        reader.read_exact(bytes)?;
+        for (value, bytes) in values.iter_mut().zip(bytes.chunks_exact(P::SIZE)) {
+            let native = P::from_native(bytes);
+            *value = if SWAP { native.swapped() } else { native };
+        }
+    }
+    Ok(())
+}
+
+/// Writes `values` a chunk at a time, swapping the bytes of
+/// every value only if `SWAP` (so a plain copy otherwise).
+fn write_chunks<P: Bytewise, const SWAP: bool>(
+    writer: &mut impl Write,
+    values: &[P],
+) -> std::io::Result<()> {
+    let mut buf = [0u8; CHUNK];
+    for values in values.chunks(CHUNK / P::SIZE) {
+        let bytes = &mut buf[..values.len() * P::SIZE];
+        for (value, bytes) in values.iter().zip(bytes.chunks_exact_mut(P::SIZE)) {
+            let value = if SWAP { value.swapped() } else { *value };
+            value.to_native(bytes);
+        }
+        writer.write_all(bytes)?;
+    }
+    Ok(())
+}
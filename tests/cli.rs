@@ -0,0 +1,95 @@
+//! Runs the `bytemuncher` binary on the files in `tests/fixtures`.
+
+use std::path::Path;
+use std::process::{Command, Output};
+
+fn run(args: &[&str]) -> Output {
+    let fixtures = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+    Command::new(env!("CARGO_BIN_EXE_bytemuncher"))
+        .current_dir(fixtures)
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+fn stdout(output: &Output) -> &str {
+    std::str::from_utf8(&output.stdout).unwrap()
+}
+
+fn stderr(output: &Output) -> &str {
+    std::str::from_utf8(&output.stderr).unwrap()
+}
+
+#[test]
+fn test_schema() {
+    let output = run(&["--schema", "header.schema", "header.bin"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(
+        stdout(&output),
+        "\
+00000000      4  magic: b\"DEMO\"
+00000004      2  version: 2
+00000006      6  name: \"hello\"
+0000000c      1  count: 2
+0000000d      2  points.x: -1
+0000000f      2  points.y: 2
+00000011      2  points.x: 3
+00000013      2  points.y: 4
+"
+    );
+    assert_eq!(stderr(&output), "2 bytes left\n");
+}
+
+#[test]
+fn test_schema_json() {
+    let output = run(&["--schema", "header.schema", "--json", "header.bin"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(
+        stdout(&output),
+        r#"{"magic":[68,69,77,79],"version":2,"name":"hello","count":2,"points":[{"x":-1,"y":2},{"x":3,"y":4}]}"#
+            .to_owned()
+            + "\n"
+    );
+}
+
+#[test]
+fn test_specs_at_offset() {
+    let output = run(&[
+        "--offset",
+        "0x4",
+        "header.bin",
+        "u16be version,",
+        "pref_u8_utf8 name",
+    ]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(
+        stdout(&output),
+        "\
+00000004      2  version: 2
+00000006      6  name: \"hello\"
+"
+    );
+    assert_eq!(stderr(&output), "11 bytes left\n");
+}
+
+#[test]
+fn test_truncated_input() {
+    let output = run(&["header.bin", "u64be a, u64be b, u64be c"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stdout(&output).ends_with("00000010      7  c: 02 00 03 00 04 aa bb\n"));
+    assert!(stderr(&output).starts_with("error: c: "));
+}
+
+#[test]
+fn test_usage_errors() {
+    let output = run(&[]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(stderr(&output).starts_with("error: missing the file to decode"));
+
+    let output = run(&["--schema", "header.schema", "header.bin", "u8 extra"]);
+    assert_eq!(output.status.code(), Some(2));
+
+    let output = run(&["--help"]);
+    assert!(output.status.success());
+    assert!(stdout(&output).starts_with("Usage: bytemuncher"));
+}